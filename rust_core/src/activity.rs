@@ -1,10 +1,26 @@
-use serde::{Deserialize, Serialize};
-use rand::{Rng, SeedableRng};
-use rand::rngs::SmallRng;
+use crate::device::{DeviceProfile, DeviceUsage};
+use crate::drift::{DriftConfig, InterestGraph};
+use crate::interests::{GeneratedUrl, InterestUrlGenerator};
+use crate::profile::{ActivityLevel, BrowsingStyle, InterestCategory, Profile};
+use crate::rng::{FastRng, LiveRng};
+use crate::safety::SafetyPolicy;
+use crate::taxonomy::{Subtopic, Taxonomy};
+use chrono::{DateTime, NaiveDate};
+use rand::distributions::{Distribution, WeightedIndex};
 use rand::seq::SliceRandom;
-use rand_distr::{Distribution, Poisson};
-use crate::profile::{Profile, ActivityLevel, BrowsingStyle, InterestCategory};
-use crate::interests::InterestUrlGenerator;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+/// Idle time after which the next activity starts a new session
+const SESSION_GAP_SECONDS: i64 = 30 * 60;
+
+/// Longest a session runs before the next activity starts a new one,
+/// possibly on another device, even without an idle gap; the interest
+/// carries over
+const MAX_SESSION_SECONDS: i64 = 2 * 3600;
+
+/// Share of activities narrowed to one of the profile's subtopics
+const SUBTOPIC_SHARE: f64 = 0.7;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BrowsingActivity {
@@ -14,9 +30,23 @@ pub struct BrowsingActivity {
     pub duration_seconds: u32,
     pub timestamp: i64,
     pub interest_category: Option<InterestCategory>,
+    /// Whether the safety policy allows actually loading `url`
+    #[serde(default)]
+    pub safe_to_load: bool,
+    /// Injected by the sensitive-topic cover mode rather than drawn from the profile
+    #[serde(default)]
+    pub sensitive_cover: bool,
+    /// Id of the [`Device`](crate::Device) in the profile's
+    /// [`DeviceProfile`] the activity came from
+    #[serde(default)]
+    pub device_id: Option<String>,
+    /// Id of the [`Profile`] browsing, which on a
+    /// [`SharedHousehold`](crate::SharedHousehold) device may be any member
+    #[serde(default)]
+    pub profile_id: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum ActivityType {
     Search,
     PageVisit,
@@ -27,48 +57,210 @@ pub enum ActivityType {
     Research,
 }
 
-pub struct ActivitySimulator {
+impl ActivityType {
+    pub const ALL: [ActivityType; 7] = [
+        ActivityType::Search,
+        ActivityType::PageVisit,
+        ActivityType::VideoWatch,
+        ActivityType::Shopping,
+        ActivityType::SocialMedia,
+        ActivityType::News,
+        ActivityType::Research,
+    ];
+}
+
+/// Opt-in mode that mixes sensitive-topic decoys into the profile's traffic
+///
+/// Gives plausible deniability for real medical, legal or immigration
+/// research: when such visits turn up in a log, they no longer stand out.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SensitiveCover {
+    /// Fraction of activities, between 0 and 1, replaced by a sensitive decoy
+    pub rate: f64,
+    /// Topics to draw from; all of [`InterestCategory::SENSITIVE`] by default
+    pub topics: Vec<InterestCategory>,
+}
+
+impl Default for SensitiveCover {
+    fn default() -> Self {
+        Self {
+            rate: 0.1,
+            topics: InterestCategory::SENSITIVE.to_vec(),
+        }
+    }
+}
+
+/// Where a browsing session has drifted to so far
+#[derive(Debug, Default)]
+struct Session {
+    interest: Option<InterestCategory>,
+    /// Consecutive activities spent outside the profile's interests
+    hops_outside: u32,
+    /// The device the session is browsed on
+    device_id: Option<String>,
+    usage: Option<&'static DeviceUsage>,
+}
+
+/// Simulates a profile's browsing, drawing from `R`
+pub struct ActivitySimulator<R = FastRng> {
     profile: Profile,
-    rng: SmallRng,
+    rng: R,
     url_generator: InterestUrlGenerator,
+    sensitive_cover: Option<SensitiveCover>,
+    drift: DriftConfig,
+    devices: DeviceProfile,
 }
 
 impl ActivitySimulator {
     pub fn new(profile: Profile) -> Self {
+        Self::from_rng(profile, FastRng::from_entropy())
+    }
+}
+
+impl ActivitySimulator<LiveRng> {
+    /// A simulator whose upcoming activities can't be predicted from the
+    /// ones an observer has already seen
+    pub fn live(profile: Profile) -> Self {
+        Self::from_rng(profile, LiveRng::from_entropy())
+    }
+}
+
+impl<R: Rng> ActivitySimulator<R> {
+    pub fn from_rng(profile: Profile, rng: R) -> Self {
         Self {
-            profile: profile.clone(),
-            rng: SmallRng::from_entropy(),
-            url_generator: InterestUrlGenerator::new(),
+            url_generator: InterestUrlGenerator::for_locale(&profile.demographics.locale),
+            devices: DeviceProfile::from_profile(&profile),
+            profile,
+            rng,
+            sensitive_cover: None,
+            drift: DriftConfig::default(),
         }
     }
 
-    /// Generate activities for a given duration in hours
+    /// Draw activities from a fixed seed, such as a
+    /// [`PersonaSeed`](crate::PersonaSeed), instead of from entropy
+    pub fn with_seed(mut self, seed: u64) -> Self
+    where
+        R: SeedableRng,
+    {
+        self.rng = R::seed_from_u64(seed);
+        self
+    }
+
+    /// Use a URL generator that has been seeded with ingested content
+    pub fn with_url_generator(mut self, url_generator: InterestUrlGenerator) -> Self {
+        self.url_generator = url_generator;
+        self
+    }
+
+    /// Apply a safety policy to every generated URL
+    pub fn with_policy(mut self, policy: SafetyPolicy) -> Self {
+        self.url_generator = self.url_generator.with_policy(policy);
+        self
+    }
+
+    /// Turn on sensitive-topic cover traffic
+    ///
+    /// Topics that aren't sensitive are ignored, so the mode can never
+    /// change which everyday interests the profile shows.
+    pub fn with_sensitive_cover(mut self, mut cover: SensitiveCover) -> Self {
+        cover.rate = cover.rate.clamp(0.0, 1.0);
+        cover.topics.retain(InterestCategory::is_sensitive);
+        self.sensitive_cover = Some(cover).filter(|cover| !cover.topics.is_empty());
+        self
+    }
+
+    /// Tune how interests drift within a session
+    pub fn with_drift(mut self, drift: DriftConfig) -> Self {
+        self.drift = drift;
+        self
+    }
+
+    /// Browse on these devices instead of the ones derived from the profile
+    pub fn with_devices(mut self, devices: DeviceProfile) -> Self {
+        self.devices = devices;
+        self
+    }
+
+    /// The devices activities are stamped with
+    pub fn devices(&self) -> &DeviceProfile {
+        &self.devices
+    }
+
+    pub fn profile(&self) -> &Profile {
+        &self.profile
+    }
+
+    /// Generate activities for a given duration in hours, starting now
     pub fn generate_activities(&mut self, duration_hours: u32) -> Vec<BrowsingActivity> {
-        let base_time = chrono::Utc::now().timestamp();
-        let mut activities = Vec::new();
+        self.generate_activities_from(chrono::Utc::now().timestamp(), duration_hours)
+    }
 
+    /// Generate activities for a given duration in hours from a Unix
+    /// timestamp
+    ///
+    /// The activity level sets how many there are; each lands in an hour
+    /// picked in proportion to how much the devices get used then, local
+    /// time, so nights are quiet and the evenings busy.
+    pub fn generate_activities_from(
+        &mut self,
+        start: i64,
+        duration_hours: u32,
+    ) -> Vec<BrowsingActivity> {
         // Calculate activities per hour based on activity level
         let activities_per_hour = self.get_activities_per_hour();
-
         let total_activities = (duration_hours as f64 * activities_per_hour) as usize;
 
-        for i in 0..total_activities {
-            // Distribute activities across the time period with realistic clustering
-            let hour_offset = (i as f64 / activities_per_hour) as i64 * 3600;
-            let minute_jitter = self.rng.gen_range(0..3600);
-            let timestamp = base_time + hour_offset + minute_jitter;
+        let hours: Vec<f64> = (0..duration_hours as i64)
+            .map(|hour| self.devices.intensity_at(start + hour * 3600 + 1800))
+            .collect();
+        let Ok(hour) = WeightedIndex::new(&hours) else {
+            return Vec::new();
+        };
+        let mut timestamps: Vec<i64> = (0..total_activities)
+            .map(|_| start + hour.sample(&mut self.rng) as i64 * 3600 + self.rng.gen_range(0..3600))
+            .collect();
+        timestamps.sort();
+        self.generate_at(&timestamps)
+    }
 
-            let activity = self.generate_single_activity(timestamp);
-            activities.push(activity);
-        }
+    /// Generate an activity at each of `timestamps`, which must be sorted
+    pub(crate) fn generate_at(&mut self, timestamps: &[i64]) -> Vec<BrowsingActivity> {
+        let mut activities = Vec::new();
 
-        // Sort by timestamp
-        activities.sort_by_key(|a| a.timestamp);
+        // Walk forward in time so interests can drift within each session
+        let mut session = Session::default();
+        let mut previous = None;
+        let mut started = 0;
+        for &timestamp in timestamps {
+            let idle = previous.is_none_or(|p| timestamp - p > SESSION_GAP_SECONDS);
+            if idle || timestamp - started > MAX_SESSION_SECONDS {
+                started = timestamp;
+                // Without a break, the interest carries over to the next device
+                let carried = if idle {
+                    Session::default()
+                } else {
+                    std::mem::take(&mut session)
+                };
+                let device = self.devices.choose(timestamp, &mut self.rng);
+                session = Session {
+                    device_id: device.map(|device| device.id.clone()),
+                    usage: device.map(|device| device.form_factor.usage()),
+                    ..carried
+                };
+            }
+            previous = Some(timestamp);
+
+            if let Some(activity) = self.generate_single_activity(timestamp, &mut session) {
+                activities.push(activity);
+            }
+        }
 
         activities
     }
 
-    fn get_activities_per_hour(&self) -> f64 {
+    pub(crate) fn get_activities_per_hour(&self) -> f64 {
         match self.profile.activity_level {
             ActivityLevel::Low => 1.5,
             ActivityLevel::Medium => 4.0,
@@ -77,76 +269,287 @@ impl ActivitySimulator {
         }
     }
 
-    fn generate_single_activity(&mut self, timestamp: i64) -> BrowsingActivity {
-        // Pick an interest category
-        let interest = self.profile.interests.choose(&mut self.rng).cloned();
+    fn generate_single_activity(
+        &mut self,
+        timestamp: i64,
+        session: &mut Session,
+    ) -> Option<BrowsingActivity> {
+        // Pick an interest category, or a sensitive decoy topic that leaves
+        // the session where it was
+        let decoy = self.choose_sensitive_topic();
+        let sensitive_cover = decoy.is_some();
+        let interest = decoy.or_else(|| self.drift_interest(session));
+        let subtopic = if sensitive_cover {
+            None
+        } else {
+            self.choose_subtopic(&interest)
+        };
 
         // Determine activity type based on browsing style and interest
-        let activity_type = self.choose_activity_type(&interest);
+        let activity_type = if sensitive_cover {
+            self.choose_sensitive_activity_type()
+        } else {
+            self.choose_activity_type(session.usage)
+        };
 
-        // Generate URL and title
-        let (url, title) = self.url_generator.generate_url(&activity_type, &interest, &mut self.rng);
+        // Generate URL and title, dated as of the activity, regenerating
+        // rather than dropping the activity when the safety policy rules
+        // out the first pick
+        let today = DateTime::from_timestamp(timestamp, 0)
+            .unwrap_or_default()
+            .date_naive();
+        let (generated, activity_type, interest) =
+            self.generate_allowed(activity_type, interest, subtopic, sensitive_cover, today)?;
+        if !sensitive_cover {
+            session.interest = interest.clone();
+        }
 
         // Generate realistic duration
-        let duration_seconds = self.generate_duration(&activity_type);
+        let duration_seconds = self.generate_duration(&activity_type, session.usage);
 
-        BrowsingActivity {
+        Some(BrowsingActivity {
             activity_type,
-            url,
-            title,
+            url: generated.url,
+            title: generated.title,
             duration_seconds,
             timestamp,
             interest_category: interest,
-        }
+            safe_to_load: generated.safe_to_load,
+            sensitive_cover,
+            device_id: session.device_id.clone(),
+            profile_id: Some(self.profile.id.clone()),
+        })
     }
 
-    fn choose_activity_type(&mut self, interest: &Option<InterestCategory>) -> ActivityType {
-        match &self.profile.browsing_style {
-            BrowsingStyle::Researcher => {
-                // Researchers do more searches and research
-                match self.rng.gen_range(0..=100) {
-                    0..=40 => ActivityType::Search,
-                    41..=75 => ActivityType::Research,
-                    76..=85 => ActivityType::PageVisit,
-                    86..=92 => ActivityType::News,
-                    _ => ActivityType::VideoWatch,
-                }
+    /// A URL the safety policy allows, so that filtering never thins out
+    /// the traffic
+    ///
+    /// Falls back to other activity types, then to the interest without its
+    /// subtopic, then to the profile's other interests (or, for a decoy, the
+    /// other cover topics). `None` only if the policy denies all of them.
+    fn generate_allowed(
+        &mut self,
+        activity_type: ActivityType,
+        interest: Option<InterestCategory>,
+        subtopic: Option<&'static Subtopic>,
+        sensitive_cover: bool,
+        today: NaiveDate,
+    ) -> Option<(GeneratedUrl, ActivityType, Option<InterestCategory>)> {
+        if let Some((generated, activity_type)) =
+            self.generate_any_type(activity_type.clone(), &interest, subtopic, today)
+        {
+            return Some((generated, activity_type, interest));
+        }
+        if subtopic.is_some() {
+            if let Some((generated, activity_type)) =
+                self.generate_any_type(activity_type.clone(), &interest, None, today)
+            {
+                return Some((generated, activity_type, interest));
             }
-            BrowsingStyle::Focused => {
-                // Focused users spend more time on fewer pages
-                match self.rng.gen_range(0..=100) {
-                    0..=60 => ActivityType::PageVisit,
-                    61..=75 => ActivityType::Research,
-                    76..=85 => ActivityType::Search,
-                    _ => ActivityType::News,
-                }
+        }
+
+        let mut others: Vec<InterestCategory> = if sensitive_cover {
+            self.sensitive_cover
+                .iter()
+                .flat_map(|cover| cover.topics.clone())
+                .collect()
+        } else {
+            self.profile.categories().cloned().collect()
+        };
+        others.retain(|other| interest.as_ref() != Some(other));
+        others.shuffle(&mut self.rng);
+        others.into_iter().find_map(|other| {
+            let other = Some(other);
+            let (generated, activity_type) =
+                self.generate_any_type(activity_type.clone(), &other, None, today)?;
+            Some((generated, activity_type, other))
+        })
+    }
+
+    /// A URL of the preferred activity type, or else of any other
+    fn generate_any_type(
+        &mut self,
+        preferred: ActivityType,
+        interest: &Option<InterestCategory>,
+        subtopic: Option<&Subtopic>,
+        today: NaiveDate,
+    ) -> Option<(GeneratedUrl, ActivityType)> {
+        if let Some(generated) =
+            self.url_generator
+                .generate_url(&preferred, interest, subtopic, today, &mut self.rng)
+        {
+            return Some((generated, preferred));
+        }
+        let mut fallbacks = ActivityType::ALL.to_vec();
+        fallbacks.shuffle(&mut self.rng);
+        fallbacks.into_iter().find_map(|fallback| {
+            let generated = self.url_generator.generate_url(
+                &fallback,
+                interest,
+                subtopic,
+                today,
+                &mut self.rng,
+            )?;
+            Some((generated, fallback))
+        })
+    }
+
+    /// Next interest in a session: stay, start fresh from the profile's
+    /// affinities, or move to a related one
+    ///
+    /// Related interests the profile declares are weighted by edge weight
+    /// and affinity; others by edge weight and `outside_weight`, until the
+    /// session has spent `max_hops_outside` activities outside the profile.
+    fn drift_interest(&mut self, session: &mut Session) -> Option<InterestCategory> {
+        let next = match session.interest.clone() {
+            Some(current)
+                if self
+                    .rng
+                    .gen_bool(self.drift.stay_probability.clamp(0.0, 1.0)) =>
+            {
+                Some(current)
             }
-            BrowsingStyle::Explorer => {
-                // Explorers hit many different types
-                match self.rng.gen_range(0..=100) {
-                    0..=30 => ActivityType::PageVisit,
-                    31..=45 => ActivityType::Search,
-                    46..=60 => ActivityType::VideoWatch,
-                    61..=75 => ActivityType::SocialMedia,
-                    76..=85 => ActivityType::Shopping,
-                    _ => ActivityType::News,
-                }
+            Some(_)
+                if self
+                    .rng
+                    .gen_bool(self.drift.restart_probability.clamp(0.0, 1.0)) =>
+            {
+                self.choose_interest()
             }
-            BrowsingStyle::Casual => {
-                // Casual browsers have balanced activity
-                match self.rng.gen_range(0..=100) {
-                    0..=25 => ActivityType::SocialMedia,
-                    26..=45 => ActivityType::VideoWatch,
-                    46..=60 => ActivityType::PageVisit,
-                    61..=75 => ActivityType::News,
-                    76..=85 => ActivityType::Shopping,
-                    _ => ActivityType::Search,
-                }
+            Some(current) => {
+                let declared = self.profile.interests.len().max(1) as f64;
+                let may_leave = session.hops_outside < self.drift.max_hops_outside;
+                let candidates: Vec<(InterestCategory, f64)> = InterestGraph::builtin()
+                    .neighbors(&current)
+                    .iter()
+                    .map(|(to, weight)| {
+                        let affinity = self.profile.affinity(to);
+                        let pull = if affinity > 0.0 {
+                            affinity
+                        } else if may_leave {
+                            self.drift.outside_weight.max(0.0) / declared
+                        } else {
+                            0.0
+                        };
+                        (to.clone(), weight * pull)
+                    })
+                    .filter(|(to, weight)| *weight > 0.0 && !to.is_sensitive())
+                    .collect();
+                candidates
+                    .choose_weighted(&mut self.rng, |(_, weight)| *weight)
+                    .ok()
+                    .map(|(to, _)| to.clone())
+                    .or_else(|| self.choose_interest())
             }
+            None => self.choose_interest(),
+        };
+
+        session.hops_outside = match &next {
+            Some(interest) if !self.profile.has_interest(interest) => session.hops_outside + 1,
+            _ => 0,
+        };
+        session.interest = next.clone();
+        next
+    }
+
+    /// Pick an interest in proportion to the profile's affinity for it
+    fn choose_interest(&mut self) -> Option<InterestCategory> {
+        let interests = &self.profile.interests;
+        interests
+            .choose_weighted(&mut self.rng, |interest| interest.weight)
+            .ok()
+            .or_else(|| interests.choose(&mut self.rng))
+            .map(|interest| interest.category.clone())
+    }
+
+    /// One of the profile's own subtopics within `interest`, most of the time
+    fn choose_subtopic(
+        &mut self,
+        interest: &Option<InterestCategory>,
+    ) -> Option<&'static Subtopic> {
+        let interest = interest.as_ref()?;
+        if !self.rng.gen_bool(SUBTOPIC_SHARE) {
+            return None;
+        }
+        let taxonomy = Taxonomy::builtin();
+        let candidates: Vec<&'static Subtopic> = self
+            .profile
+            .subtopics
+            .iter()
+            .filter_map(|id| taxonomy.get(id))
+            .filter(|subtopic| &subtopic.category == interest)
+            .collect();
+        candidates.choose(&mut self.rng).copied()
+    }
+
+    fn choose_sensitive_topic(&mut self) -> Option<InterestCategory> {
+        let cover = self.sensitive_cover.as_ref()?;
+        if !self.rng.gen_bool(cover.rate) {
+            return None;
+        }
+        cover.topics.choose(&mut self.rng).cloned()
+    }
+
+    /// Sensitive research is mostly searching and reading, not shopping or social
+    fn choose_sensitive_activity_type(&mut self) -> ActivityType {
+        match self.rng.gen_range(0..=100) {
+            0..=45 => ActivityType::Search,
+            46..=75 => ActivityType::PageVisit,
+            76..=90 => ActivityType::Research,
+            91..=95 => ActivityType::News,
+            _ => ActivityType::VideoWatch,
         }
     }
 
-    fn generate_duration(&mut self, activity_type: &ActivityType) -> u32 {
+    /// Activity type by browsing style, reweighted for the session's device
+    fn choose_activity_type(&mut self, usage: Option<&DeviceUsage>) -> ActivityType {
+        let mix: &[(ActivityType, f64)] = match &self.profile.browsing_style {
+            // Researchers do more searches and research
+            BrowsingStyle::Researcher => &[
+                (ActivityType::Search, 41.0),
+                (ActivityType::Research, 35.0),
+                (ActivityType::PageVisit, 10.0),
+                (ActivityType::News, 7.0),
+                (ActivityType::VideoWatch, 8.0),
+            ],
+            // Focused users spend more time on fewer pages
+            BrowsingStyle::Focused => &[
+                (ActivityType::PageVisit, 61.0),
+                (ActivityType::Research, 15.0),
+                (ActivityType::Search, 10.0),
+                (ActivityType::News, 15.0),
+            ],
+            // Explorers hit many different types
+            BrowsingStyle::Explorer => &[
+                (ActivityType::PageVisit, 31.0),
+                (ActivityType::Search, 15.0),
+                (ActivityType::VideoWatch, 15.0),
+                (ActivityType::SocialMedia, 15.0),
+                (ActivityType::Shopping, 10.0),
+                (ActivityType::News, 15.0),
+            ],
+            // Casual browsers have balanced activity
+            BrowsingStyle::Casual => &[
+                (ActivityType::SocialMedia, 26.0),
+                (ActivityType::VideoWatch, 20.0),
+                (ActivityType::PageVisit, 15.0),
+                (ActivityType::News, 15.0),
+                (ActivityType::Shopping, 10.0),
+                (ActivityType::Search, 15.0),
+            ],
+        };
+        mix.choose_weighted(&mut self.rng, |(activity_type, weight)| {
+            weight * usage.map_or(1.0, |usage| usage.activity_weight(activity_type))
+        })
+        .map(|(activity_type, _)| activity_type.clone())
+        .unwrap_or(ActivityType::PageVisit)
+    }
+
+    fn generate_duration(
+        &mut self,
+        activity_type: &ActivityType,
+        usage: Option<&DeviceUsage>,
+    ) -> u32 {
         // Mean durations in seconds for different activity types
         let mean = match activity_type {
             ActivityType::Search => 10.0,
@@ -157,6 +560,8 @@ impl ActivitySimulator {
             ActivityType::News => 90.0,
             ActivityType::Research => 300.0,
         };
+        // Reading is quicker on a phone, scrolling a feed slower
+        let mean = mean * usage.map_or(1.0, |usage| usage.duration_factor(activity_type));
 
         // Add some randomness
         let duration = mean * (0.5 + self.rng.gen::<f64>() * 1.5);
@@ -167,8 +572,12 @@ impl ActivitySimulator {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::device::FormFactor;
     use crate::profile::ProfileGenerator;
 
+    /// Midnight UTC on Wednesday 2026-10-07
+    const WEDNESDAY: i64 = 1_791_331_200;
+
     #[test]
     fn test_activity_generation() {
         let mut gen = ProfileGenerator::new(Some(42));
@@ -190,8 +599,160 @@ mod tests {
         let profile = gen.generate();
         let mut simulator = ActivitySimulator::new(profile);
 
-        let duration = simulator.generate_duration(&ActivityType::VideoWatch);
+        let duration = simulator.generate_duration(&ActivityType::VideoWatch, None);
         assert!(duration > 0);
         assert!(duration < 3600); // Less than an hour is reasonable
     }
+
+    #[test]
+    fn test_rng_is_pluggable() {
+        let profile = ProfileGenerator::new(Some(42)).generate();
+        let urls = |activities: Vec<BrowsingActivity>| -> Vec<String> {
+            activities.into_iter().map(|a| a.url).collect()
+        };
+
+        // Seeded fast runs repeat; live runs don't
+        let fast = |seed| {
+            ActivitySimulator::from_rng(profile.clone(), FastRng::seed_from_u64(seed))
+                .generate_activities_from(WEDNESDAY, 4)
+        };
+        assert_eq!(urls(fast(7)), urls(fast(7)));
+        let live =
+            || ActivitySimulator::live(profile.clone()).generate_activities_from(WEDNESDAY, 4);
+        assert_ne!(urls(live()), urls(live()));
+    }
+
+    #[test]
+    fn test_sensitive_cover_is_tagged() {
+        let mut gen = ProfileGenerator::new(Some(42));
+        let profile = gen.generate();
+        assert!(!profile.categories().any(InterestCategory::is_sensitive));
+
+        let mut simulator = ActivitySimulator::new(profile).with_sensitive_cover(SensitiveCover {
+            rate: 0.5,
+            topics: vec![InterestCategory::LegalAdvice, InterestCategory::Gaming],
+        });
+        let activities = simulator.generate_activities_from(WEDNESDAY, 24);
+
+        let injected: Vec<_> = activities.iter().filter(|a| a.sensitive_cover).collect();
+        assert!(!injected.is_empty());
+        for activity in &activities {
+            let sensitive = activity
+                .interest_category
+                .as_ref()
+                .is_some_and(InterestCategory::is_sensitive);
+            assert_eq!(activity.sensitive_cover, sensitive);
+        }
+        assert!(injected
+            .iter()
+            .all(|a| a.interest_category == Some(InterestCategory::LegalAdvice)));
+    }
+
+    #[test]
+    fn test_phone_sessions_lean_social() {
+        let profile = ProfileGenerator::new(Some(8))
+            .generate_with(&crate::ProfileSpec::new().browsing_style(BrowsingStyle::Casual))
+            .unwrap();
+        let mut simulator = ActivitySimulator::new(profile).with_seed(4);
+        let phone = simulator
+            .devices()
+            .primary(FormFactor::Mobile)
+            .unwrap()
+            .id
+            .clone();
+        let activities = simulator.generate_activities_from(WEDNESDAY, 24 * 14);
+
+        let social_share = |on_phone: bool| {
+            let (social, total) = activities
+                .iter()
+                .filter(|a| (a.device_id.as_ref() == Some(&phone)) == on_phone)
+                .fold((0, 0), |(social, total), a| {
+                    let social_or_video = matches!(
+                        a.activity_type,
+                        ActivityType::SocialMedia | ActivityType::VideoWatch
+                    );
+                    (social + social_or_video as usize, total + 1)
+                });
+            social as f64 / total.max(1) as f64
+        };
+        assert!(social_share(true) > social_share(false) + 0.1);
+    }
+
+    #[test]
+    fn test_volume_follows_device_use() {
+        let profile = ProfileGenerator::new(Some(11)).generate();
+        let mut simulator = ActivitySimulator::new(profile).with_seed(3);
+        let activities = simulator.generate_activities_from(WEDNESDAY, 24 * 14);
+
+        let devices = simulator.devices();
+        let in_local_hours = |hours: std::ops::Range<u32>| {
+            activities
+                .iter()
+                .filter(|a| {
+                    let local = crate::locale::local_time(
+                        &devices.timezone,
+                        devices.utc_offset_minutes,
+                        a.timestamp,
+                    );
+                    hours.contains(&chrono::Timelike::hour(&local.unwrap()))
+                })
+                .count()
+        };
+        assert!(
+            in_local_hours(2..5) * 5 < in_local_hours(19..22),
+            "{} vs {}",
+            in_local_hours(2..5),
+            in_local_hours(19..22)
+        );
+    }
+
+    #[test]
+    fn test_denied_interest_is_regenerated_not_dropped() {
+        let spec = crate::ProfileSpec::new().require_interest(InterestCategory::Gaming);
+        let profile = ProfileGenerator::new(Some(17))
+            .generate_with(&spec)
+            .unwrap();
+
+        // Every site a gaming activity could land on, found by sampling
+        let generator = InterestUrlGenerator::for_locale(&profile.demographics.locale);
+        let gaming = Some(InterestCategory::Gaming);
+        let today = NaiveDate::from_ymd_opt(2026, 10, 7).unwrap();
+        let subtopics = std::iter::once(None).chain(
+            Taxonomy::builtin()
+                .subtopics(&InterestCategory::Gaming)
+                .map(Some),
+        );
+        let mut rng = FastRng::seed_from_u64(0);
+        let mut policy = SafetyPolicy::default();
+        for subtopic in subtopics {
+            for activity_type in ActivityType::ALL.iter() {
+                for _ in 0..200 {
+                    let Some(generated) =
+                        generator.generate_url(activity_type, &gaming, subtopic, today, &mut rng)
+                    else {
+                        continue;
+                    };
+                    let host = url::Url::parse(&generated.url)
+                        .unwrap()
+                        .host_str()
+                        .unwrap()
+                        .to_string();
+                    if !policy.deny_domains.contains(&host) {
+                        policy.deny_domains.push(host);
+                    }
+                }
+            }
+        }
+
+        let unfiltered = ActivitySimulator::new(profile.clone())
+            .with_seed(6)
+            .generate_activities_from(WEDNESDAY, 24 * 7);
+        let filtered = ActivitySimulator::new(profile)
+            .with_seed(6)
+            .with_policy(policy)
+            .generate_activities_from(WEDNESDAY, 24 * 7);
+        assert_eq!(filtered.len(), unfiltered.len());
+        assert!(unfiltered.iter().any(|a| a.interest_category == gaming));
+        assert!(filtered.iter().all(|a| a.interest_category != gaming));
+    }
 }
@@ -0,0 +1,214 @@
+use crate::drift::InterestGraph;
+use crate::profile::{InterestCategory, OccupationCategory, Profile};
+use crate::spec::ProfileSpec;
+use serde::{Deserialize, Serialize};
+
+/// Everyday interests a contrasting spec must leave available, so the
+/// persona still has a believable spread to draw from
+const MIN_ALLOWED_INTERESTS: usize = 8;
+
+/// Share of a neighbour's affinity that counts as overlap with the real user
+const NEIGHBOR_OVERLAP: f64 = 0.5;
+
+/// Coarse age bracket, as precise as a user is likely to declare or a
+/// tracker to infer
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum AgeBand {
+    Young,
+    Adult,
+    MiddleAged,
+    Senior,
+}
+
+impl AgeBand {
+    pub const ALL: [AgeBand; 4] = [
+        AgeBand::Young,
+        AgeBand::Adult,
+        AgeBand::MiddleAged,
+        AgeBand::Senior,
+    ];
+
+    pub fn of(age: u8) -> AgeBand {
+        match age {
+            0..=29 => AgeBand::Young,
+            30..=44 => AgeBand::Adult,
+            45..=59 => AgeBand::MiddleAged,
+            _ => AgeBand::Senior,
+        }
+    }
+
+    /// Inclusive ages in the band, within the generator's default range
+    pub fn ages(&self) -> (u8, u8) {
+        match self {
+            AgeBand::Young => (18, 29),
+            AgeBand::Adult => (30, 44),
+            AgeBand::MiddleAged => (45, 59),
+            AgeBand::Senior => (60, 75),
+        }
+    }
+
+    /// The band furthest away
+    pub fn opposite(&self) -> AgeBand {
+        if *self <= AgeBand::Adult {
+            AgeBand::Senior
+        } else {
+            AgeBand::Young
+        }
+    }
+
+    /// How many bands apart, from 0 to 1
+    pub(crate) fn distance(&self, other: &AgeBand) -> f64 {
+        (*self as i32 - *other as i32).abs() as f64 / (AgeBand::ALL.len() - 1) as f64
+    }
+}
+
+/// What is known about the real user, whether declared or inferred
+///
+/// Every field is optional; the less is known, the less a contrasting
+/// persona is constrained.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProfileSummary {
+    pub age_band: Option<AgeBand>,
+    pub occupation: Option<OccupationCategory>,
+    /// Most prominent first
+    pub top_interests: Vec<InterestCategory>,
+}
+
+impl ProfileSummary {
+    /// Constraints that steer a persona away from this user
+    ///
+    /// Ages come from the opposite band. The user's interests are forbidden,
+    /// then their neighbours in the [`InterestGraph`], strongest first, for
+    /// as long as enough everyday interests remain.
+    pub(crate) fn contrasting_spec(&self) -> ProfileSpec {
+        let mut spec = ProfileSpec::new();
+        if let Some(band) = &self.age_band {
+            let (min, max) = band.opposite().ages();
+            spec = spec.age_range(min, max);
+        }
+
+        let graph = InterestGraph::builtin();
+        let mut neighbors: Vec<(InterestCategory, f64)> = self
+            .top_interests
+            .iter()
+            .flat_map(|interest| graph.neighbors(interest).iter().cloned())
+            .collect();
+        // Stable, so ties keep the order the user's interests were listed in
+        neighbors.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+        let everyday = InterestCategory::ALL
+            .iter()
+            .filter(|i| !i.is_sensitive())
+            .count();
+        let candidates = self
+            .top_interests
+            .iter()
+            .cloned()
+            .chain(neighbors.into_iter().map(|(interest, _)| interest));
+        for interest in candidates {
+            if interest.is_sensitive() || spec.forbidden_interests.contains(&interest) {
+                continue;
+            }
+            if everyday - spec.forbidden_interests.len() <= MIN_ALLOWED_INTERESTS {
+                break;
+            }
+            spec = spec.forbid_interest(interest);
+        }
+        spec
+    }
+
+    /// Occupations a contrasting persona may have: anything but the user's
+    /// own that fits the ages `spec` allows
+    pub(crate) fn contrasting_occupations(&self, spec: &ProfileSpec) -> Vec<OccupationCategory> {
+        OccupationCategory::ALL
+            .iter()
+            .filter(|o| self.occupation.as_ref() != Some(*o))
+            .filter(|o| spec.clone().occupation((*o).clone()).validate().is_ok())
+            .cloned()
+            .collect()
+    }
+
+    /// How far a profile is from this user, from 0 (indistinguishable) to 1
+    ///
+    /// Age band, occupation and interests count equally; whichever the
+    /// summary leaves out is ignored. Interest overlap is the profile's
+    /// affinity for the user's interests, plus half its affinity for their
+    /// neighbours.
+    pub fn distance(&self, profile: &Profile) -> f64 {
+        let mut parts = Vec::new();
+
+        if let Some(band) = &self.age_band {
+            parts.push(band.distance(&AgeBand::of(profile.demographics.age)));
+        }
+        if let Some(occupation) = &self.occupation {
+            let same = &profile.demographics.occupation_category == occupation;
+            parts.push(if same { 0.0 } else { 1.0 });
+        }
+        if !self.top_interests.is_empty() {
+            let graph = InterestGraph::builtin();
+            let overlap: f64 = profile
+                .interests
+                .iter()
+                .map(|affinity| {
+                    if self.top_interests.contains(&affinity.category) {
+                        affinity.weight
+                    } else if self.top_interests.iter().any(|interest| {
+                        graph
+                            .neighbors(interest)
+                            .iter()
+                            .any(|(n, _)| n == &affinity.category)
+                    }) {
+                        affinity.weight * NEIGHBOR_OVERLAP
+                    } else {
+                        0.0
+                    }
+                })
+                .sum();
+            parts.push(1.0 - overlap.min(1.0));
+        }
+
+        if parts.is_empty() {
+            return 1.0;
+        }
+        parts.iter().sum::<f64>() / parts.len() as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_age_bands() {
+        assert_eq!(AgeBand::of(18), AgeBand::Young);
+        assert_eq!(AgeBand::of(44), AgeBand::Adult);
+        assert_eq!(AgeBand::of(90), AgeBand::Senior);
+        assert_eq!(AgeBand::Adult.opposite(), AgeBand::Senior);
+        assert_eq!(AgeBand::MiddleAged.opposite(), AgeBand::Young);
+        assert_eq!(AgeBand::Young.distance(&AgeBand::Senior), 1.0);
+    }
+
+    #[test]
+    fn test_contrasting_spec_forbids_user_interests() {
+        let real = ProfileSummary {
+            age_band: Some(AgeBand::Young),
+            occupation: Some(OccupationCategory::Student),
+            top_interests: vec![InterestCategory::Gaming, InterestCategory::Programming],
+        };
+        let spec = real.contrasting_spec();
+
+        assert_eq!(spec.age_range, Some((60, 75)));
+        assert!(spec.forbidden_interests.contains(&InterestCategory::Gaming));
+        assert!(spec
+            .forbidden_interests
+            .contains(&InterestCategory::Programming));
+        assert!(spec.forbidden_interests.len() <= 20 - MIN_ALLOWED_INTERESTS);
+        assert!(spec.validate().is_ok());
+
+        // Never the user's own occupation, nor one that doesn't fit 60-75
+        let occupations = real.contrasting_occupations(&spec);
+        assert!(!occupations.contains(&OccupationCategory::Student));
+        assert!(occupations.contains(&OccupationCategory::Retired));
+    }
+}
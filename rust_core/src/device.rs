@@ -0,0 +1,931 @@
+use crate::activity::ActivityType;
+use crate::contrast::AgeBand;
+use crate::locale::{local_time, Country, Language};
+use crate::profile::{EducationLevel, InterestCategory, OccupationCategory, Profile};
+use crate::rng::derived_rng;
+use crate::validation::ValidationIssue;
+use chrono::{Datelike, NaiveDate, NaiveDateTime, Timelike, Weekday};
+use rand::seq::SliceRandom;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::OnceLock;
+
+/// Operating system, browser and screen shares, and how demographics and
+/// interests shift them
+const BUILTIN_DEVICES: &str = include_str!("../data/devices.json");
+
+/// Keeps device draws apart from anything else derived from a profile id
+const DEVICE_DOMAIN: &str = "doubletrack.device.v1";
+
+/// Bounds on the share of browsing done on the phone
+const MOBILE_SHARE_RANGE: (f64, f64) = (0.05, 0.95);
+
+/// How much more likely a large monitor is for someone into gaming
+const GAMING_LARGE_SCREEN: f64 = 3.0;
+
+/// Share of people whose browser is a few releases behind
+const LAGGING_SHARE: f64 = 0.08;
+
+/// Chromium each Samsung Internet major from 23 to 29 is built on, per
+/// Samsung's release notes
+const SAMSUNG_CHROMIUM: &[(u32, u32)] = &[
+    (23, 115),
+    (24, 117),
+    (25, 121),
+    (26, 122),
+    (27, 125),
+    (28, 130),
+    (29, 136),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum FormFactor {
+    Desktop,
+    Mobile,
+    Tablet,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum OperatingSystem {
+    Windows,
+    MacOS,
+    Linux,
+    ChromeOS,
+    IOS,
+    Android,
+}
+
+impl OperatingSystem {
+    pub const ALL: [OperatingSystem; 6] = [
+        OperatingSystem::Windows,
+        OperatingSystem::MacOS,
+        OperatingSystem::Linux,
+        OperatingSystem::ChromeOS,
+        OperatingSystem::IOS,
+        OperatingSystem::Android,
+    ];
+
+    /// Whether devices of `form_factor` run this OS
+    pub fn suits(&self, form_factor: FormFactor) -> bool {
+        let handheld = matches!(self, OperatingSystem::IOS | OperatingSystem::Android);
+        handheld == (form_factor != FormFactor::Desktop)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Browser {
+    Chrome,
+    Safari,
+    Firefox,
+    Edge,
+    Opera,
+    SamsungInternet,
+}
+
+impl Browser {
+    pub const ALL: [Browser; 6] = [
+        Browser::Chrome,
+        Browser::Safari,
+        Browser::Firefox,
+        Browser::Edge,
+        Browser::Opera,
+        Browser::SamsungInternet,
+    ];
+
+    /// Whether the browser ships for `os`
+    pub fn runs_on(&self, os: &OperatingSystem) -> bool {
+        match self {
+            Browser::Safari => matches!(os, OperatingSystem::MacOS | OperatingSystem::IOS),
+            Browser::SamsungInternet => *os == OperatingSystem::Android,
+            Browser::Chrome => true,
+            Browser::Firefox | Browser::Edge | Browser::Opera => *os != OperatingSystem::ChromeOS,
+        }
+    }
+
+    /// Newest stable major version on `date`
+    ///
+    /// Extrapolated from a mid-2025 release at each browser's average
+    /// cadence, holiday gaps included: about a month for Chromium browsers
+    /// and Firefox, four months for Samsung Internet, and a year for
+    /// Safari, which is numbered after the coming year since 2025. Dates
+    /// before the anchors get the anchor versions.
+    pub fn current_version(&self, date: NaiveDate) -> u32 {
+        let ymd = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).expect("valid anchor date");
+        let releases_since = |anchor: NaiveDate, cadence_days: i64| {
+            ((date - anchor).num_days().max(0) / cadence_days) as u32
+        };
+        let chrome = 138 + releases_since(ymd(2025, 6, 24), 31);
+        match self {
+            Browser::Chrome | Browser::Edge => chrome,
+            Browser::Opera => chrome - 15,
+            Browser::Firefox => 140 + releases_since(ymd(2025, 6, 24), 29),
+            Browser::SamsungInternet => 28 + releases_since(ymd(2025, 4, 1), 120),
+            Browser::Safari => {
+                let released = |year| date >= ymd(year, 9, 15);
+                if released(2025) {
+                    let year = if released(date.year()) {
+                        date.year()
+                    } else {
+                        date.year() - 1
+                    };
+                    (year - 1999) as u32
+                } else {
+                    17 + releases_since(ymd(2023, 9, 18), 365).min(1)
+                }
+            }
+        }
+    }
+}
+
+impl FormFactor {
+    /// When devices of this kind get used, for what and for how long
+    pub fn usage(&self) -> &'static DeviceUsage {
+        &DeviceData::builtin().usage[self]
+    }
+}
+
+/// How a kind of device gets used over the day
+///
+/// Phones come out on the commute and in the evening, for social media and
+/// video; desktops cover working hours and longer reading; tablets mostly
+/// come out in the evening and at weekends, for video and shopping.
+#[derive(Debug, Clone, Deserialize)]
+pub struct DeviceUsage {
+    /// Relative use in each local hour from midnight, Monday to Friday
+    pub weekday_hours: [f64; 24],
+    pub weekend_hours: [f64; 24],
+    /// Multiplies how often each activity type comes up; 1 if absent
+    pub activity_types: HashMap<ActivityType, f64>,
+    /// Multiplies the typical duration of each activity type; 1 if absent
+    pub durations: HashMap<ActivityType, f64>,
+}
+
+impl DeviceUsage {
+    /// Use at a local time relative to the day's average
+    pub fn intensity(&self, local: NaiveDateTime) -> f64 {
+        let hours = match local.weekday() {
+            Weekday::Sat | Weekday::Sun => &self.weekend_hours,
+            _ => &self.weekday_hours,
+        };
+        let mean = hours.iter().sum::<f64>() / 24.0;
+        if mean > 0.0 {
+            hours[local.hour() as usize] / mean
+        } else {
+            0.0
+        }
+    }
+
+    pub fn activity_weight(&self, activity_type: &ActivityType) -> f64 {
+        self.activity_types
+            .get(activity_type)
+            .copied()
+            .unwrap_or(1.0)
+    }
+
+    pub fn duration_factor(&self, activity_type: &ActivityType) -> f64 {
+        self.durations.get(activity_type).copied().unwrap_or(1.0)
+    }
+}
+
+/// Inclusive range of major versions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VersionRange {
+    pub min: u32,
+    pub max: u32,
+}
+
+/// Viewport in CSS pixels
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Screen {
+    pub width: u32,
+    pub height: u32,
+    /// Physical pixels per CSS pixel
+    pub pixel_ratio: f64,
+}
+
+/// One device the persona browses on
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Device {
+    pub id: String,
+    pub form_factor: FormFactor,
+    pub os: OperatingSystem,
+    pub browser: Browser,
+    /// Major versions the browser reports while an update rolls out;
+    /// headers should use one from this range
+    pub browser_versions: VersionRange,
+    pub screen: Screen,
+    /// Share of the persona's browsing done on this device, from 0 to 1
+    pub share: f64,
+}
+
+impl Device {
+    /// `User-Agent` header for the newest version in range, in the reduced
+    /// form current browsers send
+    pub fn user_agent(&self) -> String {
+        let v = self.browser_versions.max;
+        let tablet = self.form_factor == FormFactor::Tablet;
+        let webkit = "AppleWebKit/537.36 (KHTML, like Gecko)";
+        let platform = match self.os {
+            OperatingSystem::Windows => "Windows NT 10.0; Win64; x64",
+            OperatingSystem::MacOS => "Macintosh; Intel Mac OS X 10_15_7",
+            OperatingSystem::Linux => "X11; Linux x86_64",
+            OperatingSystem::ChromeOS => "X11; CrOS x86_64 14541.0.0",
+            OperatingSystem::IOS if tablet => "iPad; CPU OS 18_6 like Mac OS X",
+            OperatingSystem::IOS => "iPhone; CPU iPhone OS 18_6 like Mac OS X",
+            OperatingSystem::Android => "Linux; Android 10; K",
+        };
+
+        match (self.browser, self.os) {
+            (Browser::Firefox, OperatingSystem::IOS) => format!(
+                "Mozilla/5.0 ({}) AppleWebKit/605.1.15 (KHTML, like Gecko) FxiOS/{}.0 Mobile/15E148 Safari/605.1.15",
+                platform, v
+            ),
+            (Browser::Firefox, os) => {
+                let platform = match os {
+                    OperatingSystem::MacOS => "Macintosh; Intel Mac OS X 10.15",
+                    OperatingSystem::Android if tablet => "Android 14; Tablet",
+                    OperatingSystem::Android => "Android 14; Mobile",
+                    _ => platform,
+                };
+                let gecko = if os == OperatingSystem::Android {
+                    format!("{}.0", v)
+                } else {
+                    "20100101".to_string()
+                };
+                format!("Mozilla/5.0 ({}; rv:{}.0) Gecko/{} Firefox/{}.0", platform, v, gecko, v)
+            }
+            // Safari on an iPad asks for desktop sites as a Mac
+            (Browser::Safari, OperatingSystem::IOS) if tablet => format!(
+                "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/{}.0 Safari/605.1.15",
+                v
+            ),
+            (Browser::Safari, OperatingSystem::IOS) => format!(
+                "Mozilla/5.0 ({}) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/{}.0 Mobile/15E148 Safari/604.1",
+                platform, v
+            ),
+            (Browser::Safari, _) => format!(
+                "Mozilla/5.0 ({}) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/{}.0 Safari/605.1.15",
+                platform, v
+            ),
+            (Browser::Chrome, OperatingSystem::IOS) => format!(
+                "Mozilla/5.0 ({}) AppleWebKit/605.1.15 (KHTML, like Gecko) CriOS/{}.0.0.0 Mobile/15E148 Safari/604.1",
+                platform, v
+            ),
+            (browser, os) => {
+                let mobile = if os == OperatingSystem::Android && !tablet { "Mobile " } else { "" };
+                // The Chromium each browser is built on
+                let (chrome, suffix) = match browser {
+                    Browser::Edge => (v, format!(" Edg/{}.0.0.0", v)),
+                    Browser::Opera => (v + 15, format!(" OPR/{}.0.0.0", v)),
+                    Browser::SamsungInternet => (samsung_chromium(v), String::new()),
+                    _ => (v, String::new()),
+                };
+                let samsung = if browser == Browser::SamsungInternet {
+                    format!("SamsungBrowser/{}.0 ", v)
+                } else {
+                    String::new()
+                };
+                format!(
+                    "Mozilla/5.0 ({}) {} {}Chrome/{}.0.0.0 {}Safari/537.36{}",
+                    platform, webkit, samsung, chrome, mobile, suffix
+                )
+            }
+        }
+    }
+}
+
+/// The devices behind a profile's browsing, consistent with who it is
+///
+/// Demographics and interests shape every choice: a programmer leans
+/// towards Linux and Firefox, a gamer towards Windows and a large monitor,
+/// a teenager towards browsing on the phone. Languages and timezone follow
+/// the profile's locale. Like a [`Persona`](crate::Persona), the devices
+/// are drawn from the profile id, so a profile always has the same ones.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeviceProfile {
+    /// A desktop or laptop, then a phone, then any others the persona
+    /// owns, such as a tablet or a second laptop
+    pub devices: Vec<Device>,
+    /// Preferred languages, most preferred first, e.g. `de-DE`
+    pub languages: Vec<String>,
+    /// IANA name, e.g. `Europe/Berlin`
+    pub timezone: String,
+    /// Minutes east of UTC, in standard time; summer time follows the
+    /// bundled timezone of that name
+    pub utc_offset_minutes: i32,
+    /// Share of browsing done on the phone, from 0 to 1, as on the phone
+    /// itself
+    pub mobile_share: f64,
+}
+
+#[derive(Debug, Deserialize)]
+struct MobileShares {
+    age: HashMap<AgeBand, f64>,
+    /// Added to the age share
+    occupation: HashMap<OccupationCategory, f64>,
+    /// Added to the age share in proportion to the interest's weight
+    interests: HashMap<InterestCategory, f64>,
+}
+
+/// A device some personas own on top of a computer and a phone
+#[derive(Debug, Deserialize)]
+struct ExtraDevice {
+    form_factor: FormFactor,
+    /// Browsing on it relative to the main computer; it takes its part
+    /// from what isn't done on the phone
+    weight: f64,
+    /// Chance of owning one
+    age: HashMap<AgeBand, f64>,
+    /// Added to the age chance
+    occupation: HashMap<OccupationCategory, f64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ScreenChoice {
+    #[serde(flatten)]
+    screen: Screen,
+    weight: f64,
+}
+
+/// Multipliers on a base table, keyed by whatever shifts it
+type Shifts<K, V> = HashMap<K, HashMap<V, f64>>;
+
+#[derive(Debug, Deserialize)]
+struct DeviceData {
+    mobile_share: MobileShares,
+    desktop_os: HashMap<OperatingSystem, f64>,
+    desktop_os_occupation: Shifts<OccupationCategory, OperatingSystem>,
+    /// Applied in proportion to the interest's weight
+    desktop_os_interests: Shifts<InterestCategory, OperatingSystem>,
+    mobile_os: HashMap<Country, HashMap<OperatingSystem, f64>>,
+    mobile_os_age: Shifts<AgeBand, OperatingSystem>,
+    tablet_os: HashMap<Country, HashMap<OperatingSystem, f64>>,
+    browsers: HashMap<OperatingSystem, HashMap<Browser, f64>>,
+    browser_interests: Shifts<InterestCategory, Browser>,
+    browser_occupation: Shifts<OccupationCategory, Browser>,
+    screens: HashMap<OperatingSystem, Vec<ScreenChoice>>,
+    tablet_screens: HashMap<OperatingSystem, Vec<ScreenChoice>>,
+    extra_devices: Vec<ExtraDevice>,
+    usage: HashMap<FormFactor, DeviceUsage>,
+}
+
+impl DeviceData {
+    fn builtin() -> &'static DeviceData {
+        static DEVICES: OnceLock<DeviceData> = OnceLock::new();
+        DEVICES.get_or_init(|| {
+            serde_json::from_str(BUILTIN_DEVICES).expect("built-in device tables are valid")
+        })
+    }
+}
+
+impl DeviceProfile {
+    /// The devices behind `profile`, with browser versions as of today
+    pub fn from_profile(profile: &Profile) -> DeviceProfile {
+        Self::from_profile_on(profile, chrono::Utc::now().date_naive())
+    }
+
+    /// The devices behind `profile`, with browser versions as of `date`
+    pub fn from_profile_on(profile: &Profile, date: NaiveDate) -> DeviceProfile {
+        let data = DeviceData::builtin();
+        let mut rng = derived_rng(DEVICE_DOMAIN, &profile.id);
+        let demographics = &profile.demographics;
+        let band = AgeBand::of(demographics.age);
+        let occupation = &demographics.occupation_category;
+
+        let mut desktop_os = data.desktop_os.clone();
+        shift(
+            &mut desktop_os,
+            data.desktop_os_occupation.get(occupation),
+            1.0,
+        );
+        for interest in &profile.interests {
+            shift(
+                &mut desktop_os,
+                data.desktop_os_interests.get(&interest.category),
+                interest.weight,
+            );
+        }
+        let mut mobile_os = data.mobile_os[&demographics.locale.country].clone();
+        shift(&mut mobile_os, data.mobile_os_age.get(&band), 1.0);
+
+        let mut id = 0;
+        let mut new_device = |form_factor, weights: &HashMap<OperatingSystem, f64>, rng: &mut _| {
+            let os = weighted(weights, rng).expect("every form factor has an OS");
+            id += 1;
+            device(
+                profile,
+                format!("{}-{}", profile.id, id),
+                form_factor,
+                os,
+                date,
+                data,
+                rng,
+            )
+        };
+        let mut devices = vec![
+            new_device(FormFactor::Desktop, &desktop_os, &mut rng),
+            new_device(FormFactor::Mobile, &mobile_os, &mut rng),
+        ];
+
+        let share = data.mobile_share.age[&band]
+            + data
+                .mobile_share
+                .occupation
+                .get(occupation)
+                .copied()
+                .unwrap_or(0.0)
+            + profile
+                .interests
+                .iter()
+                .map(|interest| {
+                    interest.weight
+                        * data
+                            .mobile_share
+                            .interests
+                            .get(&interest.category)
+                            .copied()
+                            .unwrap_or(0.0)
+                })
+                .sum::<f64>()
+            + rng.gen_range(-0.05..0.05);
+        let (low, high) = MOBILE_SHARE_RANGE;
+        let mobile_share = share.clamp(low, high);
+        let languages = languages(profile, &mut rng);
+
+        // Drawn last so owning one leaves the computer and phone as they were
+        let mut tablet_os = data.tablet_os[&demographics.locale.country].clone();
+        shift(&mut tablet_os, data.mobile_os_age.get(&band), 1.0);
+        // Browsing on each device relative to the computer; the phone keeps
+        // its own share and the rest split what's left
+        let mut weights = vec![1.0, 0.0];
+        for extra in &data.extra_devices {
+            let owns = extra.age.get(&band).copied().unwrap_or(0.0)
+                + extra.occupation.get(occupation).copied().unwrap_or(0.0);
+            if !rng.gen_bool(owns.clamp(0.0, 1.0)) {
+                continue;
+            }
+            let os_weights = match extra.form_factor {
+                FormFactor::Desktop => &desktop_os,
+                FormFactor::Mobile => &mobile_os,
+                FormFactor::Tablet => &tablet_os,
+            };
+            devices.push(new_device(extra.form_factor, os_weights, &mut rng));
+            weights.push(extra.weight);
+        }
+        let total: f64 = weights.iter().sum();
+        for (device, weight) in devices.iter_mut().zip(weights) {
+            device.share = (1.0 - mobile_share) * weight / total;
+        }
+        devices[1].share = mobile_share;
+
+        let timezone = demographics.locale.timezone();
+        DeviceProfile {
+            devices,
+            languages,
+            timezone: timezone.name.clone(),
+            utc_offset_minutes: timezone.utc_offset_minutes,
+            mobile_share,
+        }
+    }
+
+    pub fn device(&self, id: &str) -> Option<&Device> {
+        self.devices.iter().find(|device| device.id == id)
+    }
+
+    /// The first device of a form factor
+    pub fn primary(&self, form_factor: FormFactor) -> Option<&Device> {
+        self.devices
+            .iter()
+            .find(|device| device.form_factor == form_factor)
+    }
+
+    /// Pick the device for a browsing session starting at `timestamp`
+    ///
+    /// Each device gets its `share` of the browsing overall, spread over
+    /// the day by its form factor's [`DeviceUsage`] in the profile's local
+    /// time, summer time included.
+    pub fn choose<R: Rng>(&self, timestamp: i64, rng: &mut R) -> Option<&Device> {
+        let local = self.local_time(timestamp)?;
+        self.devices
+            .choose_weighted(rng, |device| {
+                device.share * device.form_factor.usage().intensity(local)
+            })
+            .ok()
+            .or_else(|| self.devices.first())
+    }
+
+    /// How much the persona browses at `timestamp` relative to their
+    /// daily average, adding up every device's use at that local time
+    pub fn intensity_at(&self, timestamp: i64) -> f64 {
+        let Some(local) = self.local_time(timestamp) else {
+            return 1.0;
+        };
+        self.devices
+            .iter()
+            .map(|device| device.share * device.form_factor.usage().intensity(local))
+            .sum()
+    }
+
+    fn local_time(&self, timestamp: i64) -> Option<NaiveDateTime> {
+        local_time(&self.timezone, self.utc_offset_minutes, timestamp)
+    }
+
+    /// `Accept-Language` header for `languages`
+    pub fn accept_language(&self) -> String {
+        self.languages
+            .iter()
+            .enumerate()
+            .map(|(i, language)| match i {
+                0 => language.clone(),
+                _ => format!("{};q={:.1}", language, (10 - i.min(9)) as f64 / 10.0),
+            })
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+
+    /// Every way the devices contradict each other or themselves
+    ///
+    /// Checks that each browser ships for its OS, each OS and screen suit
+    /// the form factor, version ranges aren't inverted, the devices'
+    /// shares add up to all the browsing, and the languages and mobile
+    /// share are usable.
+    pub fn validate(&self) -> Result<(), Vec<ValidationIssue>> {
+        let mut issues = Vec::new();
+        for (i, device) in self.devices.iter().enumerate() {
+            let field = |name: &str| format!("devices[{}].{}", i, name);
+            if !device.browser.runs_on(&device.os) {
+                issues.push(ValidationIssue::error(
+                    field("browser"),
+                    format!("{:?} doesn't run on {:?}", device.browser, device.os),
+                ));
+            }
+            if !device.os.suits(device.form_factor) {
+                issues.push(ValidationIssue::error(
+                    field("os"),
+                    format!("{:?} isn't a {:?} OS", device.os, device.form_factor),
+                ));
+            }
+            let narrow = device.screen.width.min(device.screen.height);
+            let plausible = match device.form_factor {
+                FormFactor::Mobile => (300..=500).contains(&narrow),
+                FormFactor::Tablet => (600..=1100).contains(&narrow),
+                FormFactor::Desktop => narrow >= 600,
+            };
+            if !plausible {
+                issues.push(ValidationIssue::implausible(
+                    field("screen"),
+                    format!(
+                        "{}x{} is an odd screen for a {:?}",
+                        device.screen.width, device.screen.height, device.form_factor
+                    ),
+                ));
+            }
+            if device.browser_versions.min > device.browser_versions.max {
+                issues.push(ValidationIssue::error(
+                    field("browser_versions"),
+                    "min is above max",
+                ));
+            }
+            if self.devices[..i].iter().any(|other| other.id == device.id) {
+                issues.push(ValidationIssue::error(field("id"), "duplicate device id"));
+            }
+            if !(0.0..=1.0).contains(&device.share) {
+                issues.push(ValidationIssue::error(
+                    field("share"),
+                    format!("{} is not a share", device.share),
+                ));
+            }
+        }
+        let total: f64 = self.devices.iter().map(|device| device.share).sum();
+        if !self.devices.is_empty() && (total - 1.0).abs() > 1e-6 {
+            issues.push(ValidationIssue::error(
+                "devices",
+                format!("shares add up to {}, not 1", total),
+            ));
+        }
+        if self.languages.is_empty() {
+            issues.push(ValidationIssue::error("languages", "no languages"));
+        }
+        if !(0.0..=1.0).contains(&self.mobile_share) {
+            issues.push(ValidationIssue::error(
+                "mobile_share",
+                format!("{} is not a share", self.mobile_share),
+            ));
+        }
+
+        if issues.is_empty() {
+            Ok(())
+        } else {
+            Err(issues)
+        }
+    }
+}
+
+fn device<R: Rng>(
+    profile: &Profile,
+    id: String,
+    form_factor: FormFactor,
+    os: OperatingSystem,
+    date: NaiveDate,
+    data: &DeviceData,
+    rng: &mut R,
+) -> Device {
+    let mut browsers = data.browsers[&os].clone();
+    shift(
+        &mut browsers,
+        data.browser_occupation
+            .get(&profile.demographics.occupation_category),
+        1.0,
+    );
+    for interest in &profile.interests {
+        shift(
+            &mut browsers,
+            data.browser_interests.get(&interest.category),
+            interest.weight,
+        );
+    }
+    let browser = weighted(&browsers, rng).expect("every OS has a browser");
+
+    let gamer = profile.affinity(&InterestCategory::Gaming);
+    let screens = match form_factor {
+        FormFactor::Tablet => &data.tablet_screens[&os],
+        _ => &data.screens[&os],
+    };
+    let screen = screens
+        .choose_weighted(rng, |choice| {
+            let large = choice.screen.width >= 2560 && choice.screen.pixel_ratio < 2.0;
+            choice.weight
+                * if large {
+                    1.0 + (GAMING_LARGE_SCREEN - 1.0) * gamer
+                } else {
+                    1.0
+                }
+        })
+        .expect("every OS has screens")
+        .screen;
+
+    let current = browser.current_version(date);
+    let lag = if rng.gen_bool(LAGGING_SHARE) {
+        rng.gen_range(2..=4)
+    } else {
+        0
+    };
+    Device {
+        id,
+        form_factor,
+        os,
+        browser,
+        browser_versions: VersionRange {
+            min: current.saturating_sub(lag + 1),
+            max: current.saturating_sub(lag),
+        },
+        screen,
+        // Set once every device is known
+        share: 0.0,
+    }
+}
+
+/// Languages in order of preference, with English as a second language
+/// for many outside English-speaking countries
+/// Chromium a Samsung Internet major is built on
+///
+/// Outside the known releases, older majors get the oldest known base and
+/// newer ones move four Chromium releases per major, as Samsung ships
+/// about every four months against Chromium's monthly cadence.
+fn samsung_chromium(version: u32) -> u32 {
+    let (first, first_chromium) = SAMSUNG_CHROMIUM[0];
+    let (last, last_chromium) = SAMSUNG_CHROMIUM[SAMSUNG_CHROMIUM.len() - 1];
+    match SAMSUNG_CHROMIUM.iter().find(|(major, _)| *major == version) {
+        Some(&(_, chromium)) => chromium,
+        None if version < first => first_chromium,
+        None => last_chromium + 4 * (version - last),
+    }
+}
+
+fn languages<R: Rng>(profile: &Profile, rng: &mut R) -> Vec<String> {
+    let locale = &profile.demographics.locale;
+    let language = locale.language.code();
+    let mut languages = vec![
+        format!("{}-{}", language, locale.country.code()),
+        language.to_string(),
+    ];
+
+    if locale.language != Language::En {
+        let educated = matches!(
+            profile.demographics.education_level,
+            EducationLevel::Bachelor | EducationLevel::Master | EducationLevel::Doctorate
+        );
+        let young = profile.demographics.age < 45;
+        let english = 0.2 + 0.3 * educated as u8 as f64 + 0.2 * young as u8 as f64;
+        if rng.gen_bool(english) {
+            languages.extend(["en-US".to_string(), "en".to_string()]);
+        }
+    }
+    languages
+}
+
+/// Multiply `weights` by `multipliers`, scaled towards 1 by `strength`
+fn shift<K: Eq + Hash + Clone>(
+    weights: &mut HashMap<K, f64>,
+    multipliers: Option<&HashMap<K, f64>>,
+    strength: f64,
+) {
+    for (key, multiplier) in multipliers.into_iter().flatten() {
+        if let Some(weight) = weights.get_mut(key) {
+            *weight *= 1.0 + (multiplier - 1.0) * strength;
+        }
+    }
+}
+
+/// Weighted draw in a fixed order, so a seed gives the same result
+/// whatever order the map iterates in
+fn weighted<K: Copy + Ord, R: Rng>(weights: &HashMap<K, f64>, rng: &mut R) -> Option<K> {
+    let mut options: Vec<(K, f64)> = weights.iter().map(|(k, w)| (*k, *w)).collect();
+    options.sort_by_key(|(k, _)| *k);
+    options
+        .choose_weighted(rng, |(_, weight)| *weight)
+        .ok()
+        .map(|(key, _)| *key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Locale, ProfileGenerator, ProfileSpec};
+
+    #[test]
+    fn test_devices_follow_from_the_profile() {
+        let date = NaiveDate::from_ymd_opt(2026, 10, 1).unwrap();
+        let mut generator = ProfileGenerator::new(Some(5));
+        for _ in 0..50 {
+            let profile = generator.generate();
+            let devices = DeviceProfile::from_profile_on(&profile, date);
+            assert_eq!(devices, DeviceProfile::from_profile_on(&profile, date));
+            assert_eq!(devices.validate(), Ok(()));
+            assert!(devices.primary(FormFactor::Desktop).is_some());
+            assert!(devices.primary(FormFactor::Mobile).is_some());
+        }
+
+        let german = ProfileGenerator::new(Some(5))
+            .with_locale(Locale::parse("de-DE").unwrap())
+            .generate();
+        let devices = DeviceProfile::from_profile_on(&german, date);
+        assert_eq!(devices.languages[0], "de-DE");
+        assert_eq!(devices.timezone, "Europe/Berlin");
+        assert!(devices.accept_language().starts_with("de-DE,de;q=0.9"));
+    }
+
+    #[test]
+    fn test_gamers_and_programmers_get_matching_machines() {
+        let date = NaiveDate::from_ymd_opt(2026, 10, 1).unwrap();
+        let mut generator = ProfileGenerator::new(Some(9));
+        let mut desktops = |spec: ProfileSpec| {
+            (0..300)
+                .map(|_| {
+                    let profile = generator.generate_with(&spec).unwrap();
+                    DeviceProfile::from_profile_on(&profile, date)
+                        .primary(FormFactor::Desktop)
+                        .cloned()
+                        .unwrap()
+                })
+                .collect::<Vec<Device>>()
+        };
+        let count = |devices: &[Device], f: &dyn Fn(&Device) -> bool| {
+            devices.iter().filter(|d| f(d)).count()
+        };
+
+        let adults = || ProfileSpec::new().age_range(20, 40);
+        let gamers = desktops(adults().require_interest(InterestCategory::Gaming));
+        let programmers = desktops(adults().require_interest(InterestCategory::Programming));
+        let cooks = desktops(
+            adults()
+                .require_interest(InterestCategory::Cooking)
+                .forbid_interest(InterestCategory::Gaming)
+                .forbid_interest(InterestCategory::Programming),
+        );
+        let linux = |d: &Device| d.os == OperatingSystem::Linux;
+        let large = |d: &Device| d.screen.width >= 2560;
+        assert!(count(&programmers, &linux) > 2 * count(&cooks, &linux));
+        assert!(count(&gamers, &large) > count(&cooks, &large));
+
+        assert_eq!(Browser::Chrome.current_version(date), 152);
+        assert_eq!(Browser::Safari.current_version(date), 27);
+        assert_eq!(
+            Browser::Safari.current_version(NaiveDate::from_ymd_opt(2025, 9, 1).unwrap()),
+            18
+        );
+    }
+
+    #[test]
+    fn test_some_own_a_tablet_or_a_second_computer() {
+        let date = NaiveDate::from_ymd_opt(2026, 10, 1).unwrap();
+        let mut generator = ProfileGenerator::new(Some(13));
+        let mut owners = |spec: ProfileSpec, form_factor: FormFactor| {
+            let extra = |devices: &DeviceProfile| {
+                devices.devices[2..]
+                    .iter()
+                    .filter(|d| d.form_factor == form_factor)
+                    .count()
+            };
+            (0..300)
+                .map(|_| {
+                    let profile = generator.generate_with(&spec).unwrap();
+                    let devices = DeviceProfile::from_profile_on(&profile, date);
+                    assert_eq!(devices.validate(), Ok(()));
+                    assert_eq!(devices.devices[1].share, devices.mobile_share);
+                    extra(&devices)
+                })
+                .sum::<usize>()
+        };
+
+        let tablets = owners(ProfileSpec::new().age_range(40, 60), FormFactor::Tablet);
+        assert!((60..200).contains(&tablets), "{}", tablets);
+        let programmers = owners(
+            ProfileSpec::new().occupation(OccupationCategory::Technology),
+            FormFactor::Desktop,
+        );
+        let tradespeople = owners(
+            ProfileSpec::new().occupation(OccupationCategory::Trades),
+            FormFactor::Desktop,
+        );
+        assert!(
+            programmers > 3 * tradespeople,
+            "{} vs {}",
+            programmers,
+            tradespeople
+        );
+
+        let ipad = Device {
+            id: "ipad".to_string(),
+            form_factor: FormFactor::Tablet,
+            os: OperatingSystem::IOS,
+            browser: Browser::Safari,
+            browser_versions: VersionRange { min: 26, max: 26 },
+            screen: Screen {
+                width: 820,
+                height: 1180,
+                pixel_ratio: 2.0,
+            },
+            share: 1.0,
+        };
+        assert!(ipad.user_agent().contains("Macintosh"));
+        let chrome = Device {
+            browser: Browser::Chrome,
+            ..ipad.clone()
+        };
+        assert!(chrome.user_agent().contains("iPad"));
+        let android = Device {
+            os: OperatingSystem::Android,
+            browser: Browser::Chrome,
+            ..ipad
+        };
+        assert!(!android.user_agent().contains("Mobile"));
+        let samsung = |major| {
+            Device {
+                browser: Browser::SamsungInternet,
+                browser_versions: VersionRange {
+                    min: major,
+                    max: major,
+                },
+                ..android.clone()
+            }
+            .user_agent()
+        };
+        assert!(samsung(28).contains("SamsungBrowser/28.0 Chrome/130.0.0.0"));
+        assert!(samsung(31).contains("Chrome/144.0.0.0"));
+    }
+
+    #[test]
+    fn test_phone_in_the_evening_desktop_at_work() {
+        let profile = ProfileGenerator::new(Some(3)).generate();
+        let devices = DeviceProfile::from_profile(&profile);
+        // Wednesday 2026-10-07, local summer time
+        let timezone = crate::Timezone::named(&devices.timezone).unwrap();
+        let offset = timezone.utc_offset_at(1_791_331_200) as i64 * 60;
+        assert_eq!(offset, (devices.utc_offset_minutes as i64 + 60) * 60);
+        let at = |hour: i64| 1_791_331_200 + hour * 3600 - offset;
+        let mut rng = derived_rng("test", &profile.id);
+        let phone_share = |hour: i64, rng: &mut rand_chacha::ChaCha8Rng| {
+            (0..2000)
+                .filter(|_| {
+                    devices.choose(at(hour), rng).unwrap().form_factor == FormFactor::Mobile
+                })
+                .count()
+        };
+        assert!(phone_share(21, &mut rng) > phone_share(10, &mut rng) + 200);
+        assert!(devices.intensity_at(at(3)) * 5.0 < devices.intensity_at(at(20)));
+
+        let phone = FormFactor::Mobile.usage();
+        let desktop = FormFactor::Desktop.usage();
+        assert!(
+            phone.activity_weight(&ActivityType::SocialMedia)
+                > desktop.activity_weight(&ActivityType::SocialMedia)
+        );
+        assert!(
+            phone.duration_factor(&ActivityType::Research)
+                < desktop.duration_factor(&ActivityType::Research)
+        );
+    }
+}
@@ -0,0 +1,132 @@
+use crate::profile::InterestCategory;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+/// Built-in relatedness edges between everyday interests
+const BUILTIN_GRAPH: &str = include_str!("../data/interest_graph.json");
+
+/// How related interests are to each other, for drift within a session
+///
+/// Edges are undirected and weighted; a recipe can lead to kitchen gear as
+/// easily as the other way round. Sensitive categories have no edges, so
+/// drift never wanders into them.
+#[derive(Debug, Clone, Default)]
+pub struct InterestGraph {
+    neighbors: HashMap<InterestCategory, Vec<(InterestCategory, f64)>>,
+}
+
+impl InterestGraph {
+    /// The graph shipped in `data/interest_graph.json`
+    pub fn builtin() -> &'static InterestGraph {
+        static GRAPH: OnceLock<InterestGraph> = OnceLock::new();
+        GRAPH.get_or_init(|| {
+            InterestGraph::from_json(BUILTIN_GRAPH).expect("built-in interest graph is valid")
+        })
+    }
+
+    /// Load `[from, to, weight]` edges; later duplicates add to earlier ones
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        let edges: Vec<(InterestCategory, InterestCategory, f64)> = serde_json::from_str(json)?;
+
+        let mut graph = InterestGraph::default();
+        for (from, to, weight) in edges {
+            if from == to || weight <= 0.0 {
+                continue;
+            }
+            graph.add(&from, &to, weight);
+            graph.add(&to, &from, weight);
+        }
+        Ok(graph)
+    }
+
+    fn add(&mut self, from: &InterestCategory, to: &InterestCategory, weight: f64) {
+        let edges = self.neighbors.entry(from.clone()).or_default();
+        match edges.iter_mut().find(|(category, _)| category == to) {
+            Some((_, existing)) => *existing += weight,
+            None => edges.push((to.clone(), weight)),
+        }
+    }
+
+    /// Related interests and edge weights, in the order they were declared
+    pub fn neighbors(&self, category: &InterestCategory) -> &[(InterestCategory, f64)] {
+        self.neighbors.get(category).map_or(&[], Vec::as_slice)
+    }
+}
+
+/// How far a session may drift from where it started
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DriftConfig {
+    /// Chance the next activity stays on the current interest
+    pub stay_probability: f64,
+    /// Chance an activity that doesn't stay starts afresh from the
+    /// profile's affinities rather than moving to a related interest
+    pub restart_probability: f64,
+    /// Pull of interests the profile doesn't declare, relative to a declared
+    /// interest of average affinity; 0 keeps drift inside the profile
+    pub outside_weight: f64,
+    /// Consecutive activities allowed outside the profile before drift must
+    /// lead back into it
+    pub max_hops_outside: u32,
+}
+
+impl Default for DriftConfig {
+    fn default() -> Self {
+        Self {
+            stay_probability: 0.6,
+            restart_probability: 0.3,
+            outside_weight: 0.3,
+            max_hops_outside: 2,
+        }
+    }
+}
+
+impl DriftConfig {
+    /// Every activity draws a fresh interest from the profile's affinities
+    pub fn disabled() -> Self {
+        Self {
+            stay_probability: 0.0,
+            restart_probability: 1.0,
+            outside_weight: 0.0,
+            max_hops_outside: 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_graph_is_symmetric_and_safe() {
+        let graph = InterestGraph::builtin();
+        for category in &InterestCategory::ALL {
+            for (to, weight) in graph.neighbors(category) {
+                assert!(!to.is_sensitive());
+                assert!(graph
+                    .neighbors(to)
+                    .iter()
+                    .any(|(back, w)| back == category && w == weight));
+            }
+            if category.is_sensitive() {
+                assert!(graph.neighbors(category).is_empty());
+            } else {
+                assert!(!graph.neighbors(category).is_empty(), "{:?}", category);
+            }
+        }
+    }
+
+    #[test]
+    fn test_duplicate_edges_accumulate() {
+        let graph = InterestGraph::from_json(
+            r#"[["Cooking", "Travel", 1], ["Travel", "Cooking", 2], ["Art", "Art", 5]]"#,
+        )
+        .unwrap();
+        assert_eq!(
+            graph.neighbors(&InterestCategory::Cooking),
+            &[(InterestCategory::Travel, 3.0)]
+        );
+        assert!(graph.neighbors(&InterestCategory::Art).is_empty());
+    }
+}
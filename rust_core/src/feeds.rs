@@ -0,0 +1,236 @@
+use crate::profile::InterestCategory;
+use crate::url_template;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Local XML document to ingest, tagged with the interest its entries belong to
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeedSource {
+    pub category: InterestCategory,
+    /// Contents of a `sitemap.xml`, RSS 2.0/1.0 or Atom file
+    pub xml: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FeedFormat {
+    Sitemap,
+    Rss,
+    Atom,
+}
+
+/// A URL and title read from a feed
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FeedEntry {
+    pub url: String,
+    pub title: String,
+}
+
+/// Entries parsed from one document, plus how many were unusable
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ParsedFeed {
+    pub format: FeedFormat,
+    pub entries: Vec<FeedEntry>,
+    pub skipped: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FeedError {
+    /// The document is not well-formed XML
+    Xml(String),
+    /// A sitemap index lists other sitemaps rather than pages
+    SitemapIndex,
+    /// The root element is not one we know how to read
+    UnsupportedRoot(String),
+}
+
+impl fmt::Display for FeedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FeedError::Xml(message) => write!(f, "invalid XML: {}", message),
+            FeedError::SitemapIndex => {
+                write!(
+                    f,
+                    "sitemap index files must be ingested one child sitemap at a time"
+                )
+            }
+            FeedError::UnsupportedRoot(root) => write!(f, "unsupported document root <{}>", root),
+        }
+    }
+}
+
+impl std::error::Error for FeedError {}
+
+/// Parse a sitemap, RSS or Atom document
+///
+/// The format is detected from the root element. Entries whose URL doesn't
+/// parse as absolute http(s), or which have no usable title, are skipped.
+/// Sitemap pages without a `news:title` get one derived from their slug.
+pub fn parse_feed(xml: &str) -> Result<ParsedFeed, FeedError> {
+    let doc = roxmltree::Document::parse(xml).map_err(|e| FeedError::Xml(e.to_string()))?;
+    let root = doc.root_element();
+
+    let (format, raw) = match root.tag_name().name() {
+        "urlset" => (FeedFormat::Sitemap, sitemap_entries(root)),
+        "sitemapindex" => return Err(FeedError::SitemapIndex),
+        "rss" | "RDF" => (FeedFormat::Rss, rss_entries(root)),
+        "feed" => (FeedFormat::Atom, atom_entries(root)),
+        other => return Err(FeedError::UnsupportedRoot(other.to_string())),
+    };
+
+    let total = raw.len();
+    let mut entries: Vec<FeedEntry> = Vec::new();
+    for (url, title) in raw {
+        let Ok(url) = url_template::validate(url.trim()) else {
+            continue;
+        };
+        let title = title
+            .map(|t| normalize_whitespace(&t))
+            .filter(|t| !t.is_empty())
+            .or_else(|| title_from_path(&url));
+        let Some(title) = title else {
+            continue;
+        };
+
+        let url = String::from(url);
+        if !entries.iter().any(|e| e.url == url) {
+            entries.push(FeedEntry { url, title });
+        }
+    }
+
+    Ok(ParsedFeed {
+        format,
+        skipped: total - entries.len(),
+        entries,
+    })
+}
+
+type RawEntry = (String, Option<String>);
+
+fn child<'a, 'i>(node: roxmltree::Node<'a, 'i>, name: &str) -> Option<roxmltree::Node<'a, 'i>> {
+    node.children()
+        .find(|n| n.is_element() && n.tag_name().name() == name)
+}
+
+fn child_text(node: roxmltree::Node, name: &str) -> Option<String> {
+    child(node, name).map(|n| n.text().unwrap_or_default().to_string())
+}
+
+fn sitemap_entries(root: roxmltree::Node) -> Vec<RawEntry> {
+    root.children()
+        .filter(|n| n.tag_name().name() == "url")
+        .filter_map(|url| {
+            let loc = child_text(url, "loc")?;
+            let title = child(url, "news").and_then(|news| child_text(news, "title"));
+            Some((loc, title))
+        })
+        .collect()
+}
+
+fn rss_entries(root: roxmltree::Node) -> Vec<RawEntry> {
+    // RSS 2.0 nests items in <channel>; RSS 1.0 puts them beside it
+    root.descendants()
+        .filter(|n| n.tag_name().name() == "item")
+        .filter_map(|item| Some((child_text(item, "link")?, child_text(item, "title"))))
+        .collect()
+}
+
+fn atom_entries(root: roxmltree::Node) -> Vec<RawEntry> {
+    root.children()
+        .filter(|n| n.tag_name().name() == "entry")
+        .filter_map(|entry| {
+            let link = entry
+                .children()
+                .filter(|n| n.tag_name().name() == "link")
+                .find(|n| matches!(n.attribute("rel"), None | Some("alternate")))?;
+            Some((
+                link.attribute("href")?.to_string(),
+                child_text(entry, "title"),
+            ))
+        })
+        .collect()
+}
+
+fn normalize_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Turn the last meaningful path segment into a title, e.g.
+/// `/news/how-to-grow-tomatoes.html` -> "How To Grow Tomatoes"
+fn title_from_path(url: &url::Url) -> Option<String> {
+    let segment = url
+        .path_segments()?
+        .rev()
+        .map(|s| s.split('.').next().unwrap_or(s))
+        .find(|s| s.chars().any(char::is_alphabetic))?;
+
+    let words: Vec<String> = segment
+        .split(['-', '_', '+'])
+        .filter(|w| !w.is_empty() && !w.chars().all(|c| c.is_ascii_digit()))
+        .map(|w| {
+            let mut chars = w.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => String::new(),
+            }
+        })
+        .collect();
+
+    (!words.is_empty()).then(|| words.join(" "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_rss_and_atom() {
+        let rss = r#"<?xml version="1.0"?>
+            <rss version="2.0"><channel><title>Garden Weekly</title>
+              <item><title>  Ten tomatoes
+                to try this spring </title><link>https://gardens.example/tomatoes</link></item>
+              <item><title>Broken</title><link>not a url</link></item>
+            </channel></rss>"#;
+        let parsed = parse_feed(rss).unwrap();
+        assert_eq!(parsed.format, FeedFormat::Rss);
+        assert_eq!(parsed.skipped, 1);
+        assert_eq!(parsed.entries[0].title, "Ten tomatoes to try this spring");
+
+        let atom = r#"<feed xmlns="http://www.w3.org/2005/Atom">
+              <entry><title>Release notes</title>
+                <link rel="self" href="https://blog.example/feed/1"/>
+                <link href="https://blog.example/posts/release-notes"/></entry>
+            </feed>"#;
+        let parsed = parse_feed(atom).unwrap();
+        assert_eq!(parsed.format, FeedFormat::Atom);
+        assert_eq!(
+            parsed.entries[0].url,
+            "https://blog.example/posts/release-notes"
+        );
+    }
+
+    #[test]
+    fn test_parse_sitemap_derives_titles() {
+        let sitemap = r#"<urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9"
+                xmlns:news="http://www.google.com/schemas/sitemap-news/0.9">
+              <url><loc>https://news.example/2024/05/how-to-grow-tomatoes.html</loc></url>
+              <url><loc>https://news.example/a/123</loc>
+                <news:news><news:title>Rain expected all week</news:title></news:news></url>
+              <url><loc>https://news.example/42</loc></url>
+            </urlset>"#;
+        let parsed = parse_feed(sitemap).unwrap();
+
+        assert_eq!(parsed.format, FeedFormat::Sitemap);
+        assert_eq!(parsed.entries[0].title, "How To Grow Tomatoes");
+        assert_eq!(parsed.entries[1].title, "Rain expected all week");
+        assert_eq!(parsed.skipped, 1);
+
+        assert_eq!(
+            parse_feed("<sitemapindex/>").unwrap_err(),
+            FeedError::SitemapIndex
+        );
+        assert!(matches!(
+            parse_feed("<html/>"),
+            Err(FeedError::UnsupportedRoot(_))
+        ));
+    }
+}
@@ -0,0 +1,901 @@
+use crate::locale::Locale;
+use crate::profile::InterestCategory;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Typed slots a query template can reference as `#slot#`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Slot {
+    /// Named things: products, teams, people, places
+    Entity,
+    /// A noun optionally qualified by an adjective
+    Topic,
+    Noun,
+    Adjective,
+    /// Verb phrase used after a question word, e.g. "install"
+    Action,
+    /// Leading modifier, e.g. "best"
+    Prefix,
+    /// Trailing modifier, e.g. "for beginners"
+    Suffix,
+    /// Question opener, e.g. "how to"
+    Question,
+    Year,
+    Location,
+}
+
+impl Slot {
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "entity" => Some(Slot::Entity),
+            "topic" => Some(Slot::Topic),
+            "noun" => Some(Slot::Noun),
+            "adjective" => Some(Slot::Adjective),
+            "action" => Some(Slot::Action),
+            "prefix" => Some(Slot::Prefix),
+            "suffix" => Some(Slot::Suffix),
+            "question" => Some(Slot::Question),
+            "year" => Some(Slot::Year),
+            "location" => Some(Slot::Location),
+            _ => None,
+        }
+    }
+}
+
+/// Query template with a relative weight
+///
+/// Weights are tuned so most queries are two to four words with a long tail,
+/// which is roughly what search logs show.
+#[derive(Debug, Clone, Copy)]
+pub struct Template {
+    pub weight: u32,
+    pub pattern: &'static str,
+}
+
+const fn t(weight: u32, pattern: &'static str) -> Template {
+    Template { weight, pattern }
+}
+
+const BASE_TEMPLATES: &[Template] = &[
+    t(10, "#entity#"),
+    t(8, "#topic#"),
+    t(10, "#entity# #suffix#"),
+    t(9, "#prefix# #topic#"),
+    t(8, "#topic# #suffix#"),
+    t(6, "#entity# #topic#"),
+    t(4, "#prefix# #topic# #year#"),
+    t(3, "#entity# #suffix# #year#"),
+    t(2, "#prefix# #topic# #suffix# #year#"),
+    t(6, "#question# #action# #topic#"),
+    t(2, "#question# #action# #entity#"),
+    t(1, "#question# #action# #topic# #year#"),
+    t(3, "what is #entity#"),
+    t(2, "what is the best #topic#"),
+    t(2, "is #entity# worth it"),
+    t(3, "#entity# vs #entity#"),
+    t(2, "#topic# #suffix# reddit"),
+];
+
+/// Extra templates for interests people pursue somewhere physical
+const LOCAL_TEMPLATES: &[Template] = &[
+    t(4, "#topic# near me"),
+    t(3, "#topic# in #location#"),
+    t(2, "#prefix# #topic# in #location#"),
+    t(1, "#entity# #location# #year#"),
+];
+
+#[rustfmt::skip]
+const QUESTIONS: &[&str] = &[
+    "how to", "how do i", "how can i", "best way to", "easiest way to", "should i",
+];
+
+#[rustfmt::skip]
+const PREFIXES: &[&str] = &[
+    "best", "cheap", "easy", "top", "new", "free", "beginner", "affordable", "popular", "simple", "quick", "professional", "used", "budget", "top rated",
+];
+
+#[rustfmt::skip]
+const SUFFIXES: &[&str] = &[
+    "review", "reviews", "for beginners", "tips", "guide", "ideas", "explained", "price", "deals", "tutorial", "examples", "alternatives", "comparison", "list", "news", "problems", "checklist", "pros and cons", "for sale", "step by step",
+];
+
+/// Words that open a question-form query
+const QUESTION_WORDS: &[&str] = &[
+    "how", "what", "why", "when", "where", "who", "which", "is", "are", "can", "should", "does",
+    "do",
+];
+
+/// Function words that don't make a query any more specific
+const STOPWORDS: &[&str] = &[
+    "a", "an", "the", "to", "of", "for", "in", "on", "at", "and", "or", "vs", "i", "me", "my",
+    "it", "is", "are", "do", "does", "can", "how", "what", "why", "when", "where", "who", "which",
+    "should", "best", "way",
+];
+
+/// How many years back `#year#` reaches
+const YEAR_SPAN: i32 = 12;
+
+/// Vocabulary for one interest category
+#[derive(Debug, Clone, Default)]
+pub struct Lexicon {
+    pub entities: Vec<String>,
+    pub nouns: Vec<String>,
+    pub adjectives: Vec<String>,
+    pub actions: Vec<String>,
+    /// Whether location templates make sense for this vocabulary
+    pub local: bool,
+}
+
+impl Lexicon {
+    fn from_static(
+        entities: &[&str],
+        nouns: &[&str],
+        adjectives: &[&str],
+        actions: &[&str],
+        local: bool,
+    ) -> Self {
+        let owned = |words: &[&str]| words.iter().map(|w| w.to_string()).collect();
+        Self {
+            entities: owned(entities),
+            nouns: owned(nouns),
+            adjectives: owned(adjectives),
+            actions: owned(actions),
+            local,
+        }
+    }
+
+    /// Built-in vocabulary for an interest, or a general one for `None`
+    #[rustfmt::skip]
+    pub fn builtin(category: Option<&InterestCategory>) -> Self {
+        let Some(category) = category else {
+            return Self::from_static(
+                &["Amazon", "Netflix", "Wikipedia", "YouTube", "Target", "Costco"],
+                &["weather", "news", "recipes", "movies", "restaurants", "jobs", "deals"],
+                &["local", "free", "online", "weekend"],
+                &["find", "get", "watch", "compare"],
+                true,
+            );
+        };
+
+        match category {
+            InterestCategory::Technology => Self::from_static(
+                &["iPhone 15", "Pixel 8", "Galaxy S24", "MacBook Air", "iPad Pro", "Apple Watch", "Windows 11", "ChatGPT", "Nvidia", "AirPods Pro", "Steam Deck", "Raspberry Pi", "Starlink", "Kindle"],
+                &["smartphone", "laptop", "tablet", "smartwatch", "headphones", "router", "monitor", "charger", "ssd", "vpn", "password manager", "smart home hub", "webcam", "keyboard"],
+                &["wireless", "4k", "noise cancelling", "gaming", "budget", "foldable", "usb-c", "refurbished", "portable", "mesh"],
+                &[
+                    "fix", "set up", "reset", "speed up", "back up", "update", "choose a", "clean",
+                ],
+                false,
+            ),
+            InterestCategory::Gaming => Self::from_static(
+                &["Elden Ring", "Zelda Tears of the Kingdom", "Baldur's Gate 3", "Minecraft", "Fortnite", "Valorant", "Stardew Valley", "Cyberpunk 2077", "Nintendo Switch", "PS5", "Xbox Series X", "Hollow Knight", "League of Legends", "Diablo 4"],
+                &["walkthrough", "build", "boss fight", "speedrun", "mods", "controller", "gaming chair", "headset", "patch notes", "tier list", "release date", "co-op games", "indie games"],
+                &["best", "hidden", "open world", "multiplayer", "cozy", "retro", "early game", "endgame", "pvp"],
+                &["beat", "unlock", "get", "farm", "install", "level up"],
+                false,
+            ),
+            InterestCategory::Sports => Self::from_static(
+                &["Lakers", "Yankees", "Chiefs", "Real Madrid", "Manchester United", "Warriors", "Red Sox", "Cowboys", "Premier League", "NBA", "NFL", "MLB", "Champions League", "Super Bowl"],
+                &["scores", "schedule", "standings", "highlights", "tickets", "trade rumors", "draft picks", "injury report", "playoff bracket", "box score", "roster", "jersey"],
+                &["live", "fantasy", "college", "youth", "weekend", "home"],
+                &["watch", "stream", "buy", "join"],
+                true,
+            ),
+            InterestCategory::Fitness => Self::from_static(
+                &["Peloton", "CrossFit", "Couch to 5K", "Fitbit", "Garmin Forerunner", "Orangetheory", "Planet Fitness", "Whoop"],
+                &["workout", "running shoes", "protein powder", "resistance bands", "stretches", "leg day", "gym", "meal plan", "kettlebell routine", "rest day", "pilates", "yoga"],
+                &["full body", "home", "30 minute", "low impact", "hiit", "beginner", "strength", "morning"],
+                &["build", "lose", "train for", "improve", "start"],
+                true,
+            ),
+            InterestCategory::Cooking => Self::from_static(
+                &["Instant Pot", "Air Fryer", "KitchenAid", "Le Creuset", "Gordon Ramsay", "Ina Garten", "Trader Joe's", "Thanksgiving"],
+                &["pasta", "chicken thighs", "banana bread", "sourdough", "curry", "soup", "tacos", "meal prep", "lasagna", "salmon", "cookies", "stir fry", "chili", "pancakes"],
+                &["vegan", "gluten free", "spicy", "one pot", "slow cooker", "healthy", "easy", "keto", "crispy", "homemade"],
+                &["make", "cook", "bake", "freeze", "reheat", "season"],
+                true,
+            ),
+            InterestCategory::Travel => Self::from_static(
+                &["Tokyo", "Paris", "Lisbon", "Iceland", "Bali", "Yellowstone", "Costa Rica", "Rome", "Barcelona", "Banff", "Disney World", "Hawaii", "Mexico City", "Kyoto"],
+                &["flights", "hotels", "itinerary", "things to do", "travel insurance", "carry on", "airbnb", "road trip", "visa requirements", "packing list", "hostels", "train pass"],
+                &["cheap", "last minute", "family", "solo", "budget", "luxury", "weekend", "7 day"],
+                &["plan", "book", "visit", "get to", "pack for"],
+                true,
+            ),
+            InterestCategory::Fashion => Self::from_static(
+                &["Nike", "Zara", "Uniqlo", "Levi's", "Patagonia", "Adidas", "H&M", "Dr. Martens", "Lululemon", "Everlane"],
+                &["sneakers", "jeans", "winter coat", "dresses", "boots", "capsule wardrobe", "outfit", "handbag", "sunglasses", "rain jacket"],
+                &["vintage", "minimalist", "sustainable", "oversized", "casual", "work", "summer", "waterproof"],
+                &["style", "wash", "tailor", "dress for", "find"],
+                true,
+            ),
+            InterestCategory::Music => Self::from_static(
+                &["Taylor Swift", "Beyoncé", "Radiohead", "Kendrick Lamar", "Fender Stratocaster", "Spotify", "Coachella", "Billie Eilish", "The Beatles", "Glastonbury"],
+                &["lyrics", "chords", "tour dates", "vinyl", "concert tickets", "playlist", "guitar lessons", "album review", "piano", "record player"],
+                &["live", "acoustic", "new", "90s", "classic", "indie"],
+                &["play", "learn", "tune", "record", "mix"],
+                true,
+            ),
+            InterestCategory::Movies => Self::from_static(
+                &["Oppenheimer", "Dune Part Two", "Barbie", "Marvel", "Studio Ghibli", "Christopher Nolan", "Netflix", "A24", "Pixar", "Oscars"],
+                &["showtimes", "trailer", "cast", "ending explained", "box office", "streaming", "sequel", "soundtrack", "director's cut", "horror movies"],
+                &["new", "classic", "animated", "best", "upcoming", "underrated"],
+                &["watch", "stream", "rent", "download"],
+                true,
+            ),
+            InterestCategory::Books => Self::from_static(
+                &["Stephen King", "Brandon Sanderson", "Colleen Hoover", "Kindle Paperwhite", "Goodreads", "Project Hail Mary", "Fourth Wing", "Sally Rooney", "Audible", "Booker Prize"],
+                &["book club picks", "reading list", "audiobooks", "fantasy series", "summary", "sequel", "library hours", "ebooks", "memoirs", "thrillers"],
+                &["best", "new", "used", "signed", "short", "classic"],
+                &["read", "borrow", "find", "start"],
+                true,
+            ),
+            InterestCategory::Art => Self::from_static(
+                &["Van Gogh", "MoMA", "Procreate", "Frida Kahlo", "Banksy", "Louvre", "Winsor & Newton", "Monet"],
+                &["watercolor", "oil painting", "sketchbook", "gallery", "art supplies", "exhibition", "acrylic pour", "portrait", "canvas", "pottery class"],
+                &["abstract", "modern", "beginner", "digital", "landscape", "local"],
+                &["paint", "draw", "frame", "sell", "learn"],
+                true,
+            ),
+            InterestCategory::Science => Self::from_static(
+                &["James Webb Telescope", "NASA", "CERN", "Mars rover", "CRISPR", "Einstein", "SpaceX", "Nobel Prize"],
+                &["black holes", "climate change", "dna", "vaccines", "quantum mechanics", "evolution", "solar eclipse", "dark matter", "volcanoes", "fossils"],
+                &["latest", "simple", "new", "ancient"],
+                &["explain", "observe", "measure", "understand"],
+                false,
+            ),
+            InterestCategory::Politics => Self::from_static(
+                &["Supreme Court", "Senate", "Congress", "White House", "Federal Reserve", "United Nations", "NATO", "EU Parliament"],
+                &["election results", "polls", "primary", "bill", "debate", "voter registration", "ballot measures", "approval rating", "town hall", "budget"],
+                &["latest", "state", "local", "midterm", "new"],
+                &["register to", "vote in", "contact", "track"],
+                true,
+            ),
+            InterestCategory::News => Self::from_static(
+                &["AP News", "BBC", "Reuters", "NPR", "The Guardian", "CNN"],
+                &["headlines", "weather", "traffic", "breaking news", "local news", "storm warning", "power outage", "road closures"],
+                &["today's", "live", "latest", "weekend"],
+                &["check", "follow", "watch"],
+                true,
+            ),
+            InterestCategory::Finance => Self::from_static(
+                &["S&P 500", "Vanguard", "Fidelity", "Bitcoin", "Roth IRA", "Tesla stock", "Nasdaq", "Robinhood", "401k", "Ethereum"],
+                &["index funds", "mortgage rates", "savings account", "credit score", "dividend stocks", "budget", "tax brackets", "etf", "bonds", "credit card"],
+                &["high yield", "low fee", "best", "fixed", "long term", "passive"],
+                &["invest in", "pay off", "refinance", "open a", "save for"],
+                false,
+            ),
+            InterestCategory::HomeImprovement => Self::from_static(
+                &["Home Depot", "Lowe's", "IKEA", "DeWalt", "Ryobi", "Sherwin Williams", "Ring doorbell"],
+                &["drywall", "deck", "bathroom remodel", "kitchen cabinets", "paint colors", "leaky faucet", "tile", "insulation", "water heater", "fence"],
+                &["diy", "cheap", "modern", "outdoor", "small", "rustic"],
+                &["fix", "install", "replace", "paint", "repair"],
+                true,
+            ),
+            InterestCategory::Gardening => Self::from_static(
+                &["Burpee", "Miracle-Gro", "Monstera", "Japanese maple", "Fiddle Leaf Fig"],
+                &["tomatoes", "raised beds", "compost", "succulents", "hydrangeas", "seedlings", "mulch", "herb garden", "roses", "lawn"],
+                &["organic", "indoor", "perennial", "shade", "drought tolerant", "container"],
+                &["grow", "prune", "plant", "water", "propagate"],
+                true,
+            ),
+            InterestCategory::Photography => Self::from_static(
+                &["Sony A7 IV", "Canon R6", "Fujifilm X100V", "Lightroom", "Nikon Z6", "GoPro", "DJI Mini 4"],
+                &["lens", "tripod", "camera bag", "presets", "portrait photography", "film camera", "long exposure", "golden hour", "drone", "street photography"],
+                &["mirrorless", "35mm", "used", "wide angle", "vintage", "night"],
+                &["edit", "shoot", "focus", "clean", "calibrate"],
+                true,
+            ),
+            InterestCategory::Programming => Self::from_static(
+                &["Rust", "TypeScript", "Python", "React", "Kubernetes", "Docker", "Go", "PostgreSQL", "VS Code", "GitHub Actions", "Node.js", "Django", "Neovim", "WebAssembly"],
+                &["tutorial", "borrow checker", "async", "unit tests", "linked list", "regex", "error handling", "git rebase", "memory leak", "api", "dependency injection", "generics", "code review"],
+                &["simple", "async", "idiomatic", "fast", "typed", "recursive"],
+                &[
+                    "learn", "debug", "install", "deploy", "refactor", "optimize",
+                ],
+                false,
+            ),
+            InterestCategory::DataScience => Self::from_static(
+                &["pandas", "PyTorch", "scikit-learn", "Jupyter", "Kaggle", "TensorFlow", "Tableau", "Snowflake", "dbt"],
+                &["linear regression", "random forest", "feature engineering", "dataframe", "neural network", "time series", "a/b test", "confusion matrix", "sql query", "clustering"],
+                &["simple", "bayesian", "interactive", "large", "imbalanced"],
+                &["train", "visualize", "clean", "tune", "deploy"],
+                false,
+            ),
+            InterestCategory::HealthConditions => Self::from_static(
+                &["Metformin", "Lisinopril", "Ozempic", "Ibuprofen", "Omeprazole", "Levothyroxine", "Mayo Clinic", "WebMD"],
+                &["migraine", "acid reflux", "lower back pain", "high blood pressure", "type 2 diabetes", "thyroid", "eczema", "sleep apnea", "urgent care", "dermatologist", "blood test results", "cholesterol"],
+                &["chronic", "mild", "natural", "early", "persistent", "sudden"],
+                &["treat", "manage", "recognize", "relieve", "prevent", "lower"],
+                true,
+            ),
+            InterestCategory::MentalHealth => Self::from_static(
+                &["Sertraline", "Headspace", "BetterHelp", "Talkspace", "Calm", "Wellbutrin", "CBT"],
+                &["anxiety", "depression", "panic attacks", "insomnia", "burnout", "therapist", "grief", "adhd", "social anxiety", "stress", "counseling", "mood swings"],
+                &["postpartum", "seasonal", "high functioning", "adult", "generalized", "online"],
+                &["cope with", "deal with", "stop", "manage", "talk about", "help with"],
+                true,
+            ),
+            InterestCategory::LegalAdvice => Self::from_static(
+                &["LegalZoom", "Nolo", "Rocket Lawyer", "Avvo", "Chapter 7", "Chapter 13", "Miranda rights"],
+                &["divorce lawyer", "tenant rights", "will", "custody agreement", "small claims court", "eviction notice", "power of attorney", "workers comp", "expungement", "prenup", "dui attorney", "employment lawyer"],
+                &["uncontested", "free", "no fault", "contested", "pro bono", "online"],
+                &["file for", "contest", "write a", "appeal", "respond to", "dispute"],
+                true,
+            ),
+            InterestCategory::Immigration => Self::from_static(
+                &["USCIS", "DACA", "H-1B", "Green Card", "ESTA", "I-130", "N-400", "TPS"],
+                &["visa interview", "work permit", "citizenship test", "asylum", "immigration lawyer", "visa status", "naturalization", "biometrics appointment", "sponsorship", "processing times", "student visa", "travel document"],
+                &["expedited", "pending", "conditional", "family based", "employment based", "renewal"],
+                &["apply for", "check", "renew", "extend", "prepare for", "track"],
+                true,
+            ),
+        }
+    }
+}
+
+/// Templates and shared slot tables for one locale
+#[derive(Debug, Clone)]
+struct Phrasebook {
+    templates: Vec<Template>,
+    local_templates: Vec<Template>,
+    questions: Vec<&'static str>,
+    prefixes: Vec<&'static str>,
+    suffixes: Vec<&'static str>,
+    locations: Vec<&'static str>,
+    /// Opening words of questions, the English ones included since
+    /// searchers mix languages
+    question_words: Vec<&'static str>,
+    stopwords: Vec<&'static str>,
+    /// Whether `#topic#` puts the adjective after the noun
+    adjective_after: bool,
+}
+
+impl Phrasebook {
+    fn for_locale(locale: &Locale) -> Self {
+        let borrowed = |words: &'static [String]| words.iter().map(String::as_str).collect();
+        let locations = borrowed(&locale.country_data().locations);
+
+        match locale.language_data() {
+            Some(language) => Self {
+                templates: language.templates(),
+                local_templates: language.local_templates(),
+                questions: borrowed(&language.questions),
+                prefixes: borrowed(&language.prefixes),
+                suffixes: borrowed(&language.suffixes),
+                locations,
+                question_words: QUESTION_WORDS
+                    .iter()
+                    .copied()
+                    .chain(language.question_words.iter().map(String::as_str))
+                    .collect(),
+                stopwords: STOPWORDS
+                    .iter()
+                    .copied()
+                    .chain(language.stopwords.iter().map(String::as_str))
+                    .collect(),
+                adjective_after: language.adjective_after,
+            },
+            None => Self {
+                templates: BASE_TEMPLATES.to_vec(),
+                local_templates: LOCAL_TEMPLATES.to_vec(),
+                questions: QUESTIONS.to_vec(),
+                prefixes: PREFIXES.to_vec(),
+                suffixes: SUFFIXES.to_vec(),
+                locations,
+                question_words: QUESTION_WORDS.to_vec(),
+                stopwords: STOPWORDS.to_vec(),
+                adjective_after: false,
+            },
+        }
+    }
+}
+
+/// Tracery-style generator for long-tail search queries
+///
+/// Templates reference typed slots (`#entity#`, `#year#`, ...) which expand
+/// from the category's [`Lexicon`] or the shared tables. Output depends only
+/// on the RNG, `latest_year` and the locale, so a seeded RNG reproduces the
+/// same queries.
+#[derive(Debug, Clone)]
+pub struct QueryGrammar {
+    latest_year: i32,
+    lexicons: HashMap<Option<InterestCategory>, Lexicon>,
+    phrasebook: Phrasebook,
+}
+
+impl QueryGrammar {
+    /// Grammar for US English
+    pub fn new(latest_year: i32) -> Self {
+        Self::for_locale(latest_year, &Locale::default())
+    }
+
+    /// Grammar writing queries in the locale's language, with its places
+    ///
+    /// [`features`](Self::features) recognises questions by their opening
+    /// words in the locale's language as well as in English.
+    pub fn for_locale(latest_year: i32, locale: &Locale) -> Self {
+        let language = locale.language_data();
+        let lexicons = InterestCategory::ALL
+            .iter()
+            .map(Some)
+            .chain(std::iter::once(None))
+            .map(|category| {
+                let lexicon = match language {
+                    Some(language) => language.lexicon(category),
+                    None => Lexicon::builtin(category),
+                };
+                (category.cloned(), lexicon)
+            })
+            .collect();
+
+        Self {
+            latest_year,
+            lexicons,
+            phrasebook: Phrasebook::for_locale(locale),
+        }
+    }
+
+    /// Generate a query for an interest
+    pub fn generate<R: Rng>(&self, category: Option<&InterestCategory>, rng: &mut R) -> String {
+        self.generate_with(self.lexicon(category), rng)
+    }
+
+    /// Vocabulary used for an interest
+    pub fn lexicon(&self, category: Option<&InterestCategory>) -> &Lexicon {
+        &self.lexicons[&category.cloned()]
+    }
+
+    /// Generate a query from an explicit vocabulary
+    pub fn generate_with<R: Rng>(&self, lexicon: &Lexicon, rng: &mut R) -> String {
+        let templates = self.templates(lexicon);
+        let template = templates
+            .choose_weighted(rng, |t| t.weight)
+            .expect("template weights are positive");
+        let query = self.expand(template.pattern, lexicon, rng);
+
+        // Most people don't bother with capitals when typing into a search box
+        if rng.gen_bool(0.6) {
+            query.to_lowercase()
+        } else {
+            query
+        }
+    }
+
+    /// Number of queries an interest's vocabulary can produce
+    ///
+    /// A slot used twice in one template only counts different values, as
+    /// generation re-draws repeats, so "#entity# vs #entity#" never counts
+    /// comparing an entity with itself. Two templates occasionally spell the
+    /// same query ("cheap hotels" is a prefix and a noun, or an adjective and
+    /// a noun); those few are counted twice.
+    pub fn distinct_queries(&self, category: Option<&InterestCategory>) -> u64 {
+        let lexicon = self.lexicon(category);
+        self.templates(lexicon)
+            .iter()
+            .map(|template| {
+                let mut seen = Vec::new();
+                slots(template.pattern)
+                    .map(|slot| {
+                        let repeats = seen.iter().filter(|&&s| s == slot).count() as u64;
+                        seen.push(slot);
+                        self.slot_size(slot, lexicon).saturating_sub(repeats)
+                    })
+                    .product::<u64>()
+            })
+            .sum()
+    }
+
+    /// Measure the shape of a query
+    pub fn features(&self, query: &str) -> QueryFeatures {
+        let tokens: Vec<&str> = query.split_whitespace().collect();
+        let lower = query.to_lowercase();
+
+        let form = match tokens.first() {
+            _ if query.trim_end().ends_with('?') => QueryForm::Question,
+            Some(first)
+                if self
+                    .phrasebook
+                    .question_words
+                    .contains(&first.to_lowercase().as_str()) =>
+            {
+                QueryForm::Question
+            }
+            _ => QueryForm::Keyword,
+        };
+
+        let has_year = tokens.iter().any(|t| is_year(t));
+        let has_entity = self.has_known_entity(&lower)
+            || tokens.iter().enumerate().any(|(i, token)| {
+                // A capital after the first word, or a model number like "ps5"
+                let capitalized = i > 0 && token.chars().next().is_some_and(char::is_uppercase);
+                let model_number = !is_year(token)
+                    && token.chars().any(|c| c.is_ascii_digit())
+                    && (token.chars().any(char::is_alphabetic) || i > 0);
+                capitalized || model_number
+            });
+
+        let content_tokens = tokens
+            .iter()
+            .filter(|t| {
+                !self
+                    .phrasebook
+                    .stopwords
+                    .contains(&t.to_lowercase().as_str())
+            })
+            .count();
+        let score = content_tokens + has_entity as usize + has_year as usize;
+        let specificity = match score {
+            0..=2 => Specificity::Broad,
+            3..=4 => Specificity::Moderate,
+            _ => Specificity::Specific,
+        };
+
+        QueryFeatures {
+            tokens: tokens.len(),
+            form,
+            has_entity,
+            specificity,
+            lowercase: query == lower,
+        }
+    }
+
+    /// Best guess at which interest a query belongs to
+    pub fn classify(&self, query: &str) -> Option<InterestCategory> {
+        let lower = query.to_lowercase();
+        InterestCategory::ALL
+            .iter()
+            .map(|category| {
+                let lexicon = self.lexicon(Some(category));
+                let hits = lexicon
+                    .entities
+                    .iter()
+                    .chain(&lexicon.nouns)
+                    .filter(|word| contains_phrase(&lower, &word.to_lowercase()))
+                    .count();
+                (category, hits)
+            })
+            .filter(|(_, hits)| *hits > 0)
+            .max_by_key(|(_, hits)| *hits)
+            .map(|(category, _)| category.clone())
+    }
+
+    fn has_known_entity(&self, lower: &str) -> bool {
+        self.lexicons
+            .values()
+            .flat_map(|lexicon| &lexicon.entities)
+            .any(|entity| contains_phrase(lower, &entity.to_lowercase()))
+    }
+
+    fn templates(&self, lexicon: &Lexicon) -> Vec<Template> {
+        let mut templates = self.phrasebook.templates.clone();
+        if lexicon.local {
+            templates.extend_from_slice(&self.phrasebook.local_templates);
+        }
+        templates
+    }
+
+    fn expand<R: Rng>(&self, pattern: &str, lexicon: &Lexicon, rng: &mut R) -> String {
+        let mut out = String::new();
+        let mut used = Vec::new();
+        for (i, part) in pattern.split('#').enumerate() {
+            // Odd-numbered parts sit between a pair of '#'
+            match (i % 2 == 1).then(|| Slot::parse(part)).flatten() {
+                Some(slot) => {
+                    // Re-draw a few times so "#entity# vs #entity#" compares two things
+                    let mut value = self.expand_slot(slot, lexicon, rng);
+                    for _ in 0..3 {
+                        if !used.contains(&value) {
+                            break;
+                        }
+                        value = self.expand_slot(slot, lexicon, rng);
+                    }
+                    out.push_str(&value);
+                    used.push(value);
+                }
+                None => out.push_str(part),
+            }
+        }
+        out
+    }
+
+    fn expand_slot<R: Rng>(&self, slot: Slot, lexicon: &Lexicon, rng: &mut R) -> String {
+        let pick = |words: &[String], rng: &mut R| words.choose(rng).cloned().unwrap_or_default();
+        let pick_static = |words: &[&str], rng: &mut R| words.choose(rng).unwrap().to_string();
+        let phrasebook = &self.phrasebook;
+
+        match slot {
+            Slot::Entity => pick(&lexicon.entities, rng),
+            Slot::Noun => pick(&lexicon.nouns, rng),
+            Slot::Adjective => pick(&lexicon.adjectives, rng),
+            Slot::Action => pick(&lexicon.actions, rng),
+            Slot::Topic => {
+                if !lexicon.adjectives.is_empty() && rng.gen_bool(0.35) {
+                    let adjective = pick(&lexicon.adjectives, rng);
+                    let noun = pick(&lexicon.nouns, rng);
+                    if phrasebook.adjective_after {
+                        format!("{} {}", noun, adjective)
+                    } else {
+                        format!("{} {}", adjective, noun)
+                    }
+                } else {
+                    pick(&lexicon.nouns, rng)
+                }
+            }
+            Slot::Prefix => pick_static(&phrasebook.prefixes, rng),
+            Slot::Suffix => pick_static(&phrasebook.suffixes, rng),
+            Slot::Question => pick_static(&phrasebook.questions, rng),
+            Slot::Location => pick_static(&phrasebook.locations, rng),
+            Slot::Year => rng
+                .gen_range(self.latest_year - YEAR_SPAN + 1..=self.latest_year)
+                .to_string(),
+        }
+    }
+
+    fn slot_size(&self, slot: Slot, lexicon: &Lexicon) -> u64 {
+        let len = |words: &[String]| words.len().max(1) as u64;
+        match slot {
+            Slot::Entity => len(&lexicon.entities),
+            Slot::Noun => len(&lexicon.nouns),
+            Slot::Adjective => len(&lexicon.adjectives),
+            Slot::Action => len(&lexicon.actions),
+            Slot::Topic => len(&lexicon.nouns) * (1 + lexicon.adjectives.len() as u64),
+            Slot::Prefix => self.phrasebook.prefixes.len() as u64,
+            Slot::Suffix => self.phrasebook.suffixes.len() as u64,
+            Slot::Question => self.phrasebook.questions.len() as u64,
+            Slot::Location => self.phrasebook.locations.len() as u64,
+            Slot::Year => YEAR_SPAN as u64,
+        }
+    }
+}
+
+/// Whether a query is phrased as a question or as keywords
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum QueryForm {
+    Question,
+    Keyword,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Specificity {
+    Broad,
+    Moderate,
+    Specific,
+}
+
+/// Shape of a search query, independent of its topic
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QueryFeatures {
+    pub tokens: usize,
+    pub form: QueryForm,
+    pub has_entity: bool,
+    pub specificity: Specificity,
+    /// Typed without any capitals
+    pub lowercase: bool,
+}
+
+impl QueryFeatures {
+    /// How far apart two query shapes are; zero means indistinguishable
+    pub fn mismatch(&self, other: &QueryFeatures) -> usize {
+        self.tokens.abs_diff(other.tokens)
+            + 3 * (self.form != other.form) as usize
+            + 2 * (self.has_entity != other.has_entity) as usize
+            + (self.specificity as usize).abs_diff(other.specificity as usize)
+    }
+}
+
+fn is_year(token: &str) -> bool {
+    token.len() == 4 && token.starts_with(['1', '2']) && token.chars().all(|c| c.is_ascii_digit())
+}
+
+/// Whether `phrase` occurs in `text` on word boundaries
+fn contains_phrase(text: &str, phrase: &str) -> bool {
+    text.match_indices(phrase).any(|(start, _)| {
+        let end = start + phrase.len();
+        let before = text[..start].chars().next_back();
+        let after = text[end..].chars().next();
+        !before.is_some_and(char::is_alphanumeric) && !after.is_some_and(char::is_alphanumeric)
+    })
+}
+
+/// Slots referenced by a template pattern
+fn slots(pattern: &str) -> impl Iterator<Item = Slot> + '_ {
+    pattern
+        .split('#')
+        .skip(1)
+        .step_by(2)
+        .filter_map(Slot::parse)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::SmallRng;
+    use rand::SeedableRng;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_seeded_generation_is_deterministic() {
+        let grammar = QueryGrammar::new(2024);
+        let mut rng1 = SmallRng::seed_from_u64(42);
+        let mut rng2 = SmallRng::seed_from_u64(42);
+
+        for _ in 0..100 {
+            let category = Some(&InterestCategory::Cooking);
+            assert_eq!(
+                grammar.generate(category, &mut rng1),
+                grammar.generate(category, &mut rng2)
+            );
+        }
+    }
+
+    #[test]
+    fn test_long_tail_variety_and_length() {
+        let grammar = QueryGrammar::new(2024);
+        let mut rng = SmallRng::seed_from_u64(7);
+
+        let queries: Vec<String> = (0..5000)
+            .map(|_| grammar.generate(Some(&InterestCategory::Programming), &mut rng))
+            .collect();
+        let distinct: HashSet<_> = queries.iter().collect();
+        assert!(
+            distinct.len() > 2500,
+            "only {} distinct queries",
+            distinct.len()
+        );
+
+        let lengths: Vec<usize> = queries
+            .iter()
+            .map(|q| q.split_whitespace().count())
+            .collect();
+        let mean = lengths.iter().sum::<usize>() as f64 / lengths.len() as f64;
+        assert!((2.0..4.5).contains(&mean), "mean length {}", mean);
+        assert!(lengths.iter().any(|&len| len >= 6));
+    }
+
+    #[test]
+    fn test_query_features() {
+        let grammar = QueryGrammar::new(2024);
+
+        let question = grammar.features("how do i fix a leaky faucet");
+        assert_eq!(question.form, QueryForm::Question);
+        assert_eq!(question.tokens, 7);
+        assert!(!question.has_entity);
+        assert!(question.lowercase);
+
+        let keyword = grammar.features("iphone 15 review");
+        assert_eq!(keyword.form, QueryForm::Keyword);
+        assert!(keyword.has_entity);
+
+        assert_eq!(grammar.features("weather").specificity, Specificity::Broad);
+        assert_eq!(
+            grammar
+                .features("best rust async tutorial for beginners 2024")
+                .specificity,
+            Specificity::Specific
+        );
+        assert_eq!(
+            grammar.classify("rust borrow checker"),
+            Some(InterestCategory::Programming)
+        );
+
+        let german = QueryGrammar::for_locale(2024, &Locale::parse("de-DE").unwrap());
+        let french = QueryGrammar::for_locale(2024, &Locale::parse("fr-FR").unwrap());
+        assert_eq!(
+            german.features("wie koche ich nudeln").form,
+            QueryForm::Question
+        );
+        assert_eq!(
+            german.features("how to cook pasta").form,
+            QueryForm::Question
+        );
+        assert_eq!(german.features("beste laufschuhe").form, QueryForm::Keyword);
+        assert_eq!(
+            german.features("wie koche ich nudeln").specificity,
+            Specificity::Broad
+        );
+        assert_eq!(
+            french.features("comment faire du pain").form,
+            QueryForm::Question
+        );
+        assert_eq!(
+            grammar.features("comment faire du pain").form,
+            QueryForm::Keyword
+        );
+    }
+
+    /// Every value a slot can expand to
+    fn slot_values(grammar: &QueryGrammar, slot: Slot, lexicon: &Lexicon) -> Vec<String> {
+        let owned = |words: &[&str]| words.iter().map(|w| w.to_string()).collect();
+        let phrasebook = &grammar.phrasebook;
+        match slot {
+            Slot::Entity => lexicon.entities.clone(),
+            Slot::Noun => lexicon.nouns.clone(),
+            Slot::Adjective => lexicon.adjectives.clone(),
+            Slot::Action => lexicon.actions.clone(),
+            Slot::Topic => lexicon
+                .adjectives
+                .iter()
+                .flat_map(|adjective| {
+                    lexicon
+                        .nouns
+                        .iter()
+                        .map(move |noun| format!("{} {}", adjective, noun))
+                })
+                .chain(lexicon.nouns.iter().cloned())
+                .collect(),
+            Slot::Prefix => owned(&phrasebook.prefixes),
+            Slot::Suffix => owned(&phrasebook.suffixes),
+            Slot::Question => owned(&phrasebook.questions),
+            Slot::Location => owned(&phrasebook.locations),
+            Slot::Year => (grammar.latest_year - YEAR_SPAN + 1..=grammar.latest_year)
+                .map(|year| year.to_string())
+                .collect(),
+        }
+    }
+
+    /// Every query a category can produce, without repeating a value
+    fn all_queries(grammar: &QueryGrammar, category: &InterestCategory) -> HashSet<String> {
+        let lexicon = grammar.lexicon(Some(category));
+        let mut queries = HashSet::new();
+        for template in grammar.templates(lexicon) {
+            let mut partial = vec![(String::new(), Vec::new())];
+            for (i, part) in template.pattern.split('#').enumerate() {
+                let values = match (i % 2 == 1).then(|| Slot::parse(part)).flatten() {
+                    Some(slot) => slot_values(grammar, slot, lexicon),
+                    None => {
+                        for (text, _) in &mut partial {
+                            text.push_str(part);
+                        }
+                        continue;
+                    }
+                };
+                partial = partial
+                    .into_iter()
+                    .flat_map(|(text, used)| {
+                        values
+                            .iter()
+                            .filter(|value| !used.contains(*value))
+                            .map(|value| {
+                                let mut used = used.clone();
+                                used.push(value.clone());
+                                (format!("{}{}", text, value), used)
+                            })
+                            .collect::<Vec<_>>()
+                    })
+                    .collect();
+            }
+            queries.extend(partial.into_iter().map(|(text, _)| text));
+        }
+        queries
+    }
+
+    #[test]
+    fn test_grammar_space_per_category() {
+        let grammar = QueryGrammar::new(2024);
+
+        // Enumerating is slow, so check the count against two categories
+        for category in [InterestCategory::News, InterestCategory::Books] {
+            let counted = grammar.distinct_queries(Some(&category));
+            let enumerated = all_queries(&grammar, &category).len() as u64;
+            assert!(enumerated <= counted);
+            assert!(
+                counted - enumerated < counted / 100,
+                "{:?}: counted {} but only {} distinct",
+                category,
+                counted,
+                enumerated
+            );
+        }
+
+        let mut total = 0;
+        for category in InterestCategory::ALL.iter() {
+            let count = grammar.distinct_queries(Some(category));
+            assert!(count > 150_000, "{:?}: only {} queries", category, count);
+            total += count;
+        }
+        assert!(total > 5_000_000, "only {} queries", total);
+    }
+}
@@ -0,0 +1,323 @@
+use crate::activity::{ActivitySimulator, BrowsingActivity};
+use crate::device::{Device, DeviceProfile, FormFactor};
+use crate::profile::Profile;
+use crate::rng::{FastRng, LiveRng};
+use crate::schedule::Schedule;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+
+/// Shortest and longest time one member keeps the device, in minutes
+const TURN_MINUTES: (i64, i64) = (15, 90);
+
+/// Shortest and longest pause between turns, in minutes
+const HANDOVER_MINUTES: (i64, i64) = (2, 20);
+
+/// Shortest and longest time the device sits unused before someone might
+/// pick it up again, in minutes
+const IDLE_MINUTES: (i64, i64) = (15, 60);
+
+/// Several profiles taking turns on one shared device
+///
+/// A combined schedule decides who is at the device. Each member wants it
+/// as often as their own [`Schedule`] makes them browse, so the device is
+/// in use when at least one of them does and sits idle otherwise; among
+/// those who want it, the keener ones get it more often. Each turn is one
+/// of that member's sessions, so interests drift within a turn but never
+/// leak from one member to the next.
+///
+/// Not to be confused with a persona's [`Household`](crate::Household),
+/// which only tells who the persona lives with.
+pub struct SharedHousehold<R = FastRng> {
+    members: Vec<ActivitySimulator<R>>,
+    schedules: Vec<Schedule>,
+    rng: R,
+}
+
+impl SharedHousehold {
+    /// # Panics
+    ///
+    /// If `members` is empty.
+    pub fn new(members: Vec<Profile>) -> Self {
+        Self::from_rng(members, FastRng::from_entropy())
+    }
+}
+
+impl SharedHousehold<LiveRng> {
+    /// A household whose upcoming activities can't be predicted from the
+    /// ones an observer has already seen
+    ///
+    /// # Panics
+    ///
+    /// If `members` is empty.
+    pub fn live(members: Vec<Profile>) -> Self {
+        Self::from_rng(members, LiveRng::from_entropy())
+    }
+}
+
+impl<R: Rng + SeedableRng> SharedHousehold<R> {
+    /// Members share the first member's desktop
+    ///
+    /// # Panics
+    ///
+    /// If `members` is empty.
+    pub fn from_rng(members: Vec<Profile>, mut rng: R) -> Self {
+        let owner = members
+            .first()
+            .expect("a household has at least one member");
+        let owner = DeviceProfile::from_profile(owner);
+        let device = owner
+            .primary(FormFactor::Desktop)
+            .or(owner.devices.first())
+            .cloned()
+            .expect("every profile has a device");
+        let devices = shared(owner.clone(), device);
+
+        let schedules = members.iter().map(Schedule::from_profile).collect();
+        let members = members
+            .into_iter()
+            .map(|profile| {
+                let member_rng = R::from_rng(&mut rng).expect("RNGs seed from one another");
+                ActivitySimulator::from_rng(profile, member_rng).with_devices(devices.clone())
+            })
+            .collect();
+        Self {
+            members,
+            schedules,
+            rng,
+        }
+    }
+
+    /// Draw activities from a fixed seed instead of from entropy
+    pub fn with_seed(self, seed: u64) -> Self {
+        let device = self.device().clone();
+        let profiles = self
+            .members
+            .iter()
+            .map(|member| member.profile().clone())
+            .collect();
+        Self::from_rng(profiles, R::seed_from_u64(seed)).with_device(device)
+    }
+
+    /// Share this device instead of the first member's desktop
+    pub fn with_device(mut self, device: Device) -> Self {
+        let owner = self.members[0].devices().clone();
+        let devices = shared(owner, device);
+        self.members = self
+            .members
+            .into_iter()
+            .map(|member| member.with_devices(devices.clone()))
+            .collect();
+        self
+    }
+
+    pub fn members(&self) -> impl Iterator<Item = &Profile> {
+        self.members.iter().map(ActivitySimulator::profile)
+    }
+
+    /// The shared device every activity is stamped with
+    pub fn device(&self) -> &Device {
+        &self.members[0].devices().devices[0]
+    }
+
+    /// Chance that anyone is at the device at a Unix timestamp: that at
+    /// least one member, browsing at their schedule's intensity, wants it
+    pub fn in_use_at(&self, timestamp: i64) -> f64 {
+        let idle: f64 = self
+            .schedules
+            .iter()
+            .map(|schedule| 1.0 - (schedule.intensity_at(timestamp) as f64).clamp(0.0, 1.0))
+            .product();
+        1.0 - idle
+    }
+
+    /// Each member's chance of being the one at the device at a Unix
+    /// timestamp, in member order; all zero while nobody is browsing
+    pub fn shares_at(&self, timestamp: i64) -> Vec<f64> {
+        let weights: Vec<f64> = self
+            .schedules
+            .iter()
+            .map(|schedule| schedule.intensity_at(timestamp) as f64)
+            .collect();
+        let total: f64 = weights.iter().sum();
+        weights
+            .into_iter()
+            .map(|weight| if total > 0.0 { weight / total } else { 0.0 })
+            .collect()
+    }
+
+    /// Generate the device's activities for a given duration in hours,
+    /// members taking turns at it, starting now
+    pub fn generate_activities(&mut self, duration_hours: u32) -> Vec<BrowsingActivity> {
+        self.generate_activities_from(chrono::Utc::now().timestamp(), duration_hours)
+    }
+
+    /// Generate the device's activities for a given duration in hours from
+    /// a Unix timestamp
+    pub fn generate_activities_from(
+        &mut self,
+        start: i64,
+        duration_hours: u32,
+    ) -> Vec<BrowsingActivity> {
+        let end = start + duration_hours as i64 * 3600;
+        let mut activities = Vec::new();
+
+        let mut time = start;
+        while time < end {
+            let in_use = self.in_use_at(time);
+            if in_use <= 0.0 {
+                // Nobody is up; try again on the hour
+                time += 3600 - time.rem_euclid(3600);
+                continue;
+            }
+            if !self.rng.gen_bool(in_use.min(1.0)) {
+                time += self.rng.gen_range(IDLE_MINUTES.0..=IDLE_MINUTES.1) * 60;
+                continue;
+            }
+
+            let shares = self.shares_at(time);
+            let Ok(member) = (0..self.members.len())
+                .collect::<Vec<_>>()
+                .choose_weighted(&mut self.rng, |&i| shares[i])
+                .copied()
+            else {
+                time += self.rng.gen_range(IDLE_MINUTES.0..=IDLE_MINUTES.1) * 60;
+                continue;
+            };
+
+            let turn = self.rng.gen_range(TURN_MINUTES.0..=TURN_MINUTES.1) * 60;
+            let turn = turn.min(end - time);
+            let member = &mut self.members[member];
+            let count = (member.get_activities_per_hour() * turn as f64 / 3600.0)
+                .round()
+                .max(1.0);
+            let mut timestamps: Vec<i64> = (0..count as usize)
+                .map(|_| time + self.rng.gen_range(0..turn.max(1)))
+                .collect();
+            timestamps.sort();
+            activities.extend(member.generate_at(&timestamps));
+
+            time += turn + self.rng.gen_range(HANDOVER_MINUTES.0..=HANDOVER_MINUTES.1) * 60;
+        }
+
+        activities
+    }
+}
+
+/// The owner's device profile, narrowed to the one shared device
+fn shared(owner: DeviceProfile, device: Device) -> DeviceProfile {
+    DeviceProfile {
+        mobile_share: match device.form_factor {
+            FormFactor::Mobile => 1.0,
+            FormFactor::Desktop | FormFactor::Tablet => 0.0,
+        },
+        devices: vec![Device {
+            share: 1.0,
+            ..device
+        }],
+        ..owner
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::profile::{ActivityLevel, OccupationCategory, ProfileGenerator};
+    use crate::spec::ProfileSpec;
+
+    /// Midnight UTC on Wednesday 2026-10-07
+    const WEDNESDAY: i64 = 1_791_331_200;
+
+    fn household(seed: u64) -> SharedHousehold {
+        let mut generator = ProfileGenerator::new(Some(seed));
+        let members = vec![
+            generator
+                .generate_with(&ProfileSpec::new().occupation(OccupationCategory::Technology))
+                .unwrap(),
+            generator
+                .generate_with(&ProfileSpec::new().occupation(OccupationCategory::Student))
+                .unwrap(),
+            generator
+                .generate_with(&ProfileSpec::new().activity_level(ActivityLevel::Low))
+                .unwrap(),
+        ];
+        SharedHousehold::new(members).with_seed(seed)
+    }
+
+    #[test]
+    fn test_members_take_turns_on_one_device() {
+        let mut household = household(12);
+        let ids: Vec<String> = household
+            .members()
+            .map(|member| member.id.clone())
+            .collect();
+        let device = household.device().id.clone();
+        let activities = household.generate_activities_from(WEDNESDAY, 24 * 7);
+
+        assert!(activities
+            .iter()
+            .all(|a| a.device_id.as_ref() == Some(&device)));
+        for id in &ids {
+            assert!(
+                activities.iter().any(|a| a.profile_id.as_ref() == Some(id)),
+                "{}",
+                id
+            );
+        }
+        for pair in activities.windows(2) {
+            assert!(pair[0].timestamp <= pair[1].timestamp);
+        }
+        let handovers = activities
+            .windows(2)
+            .filter(|pair| pair[0].profile_id != pair[1].profile_id)
+            .count();
+        assert!(handovers > 10);
+    }
+
+    #[test]
+    fn test_shares_follow_the_schedules() {
+        let household = household(4);
+        for hour in 0..24 * 7 {
+            let shares = household.shares_at(WEDNESDAY + hour * 3600);
+            let total: f64 = shares.iter().sum();
+            assert!(total == 0.0 || (total - 1.0).abs() < 1e-9);
+        }
+        assert!((0..24).any(|hour| household
+            .shares_at(WEDNESDAY + hour * 3600)
+            .iter()
+            .sum::<f64>()
+            > 0.0));
+    }
+
+    #[test]
+    fn test_device_use_scales_with_intensity() {
+        let busy_share = |level: ActivityLevel| {
+            let spec = ProfileSpec::new()
+                .occupation(OccupationCategory::Retired)
+                .activity_level(level);
+            let mut generator = ProfileGenerator::new(Some(21));
+            let members = (0..2)
+                .map(|_| generator.generate_with(&spec).unwrap())
+                .collect();
+            let mut household = SharedHousehold::new(members).with_seed(21);
+            let activities = household.generate_activities_from(WEDNESDAY, 24 * 14);
+
+            // Hours someone could be browsing, and hours someone was
+            let awake: Vec<i64> = (0..24 * 14)
+                .map(|hour| WEDNESDAY / 3600 + hour)
+                .filter(|hour| household.in_use_at(hour * 3600 + 1800) > 0.0)
+                .collect();
+            let busy = awake
+                .iter()
+                .filter(|hour| activities.iter().any(|a| a.timestamp / 3600 == **hour))
+                .count();
+            busy as f64 / awake.len() as f64
+        };
+
+        // Two members browsing at intensity 0.3 leave the device idle about
+        // half the time they're up; at 1.0 it's always in use
+        let low = busy_share(ActivityLevel::Low);
+        let high = busy_share(ActivityLevel::VeryHigh);
+        assert!(high > 0.9, "{}", high);
+        assert!(low < high * 0.8, "{} vs {}", low, high);
+    }
+}
@@ -0,0 +1,425 @@
+use crate::profile::{
+    ActivityLevel, BrowsingStyle, Demographics, EducationLevel, Gender, InterestCategory,
+    LocationType, OccupationCategory, Profile,
+};
+use crate::spec::{self, ProfileSpec, MAX_INTERESTS};
+use rand::seq::SliceRandom;
+use rand::Rng;
+use serde::de::Error as _;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::ops::RangeInclusive;
+use std::sync::OnceLock;
+
+/// Built-in conditional probability tables
+const BUILTIN_MODEL: &str = include_str!("../data/interest_model.json");
+
+/// Fewest interests a generated profile has
+const MIN_INTERESTS: usize = 2;
+
+/// Draws of the interest nodes before settling for the likeliest interests
+const SAMPLE_ATTEMPTS: usize = 64;
+
+/// Additive contributions to a node's score from each of its parents
+///
+/// Interest nodes turn the score into a probability with the logistic
+/// function; browsing style and activity level take a softmax over their
+/// values' scores. Anything not listed contributes nothing.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+struct Factor {
+    base: f64,
+    /// One entry per age bracket, or none
+    age: Vec<f64>,
+    gender: HashMap<Gender, f64>,
+    location: HashMap<LocationType, f64>,
+    education: HashMap<EducationLevel, f64>,
+    occupation: HashMap<OccupationCategory, f64>,
+    /// Only for browsing style and activity level, which are children of
+    /// the interest nodes
+    interests: HashMap<InterestCategory, f64>,
+}
+
+impl Factor {
+    fn score(
+        &self,
+        bracket: usize,
+        demographics: &Demographics,
+        interests: &[&InterestCategory],
+    ) -> f64 {
+        fn get<T: Eq + Hash>(map: &HashMap<T, f64>, key: &T) -> f64 {
+            map.get(key).copied().unwrap_or(0.0)
+        }
+
+        self.base
+            + self.age.get(bracket).copied().unwrap_or(0.0)
+            + get(&self.gender, &demographics.gender)
+            + get(&self.location, &demographics.location_type)
+            + get(&self.education, &demographics.education_level)
+            + get(&self.occupation, &demographics.occupation_category)
+            + interests
+                .iter()
+                .map(|interest| get(&self.interests, interest))
+                .sum::<f64>()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct RawModel {
+    age_brackets: Vec<(u8, u8)>,
+    interests: HashMap<InterestCategory, Factor>,
+    browsing_style: HashMap<BrowsingStyle, Factor>,
+    activity_level: HashMap<ActivityLevel, Factor>,
+}
+
+/// A small Bayesian network from demographics to interests, browsing style
+/// and activity level
+///
+/// Every demographic field is a parent of every interest node, and the
+/// interest nodes are in turn parents of browsing style and activity level.
+/// The conditional probability tables in `data/interest_model.json` are
+/// kept small by giving each parent value an additive effect on a node's
+/// log-odds rather than listing every combination. Profiles need between
+/// two and ten interests, so draws outside that range are rejected.
+#[derive(Debug, Clone)]
+pub struct InterestModel {
+    brackets: Vec<RangeInclusive<u8>>,
+    /// In [`InterestCategory::ALL`] order, so sampling is reproducible
+    interests: Vec<(InterestCategory, Factor)>,
+    styles: Vec<(BrowsingStyle, Factor)>,
+    levels: Vec<(ActivityLevel, Factor)>,
+}
+
+impl InterestModel {
+    /// The model shipped in `data/interest_model.json`
+    pub fn builtin() -> &'static InterestModel {
+        static MODEL: OnceLock<InterestModel> = OnceLock::new();
+        MODEL.get_or_init(|| {
+            InterestModel::from_json(BUILTIN_MODEL).expect("built-in interest model is valid")
+        })
+    }
+
+    /// Load a model from JSON in the same shape as `data/interest_model.json`
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        let mut raw: RawModel = serde_json::from_str(json)?;
+
+        let mut next = *spec::VALID_AGES.start();
+        for &(min, max) in &raw.age_brackets {
+            if min != next || max < min {
+                return Err(serde_json::Error::custom(format!(
+                    "age bracket {}-{} should start at {}",
+                    min, max, next
+                )));
+            }
+            next = max.saturating_add(1);
+        }
+        if next <= *spec::VALID_AGES.end() {
+            return Err(serde_json::Error::custom("age brackets stop short of 100"));
+        }
+        let brackets = raw.age_brackets.len();
+        let factors = raw
+            .interests
+            .values()
+            .chain(raw.browsing_style.values())
+            .chain(raw.activity_level.values());
+        if factors
+            .into_iter()
+            .any(|f| !f.age.is_empty() && f.age.len() != brackets)
+        {
+            return Err(serde_json::Error::custom(
+                "age effects need one entry per bracket",
+            ));
+        }
+        if let Some(interest) = raw.interests.keys().find(|i| i.is_sensitive()) {
+            return Err(serde_json::Error::custom(format!(
+                "{:?} is only used for cover traffic",
+                interest
+            )));
+        }
+        if raw.interests.values().any(|f| !f.interests.is_empty()) {
+            return Err(serde_json::Error::custom(
+                "interest nodes can only depend on demographics",
+            ));
+        }
+
+        Ok(InterestModel {
+            brackets: raw
+                .age_brackets
+                .iter()
+                .map(|&(min, max)| min..=max)
+                .collect(),
+            interests: in_order(&mut raw.interests, &InterestCategory::ALL),
+            styles: complete(
+                &mut raw.browsing_style,
+                &BrowsingStyle::ALL,
+                "browsing_style",
+            )?,
+            levels: complete(
+                &mut raw.activity_level,
+                &ActivityLevel::ALL,
+                "activity_level",
+            )?,
+        })
+    }
+
+    fn bracket(&self, age: u8) -> usize {
+        self.brackets
+            .iter()
+            .position(|bracket| bracket.contains(&age))
+            .unwrap_or(self.brackets.len() - 1)
+    }
+
+    /// Chance of each modelled interest on its own, before the limits on
+    /// how many a profile has
+    pub fn interest_probabilities(
+        &self,
+        demographics: &Demographics,
+    ) -> Vec<(InterestCategory, f64)> {
+        let bracket = self.bracket(demographics.age);
+        self.interests
+            .iter()
+            .map(|(category, factor)| {
+                let p = logistic(factor.score(bracket, demographics, &[]));
+                (category.clone(), p)
+            })
+            .collect()
+    }
+
+    /// Log-probability of a profile's interests, browsing style and activity
+    /// level given its demographics
+    ///
+    /// Interest weights and subtopics aren't part of the network and don't
+    /// count. Anything the model can't produce, such as a sensitive
+    /// interest, gives negative infinity.
+    pub fn log_probability(&self, profile: &Profile) -> f64 {
+        let demographics = &profile.demographics;
+        let probabilities = self.interest_probabilities(demographics);
+        if profile
+            .categories()
+            .any(|c| !probabilities.iter().any(|(m, _)| m == c))
+        {
+            return f64::NEG_INFINITY;
+        }
+
+        let mut total: f64 = probabilities
+            .iter()
+            .map(|(category, p)| {
+                if profile.has_interest(category) {
+                    p.ln()
+                } else {
+                    (1.0 - p).ln()
+                }
+            })
+            .sum();
+        // Draws outside the allowed count are rejected, so condition on it
+        total -= count_in_range(&probabilities, MIN_INTERESTS, MAX_INTERESTS).ln();
+
+        let interests: Vec<&InterestCategory> = profile.categories().collect();
+        let bracket = self.bracket(demographics.age);
+        total += softmax(&self.styles, bracket, demographics, &interests)
+            .into_iter()
+            .find(|(style, _)| style == &profile.browsing_style)
+            .map_or(f64::NEG_INFINITY, |(_, p)| p.ln());
+        total += softmax(&self.levels, bracket, demographics, &interests)
+            .into_iter()
+            .find(|(level, _)| level == &profile.activity_level)
+            .map_or(f64::NEG_INFINITY, |(_, p)| p.ln());
+        total
+    }
+
+    /// Draw interests for `demographics`, honouring the spec's required and
+    /// forbidden interests
+    ///
+    /// Required interests come first, then the rest from most to least
+    /// likely.
+    pub(crate) fn sample_interests<R: Rng>(
+        &self,
+        demographics: &Demographics,
+        spec: &ProfileSpec,
+        rng: &mut R,
+    ) -> Vec<InterestCategory> {
+        let mut probabilities: Vec<(InterestCategory, f64)> = self
+            .interest_probabilities(demographics)
+            .into_iter()
+            .filter(|(category, _)| {
+                spec.allows_interest(category) && !spec.required_interests.contains(category)
+            })
+            .collect();
+        probabilities.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+        let required = spec.required_interests.len();
+        let min = MIN_INTERESTS.saturating_sub(required);
+        let max = MAX_INTERESTS.saturating_sub(required);
+        let drawn = (0..SAMPLE_ATTEMPTS)
+            .map(|_| {
+                probabilities
+                    .iter()
+                    .filter(|(_, p)| rng.gen::<f64>() < *p)
+                    .map(|(category, _)| category.clone())
+                    .collect::<Vec<_>>()
+            })
+            .find(|drawn| (min..=max).contains(&drawn.len()))
+            .unwrap_or_else(|| {
+                probabilities
+                    .iter()
+                    .take(min)
+                    .map(|(category, _)| category.clone())
+                    .collect()
+            });
+
+        spec.required_interests
+            .iter()
+            .cloned()
+            .chain(drawn)
+            .collect()
+    }
+
+    pub(crate) fn sample_browsing_style<R: Rng>(
+        &self,
+        demographics: &Demographics,
+        interests: &[InterestCategory],
+        rng: &mut R,
+    ) -> BrowsingStyle {
+        let interests: Vec<&InterestCategory> = interests.iter().collect();
+        let bracket = self.bracket(demographics.age);
+        choose(
+            softmax(&self.styles, bracket, demographics, &interests),
+            rng,
+        )
+    }
+
+    pub(crate) fn sample_activity_level<R: Rng>(
+        &self,
+        demographics: &Demographics,
+        interests: &[InterestCategory],
+        rng: &mut R,
+    ) -> ActivityLevel {
+        let interests: Vec<&InterestCategory> = interests.iter().collect();
+        let bracket = self.bracket(demographics.age);
+        choose(
+            softmax(&self.levels, bracket, demographics, &interests),
+            rng,
+        )
+    }
+}
+
+/// Factors in `all` order, skipping values the data leaves out
+fn in_order<T: Clone + Eq + Hash>(map: &mut HashMap<T, Factor>, all: &[T]) -> Vec<(T, Factor)> {
+    all.iter()
+        .filter_map(|value| Some((value.clone(), map.remove(value)?)))
+        .collect()
+}
+
+/// Factors for every value in `all`
+fn complete<T: Clone + Eq + Hash + std::fmt::Debug>(
+    map: &mut HashMap<T, Factor>,
+    all: &[T],
+    node: &str,
+) -> Result<Vec<(T, Factor)>, serde_json::Error> {
+    match all.iter().find(|value| !map.contains_key(value)) {
+        Some(missing) => Err(serde_json::Error::custom(format!(
+            "{} has no entry for {:?}",
+            node, missing
+        ))),
+        None => Ok(in_order(map, all)),
+    }
+}
+
+fn logistic(x: f64) -> f64 {
+    1.0 / (1.0 + (-x).exp())
+}
+
+fn softmax<T: Clone>(
+    factors: &[(T, Factor)],
+    bracket: usize,
+    demographics: &Demographics,
+    interests: &[&InterestCategory],
+) -> Vec<(T, f64)> {
+    let scores: Vec<f64> = factors
+        .iter()
+        .map(|(_, factor)| factor.score(bracket, demographics, interests))
+        .collect();
+    let max = scores.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let exps: Vec<f64> = scores.iter().map(|s| (s - max).exp()).collect();
+    let total: f64 = exps.iter().sum();
+    factors
+        .iter()
+        .zip(exps)
+        .map(|((value, _), e)| (value.clone(), e / total))
+        .collect()
+}
+
+fn choose<T: Clone, R: Rng>(distribution: Vec<(T, f64)>, rng: &mut R) -> T {
+    distribution
+        .choose_weighted(rng, |(_, p)| *p)
+        .expect("softmax weights are positive")
+        .0
+        .clone()
+}
+
+/// Chance that between `min` and `max` of independent events happen
+fn count_in_range<T>(probabilities: &[(T, f64)], min: usize, max: usize) -> f64 {
+    // counts[k] is the chance exactly k of the events so far happened
+    let mut counts = vec![1.0];
+    for (_, p) in probabilities {
+        let mut next = vec![0.0; counts.len() + 1];
+        for (k, c) in counts.iter().enumerate() {
+            next[k] += c * (1.0 - p);
+            next[k + 1] += c * p;
+        }
+        counts = next;
+    }
+    counts
+        .iter()
+        .enumerate()
+        .filter(|(k, _)| (min..=max).contains(k))
+        .map(|(_, c)| c)
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn demographics(age: u8, occupation: OccupationCategory) -> Demographics {
+        Demographics {
+            age,
+            gender: Gender::Female,
+            location_type: LocationType::Suburban,
+            occupation_category: occupation,
+            education_level: EducationLevel::SomeCollege,
+            locale: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_interests_depend_on_every_field() {
+        let model = InterestModel::builtin();
+        let p = |d: &Demographics, category: InterestCategory| {
+            model
+                .interest_probabilities(d)
+                .into_iter()
+                .find(|(c, _)| c == &category)
+                .unwrap()
+                .1
+        };
+
+        let young = demographics(19, OccupationCategory::Service);
+        let old = demographics(60, OccupationCategory::Service);
+        assert!(p(&young, InterestCategory::Gaming) > 2.0 * p(&old, InterestCategory::Gaming));
+        assert!(p(&old, InterestCategory::News) > 2.0 * p(&young, InterestCategory::News));
+
+        let mut rural = old.clone();
+        rural.location_type = LocationType::Rural;
+        assert!(p(&rural, InterestCategory::Gardening) > p(&old, InterestCategory::Gardening));
+    }
+
+    #[test]
+    fn test_count_in_range() {
+        let events = [((), 0.5), ((), 0.5)];
+        assert!((count_in_range(&events, 0, 2) - 1.0).abs() < 1e-12);
+        assert!((count_in_range(&events, 1, 1) - 0.5).abs() < 1e-12);
+        assert!((count_in_range(&events, 2, 5) - 0.25).abs() < 1e-12);
+    }
+}
@@ -1,197 +1,509 @@
 use crate::activity::ActivityType;
+use crate::feeds::{self, FeedEntry, FeedError, FeedFormat, FeedSource};
+use crate::grammar::{Lexicon, QueryFeatures, QueryGrammar};
+use crate::locale::{CountryData, LanguageData, Locale, Titles};
 use crate::profile::InterestCategory;
+use crate::safety::{SafetyPolicy, Verdict};
+use crate::taxonomy::Subtopic;
+use crate::url_template::{self, TemplateContext};
+use chrono::{Datelike, NaiveDate};
 use rand::Rng;
 use rand::seq::SliceRandom;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+/// Share of news and page visits served from ingested content when there is some
+const INGESTED_SHARE: f64 = 0.6;
+
+/// How many candidates to generate before giving up on a policy-compliant URL
+const POLICY_ATTEMPTS: usize = 32;
+
+/// How many grammar samples to draw when looking for a query of a given shape
+const MATCH_ATTEMPTS: usize = 200;
+
+/// A URL that passed the safety policy
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GeneratedUrl {
+    pub url: String,
+    pub title: String,
+    /// Whether the policy allows actually loading the URL, not just recording it
+    pub safe_to_load: bool,
+}
+
+/// Outcome of ingesting one feed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IngestReport {
+    pub format: FeedFormat,
+    pub added: usize,
+    pub skipped: usize,
+}
+
+/// A decoy search query meant to be sent alongside a real one
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CoverQuery {
+    pub category: InterestCategory,
+    pub query: String,
+}
 
 /// Generates realistic URLs and titles based on interests and activity types
 pub struct InterestUrlGenerator {
     domains: DomainDatabase,
+    grammar: QueryGrammar,
+    /// Title formats outside English, whose titles are built in
+    language: Option<&'static LanguageData>,
+    policy: SafetyPolicy,
+}
+
+impl Default for InterestUrlGenerator {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl InterestUrlGenerator {
+    /// Generator for US English
     pub fn new() -> Self {
+        Self::for_locale(&Locale::default())
+    }
+
+    /// Generator visiting the locale's regional sites, with queries and
+    /// titles in its language
+    pub fn for_locale(locale: &Locale) -> Self {
         Self {
-            domains: DomainDatabase::new(),
+            domains: DomainDatabase::for_country(locale.country_data()),
+            grammar: QueryGrammar::for_locale(chrono::Utc::now().year(), locale),
+            language: locale.language_data(),
+            policy: SafetyPolicy::default(),
         }
     }
 
+    /// Add the URLs and titles from a local sitemap, RSS or Atom document
+    ///
+    /// RSS and Atom entries become news articles; sitemap entries become
+    /// pages. Both are tagged with the source's category, and their titles
+    /// also join that category's headline and page title tables.
+    pub fn ingest(&mut self, source: &FeedSource) -> Result<IngestReport, FeedError> {
+        let parsed = feeds::parse_feed(&source.xml)?;
+        let added = match parsed.format {
+            FeedFormat::Rss | FeedFormat::Atom => {
+                self.domains.add_articles(&source.category, parsed.entries)
+            }
+            FeedFormat::Sitemap => self.domains.add_pages(&source.category, parsed.entries),
+        };
+
+        Ok(IngestReport {
+            format: parsed.format,
+            added,
+            skipped: parsed.skipped,
+        })
+    }
+
+    /// Apply a safety policy to every URL generated from now on
+    pub fn with_policy(mut self, policy: SafetyPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    pub fn policy(&self) -> &SafetyPolicy {
+        &self.policy
+    }
+
+    /// Generate a URL and title that the safety policy lets through
+    ///
+    /// A `subtopic` narrows queries, titles and page domains to something
+    /// more specific than the category. Denied candidates are regenerated
+    /// rather than dropped. `None` means the policy denied every attempt
+    /// for this activity type, which only happens when it blocks most of
+    /// the built-in sites.
     pub fn generate_url<R: Rng>(
         &self,
         activity_type: &ActivityType,
         interest: &Option<InterestCategory>,
+        subtopic: Option<&Subtopic>,
+        today: NaiveDate,
+        rng: &mut R,
+    ) -> Option<GeneratedUrl> {
+        (0..POLICY_ATTEMPTS).find_map(|_| {
+            let (url, title) =
+                self.generate_unchecked(activity_type, interest, subtopic, today, rng);
+            match self.policy.check_str(&url) {
+                Verdict::Deny => None,
+                verdict => Some(GeneratedUrl {
+                    url,
+                    title,
+                    safe_to_load: verdict == Verdict::Allow,
+                }),
+            }
+        })
+    }
+
+    fn generate_unchecked<R: Rng>(
+        &self,
+        activity_type: &ActivityType,
+        interest: &Option<InterestCategory>,
+        subtopic: Option<&Subtopic>,
+        today: NaiveDate,
         rng: &mut R,
     ) -> (String, String) {
         match activity_type {
-            ActivityType::Search => self.generate_search_url(interest, rng),
-            ActivityType::VideoWatch => self.generate_video_url(interest, rng),
-            ActivityType::Shopping => self.generate_shopping_url(interest, rng),
+            ActivityType::Search => self.generate_search_url(interest, subtopic, today, rng),
+            ActivityType::VideoWatch => self.generate_video_url(interest, subtopic, today, rng),
+            ActivityType::Shopping => self.generate_shopping_url(interest, today, rng),
             ActivityType::SocialMedia => self.generate_social_url(rng),
-            ActivityType::News => self.generate_news_url(interest, rng),
-            ActivityType::Research => self.generate_research_url(interest, rng),
-            ActivityType::PageVisit => self.generate_page_url(interest, rng),
+            ActivityType::News => self.generate_news_url(interest, subtopic, today, rng),
+            ActivityType::Research => self.generate_research_url(interest, subtopic, today, rng),
+            ActivityType::PageVisit => self.generate_page_url(interest, subtopic, today, rng),
         }
     }
 
     fn generate_search_url<R: Rng>(
         &self,
         interest: &Option<InterestCategory>,
+        subtopic: Option<&Subtopic>,
+        today: NaiveDate,
         rng: &mut R,
     ) -> (String, String) {
-        let query = self.get_search_query(interest, rng);
-        let encoded_query = query.replace(" ", "+");
-
-        let search_engine = ["google.com", "bing.com", "duckduckgo.com"]
-            .choose(rng)
-            .unwrap();
-
-        let url = format!("https://{}/search?q={}", search_engine, encoded_query);
-        let title = format!("{} - Search", query);
+        let query = self.get_search_query(interest, subtopic, rng);
+        let search_engine = self.domains.get_search_domain(rng);
 
-        (url, title)
+        self.render(search_engine, &query, interest, today, rng)
     }
 
     fn generate_video_url<R: Rng>(
         &self,
         interest: &Option<InterestCategory>,
+        subtopic: Option<&Subtopic>,
+        today: NaiveDate,
         rng: &mut R,
     ) -> (String, String) {
-        let title = self.get_video_title(interest, rng);
-        let video_id = self.generate_video_id(rng);
-
-        let platform = ["youtube.com", "vimeo.com"].choose(rng).unwrap();
-        let url = if *platform == "youtube.com" {
-            format!("https://www.youtube.com/watch?v={}", video_id)
-        } else {
-            format!("https://vimeo.com/{}", rng.gen_range(100000000..999999999))
+        let title = match self.localized_title(|t| &t.video, interest, subtopic, rng) {
+            Some(title) => title,
+            None => match subtopic {
+                Some(subtopic) => subtopic_title(VIDEO_TITLES, subtopic, rng),
+                None => self.get_video_title(interest, rng),
+            },
         };
+        let platform = self.domains.get_video_domain(rng);
 
-        (url, title)
+        self.render(platform, &title, interest, today, rng)
     }
 
     fn generate_shopping_url<R: Rng>(
         &self,
         interest: &Option<InterestCategory>,
+        today: NaiveDate,
         rng: &mut R,
     ) -> (String, String) {
         let product = self.get_product_name(interest, rng);
         let domain = self.domains.get_shopping_domain(rng);
 
-        let url = format!(
-            "https://{}/products/{}",
-            domain,
-            product.to_lowercase().replace(" ", "-")
-        );
-        let title = format!("{} - {}", product, domain.split('.').next().unwrap());
-
-        (url, title)
+        self.render(domain, &product, interest, today, rng)
     }
 
     fn generate_social_url<R: Rng>(&self, rng: &mut R) -> (String, String) {
-        let platforms = vec![
+        let platforms = [
             ("twitter.com", "Twitter"),
-            ("reddit.com", "Reddit"),
-            ("facebook.com", "Facebook"),
-            ("instagram.com", "Instagram"),
-            ("linkedin.com", "LinkedIn"),
+            ("www.reddit.com", "Reddit"),
+            ("www.facebook.com", "Facebook"),
+            ("www.instagram.com", "Instagram"),
+            ("www.linkedin.com", "LinkedIn"),
         ];
 
         let (domain, name) = platforms.choose(rng).unwrap();
-        let url = format!("https://{}", domain);
+        let url = url_template::validate(&format!("https://{}/", domain))
+            .expect("social platform hosts are valid");
         let title = format!("Home - {}", name);
 
-        (url, title)
+        (url.into(), title)
     }
 
     fn generate_news_url<R: Rng>(
         &self,
         interest: &Option<InterestCategory>,
+        subtopic: Option<&Subtopic>,
+        today: NaiveDate,
         rng: &mut R,
     ) -> (String, String) {
-        let domain = self.domains.get_news_domain(rng);
-        let headline = self.get_news_headline(interest, rng);
-
-        let slug = headline
-            .to_lowercase()
-            .chars()
-            .map(|c| if c.is_alphanumeric() || c == ' ' { c } else { ' ' })
-            .collect::<String>()
-            .split_whitespace()
-            .take(8)
-            .collect::<Vec<_>>()
-            .join("-");
+        if let Some(article) = self.sample_ingested(self.domains.articles(interest), rng) {
+            return self.ingested(article);
+        }
 
-        let url = format!("https://{}/article/{}", domain, slug);
-        let title = format!("{} - {}", headline, domain.split('.').next().unwrap());
+        let domain = self.domains.get_news_domain(rng);
+        let headline = match self.localized_title(|t| &t.news, interest, subtopic, rng) {
+            Some(headline) => headline,
+            None => match subtopic {
+                Some(subtopic) if rng.gen_bool(0.5) => {
+                    subtopic_title(NEWS_HEADLINES, subtopic, rng)
+                }
+                _ => self.get_news_headline(interest, rng),
+            },
+        };
 
-        (url, title)
+        self.render(domain, &headline, interest, today, rng)
     }
 
     fn generate_research_url<R: Rng>(
         &self,
         interest: &Option<InterestCategory>,
+        subtopic: Option<&Subtopic>,
+        today: NaiveDate,
         rng: &mut R,
     ) -> (String, String) {
         let domain = self.domains.get_research_domain(rng);
-        let topic = self.get_research_topic(interest, rng);
+        let topic = match subtopic {
+            _ if self.language.is_some() => {
+                self.get_localized_research_topic(interest, subtopic, rng)
+            }
+            Some(subtopic) => subtopic
+                .lexicon
+                .entities
+                .choose(rng)
+                .cloned()
+                .unwrap_or_else(|| subtopic.name.clone()),
+            None => self.get_research_topic(interest, rng),
+        };
 
-        let url = if domain.contains("wikipedia") {
-            format!("https://{}/wiki/{}", domain, topic.replace(" ", "_"))
-        } else {
-            format!("https://{}/article/{}", domain, topic.to_lowercase().replace(" ", "-"))
+        self.render(domain, &topic, interest, today, rng)
+    }
+
+    fn generate_page_url<R: Rng>(
+        &self,
+        interest: &Option<InterestCategory>,
+        subtopic: Option<&Subtopic>,
+        today: NaiveDate,
+        rng: &mut R,
+    ) -> (String, String) {
+        if let Some(page) = self.sample_ingested(self.domains.pages(interest), rng) {
+            return self.ingested(page);
+        }
+
+        // Regional sites for the interest beat the subtopic's, which are
+        // mostly American
+        let domain = match subtopic {
+            Some(subtopic) if !self.domains.has_local_sites(interest) => {
+                subtopic.domains.choose(rng).map_or_else(
+                    || self.domains.get_interest_domain(interest, rng),
+                    String::as_str,
+                )
+            }
+            _ => self.domains.get_interest_domain(interest, rng),
+        };
+        let page = match self.localized_title(|t| &t.page, interest, subtopic, rng) {
+            Some(page) => page,
+            None => match subtopic {
+                Some(subtopic) => subtopic_title(PAGE_TITLES, subtopic, rng),
+                None => self.get_page_title(interest, rng),
+            },
         };
 
-        let title = format!("{} - {}", topic, domain.split('.').next().unwrap());
+        self.render(domain, &page, interest, today, rng)
+    }
 
-        (url, title)
+    fn sample_ingested<'a, R: Rng>(
+        &self,
+        entries: &'a [FeedEntry],
+        rng: &mut R,
+    ) -> Option<&'a FeedEntry> {
+        if entries.is_empty() || !rng.gen_bool(INGESTED_SHARE) {
+            return None;
+        }
+        entries.choose(rng)
     }
 
-    fn generate_page_url<R: Rng>(
+    /// Emit an ingested entry verbatim, titled the way its site would title it
+    fn ingested(&self, entry: &FeedEntry) -> (String, String) {
+        let url =
+            url_template::validate(&entry.url).expect("ingested URLs are validated on the way in");
+        let host = url.host_str().unwrap_or_default();
+        let title = url_template::site_template(host).render_title(host, &entry.title);
+
+        (entry.url.clone(), title)
+    }
+
+    /// Render `text` through the host's URL template, dated shortly before
+    /// `today`
+    fn render<R: Rng>(
         &self,
+        host: &str,
+        text: &str,
         interest: &Option<InterestCategory>,
+        today: NaiveDate,
         rng: &mut R,
     ) -> (String, String) {
-        let domain = self.domains.get_interest_domain(interest, rng);
-        let page = self.get_page_title(interest, rng);
+        let template = url_template::site_template(host);
+        let ctx = TemplateContext::recent(text, section_for(interest), today, rng);
+        let url = template
+            .render(host, &ctx, rng)
+            .expect("built-in hosts and templates produce valid URLs");
 
-        let url = format!(
-            "https://{}/{}",
-            domain,
-            page.to_lowercase().replace(" ", "-")
-        );
-        let title = format!("{} | {}", page, domain.split('.').next().unwrap());
+        (url.into(), template.render_title(host, text))
+    }
 
-        (url, title)
+    fn get_search_query<R: Rng>(
+        &self,
+        interest: &Option<InterestCategory>,
+        subtopic: Option<&Subtopic>,
+        rng: &mut R,
+    ) -> String {
+        match subtopic {
+            Some(subtopic) => self
+                .grammar
+                .generate_with(&self.subtopic_lexicon(subtopic), rng),
+            None => self.grammar.generate(interest.as_ref(), rng),
+        }
     }
 
-    fn get_search_query<R: Rng>(&self, interest: &Option<InterestCategory>, rng: &mut R) -> String {
-        if let Some(cat) = interest {
-            let queries = match cat {
-                InterestCategory::Technology => vec!["latest smartphones", "cloud computing trends", "AI news", "tech reviews"],
-                InterestCategory::Gaming => vec!["best games 2024", "gaming benchmarks", "esports tournament", "game reviews"],
-                InterestCategory::Sports => vec!["football scores", "NBA highlights", "soccer news", "sports statistics"],
-                InterestCategory::Cooking => vec!["easy recipes", "meal prep ideas", "cooking techniques", "healthy meals"],
-                InterestCategory::Travel => vec!["travel destinations", "cheap flights", "hotel reviews", "travel tips"],
-                InterestCategory::Finance => vec!["stock market news", "investment strategies", "crypto prices", "financial planning"],
-                InterestCategory::Programming => vec!["rust tutorial", "typescript best practices", "algorithm examples", "code review"],
-                _ => vec!["latest news", "trending topics", "popular articles", "how to"],
-            };
-            queries.choose(rng).unwrap().to_string()
+    /// A subtopic's vocabulary in the grammar's language
+    ///
+    /// Subtopic vocabularies are English; in other languages the subtopic
+    /// adds its named things to its category's and borrows the rest.
+    fn subtopic_lexicon<'a>(&self, subtopic: &'a Subtopic) -> Cow<'a, Lexicon> {
+        if self.language.is_none() {
+            return Cow::Borrowed(&subtopic.lexicon);
+        }
+        let category = self.grammar.lexicon(Some(&subtopic.category));
+        let mut lexicon = category.clone();
+        lexicon
+            .entities
+            .extend(subtopic.lexicon.entities.iter().cloned());
+        lexicon.local = subtopic.lexicon.local;
+        Cow::Owned(lexicon)
+    }
+
+    /// A title in the generator's language from one of the formats `pick`
+    /// chooses, or `None` in English or without an interest
+    fn localized_title<R: Rng>(
+        &self,
+        pick: impl Fn(&'static Titles) -> &'static [String],
+        interest: &Option<InterestCategory>,
+        subtopic: Option<&Subtopic>,
+        rng: &mut R,
+    ) -> Option<String> {
+        let language = self.language?;
+        let category = subtopic.map(|s| &s.category).or(interest.as_ref())?;
+        let lexicon = match subtopic {
+            Some(subtopic) => self.subtopic_lexicon(subtopic),
+            None => Cow::Borrowed(self.grammar.lexicon(Some(category))),
+        };
+        Some(localized_title(
+            pick(&language.titles),
+            language,
+            category,
+            &lexicon,
+            rng,
+        ))
+    }
+
+    /// Generate a query for an interest shaped like `target`
+    ///
+    /// Samples the grammar and keeps the closest match, stopping early on an
+    /// exact one, then copies the target's capitalisation.
+    pub fn get_matching_search_query<R: Rng>(
+        &self,
+        interest: &Option<InterestCategory>,
+        target: &QueryFeatures,
+        rng: &mut R,
+    ) -> String {
+        let mut best = (usize::MAX, String::new());
+        for _ in 0..MATCH_ATTEMPTS {
+            let candidate = self.grammar.generate(interest.as_ref(), rng);
+            let mismatch = self.grammar.features(&candidate).mismatch(target);
+            if mismatch < best.0 {
+                best = (mismatch, candidate);
+                if mismatch == 0 {
+                    break;
+                }
+            }
+        }
+
+        if target.lowercase {
+            best.1.to_lowercase()
         } else {
-            vec!["news", "weather", "recipes", "reviews"]
-                .choose(rng)
-                .unwrap()
-                .to_string()
+            best.1
         }
     }
 
+    /// Generate `count` cover queries in other categories matching the shape of `real_query`
+    ///
+    /// When `real_category` is unknown it is inferred from the query's
+    /// vocabulary. Categories are spread evenly before any repeats.
+    pub fn cover_queries<R: Rng>(
+        &self,
+        real_query: &str,
+        real_category: Option<&InterestCategory>,
+        count: usize,
+        rng: &mut R,
+    ) -> Vec<CoverQuery> {
+        let target = self.grammar.features(real_query);
+        let real_category = real_category
+            .cloned()
+            .or_else(|| self.grammar.classify(real_query));
+
+        // Sensitive topics only serve as cover for a query that is itself
+        // sensitive; next to an everyday one they would stand out
+        let sensitive = real_category
+            .as_ref()
+            .is_some_and(InterestCategory::is_sensitive);
+        let mut categories: Vec<InterestCategory> = InterestCategory::ALL
+            .iter()
+            .filter(|category| Some(*category) != real_category.as_ref())
+            .filter(|category| !category.is_sensitive() || sensitive)
+            .cloned()
+            .collect();
+        categories.shuffle(rng);
+
+        categories
+            .iter()
+            .cycle()
+            .take(count)
+            .map(|category| CoverQuery {
+                category: category.clone(),
+                query: self.get_matching_search_query(&Some(category.clone()), &target, rng),
+            })
+            .collect()
+    }
+
     fn get_video_title<R: Rng>(&self, interest: &Option<InterestCategory>, rng: &mut R) -> String {
         if let Some(cat) = interest {
             let titles = match cat {
-                InterestCategory::Technology => vec!["Tech Review: Latest Gadgets", "Programming Tutorial", "Tech News Weekly"],
-                InterestCategory::Gaming => vec!["Gameplay Walkthrough", "Gaming News", "Top 10 Games"],
-                InterestCategory::Cooking => vec!["Quick Recipe Tutorial", "Cooking Tips", "Chef's Special"],
-                InterestCategory::Music => vec!["Official Music Video", "Live Performance", "Music Review"],
-                InterestCategory::Fitness => vec!["Workout Routine", "Fitness Tips", "Exercise Guide"],
+                InterestCategory::Technology => vec![
+                    "Tech Review: Latest Gadgets",
+                    "Programming Tutorial",
+                    "Tech News Weekly",
+                ],
+                InterestCategory::Gaming => {
+                    vec!["Gameplay Walkthrough", "Gaming News", "Top 10 Games"]
+                }
+                InterestCategory::Cooking => {
+                    vec!["Quick Recipe Tutorial", "Cooking Tips", "Chef's Special"]
+                }
+                InterestCategory::Music => {
+                    vec!["Official Music Video", "Live Performance", "Music Review"]
+                }
+                InterestCategory::Fitness => {
+                    vec!["Workout Routine", "Fitness Tips", "Exercise Guide"]
+                }
+                InterestCategory::HealthConditions => vec![
+                    "Doctor Explains: Common Symptoms",
+                    "What Your Blood Test Means",
+                    "Managing a Chronic Condition",
+                ],
+                InterestCategory::MentalHealth => vec![
+                    "Guided Meditation for Anxiety",
+                    "Therapist Answers Questions",
+                    "Coping Strategies That Work",
+                ],
+                InterestCategory::LegalAdvice => vec![
+                    "Lawyer Explains Your Rights",
+                    "Small Claims Court Walkthrough",
+                    "Estate Planning Basics",
+                ],
+                InterestCategory::Immigration => vec![
+                    "Visa Interview Tips",
+                    "Citizenship Test Practice",
+                    "Green Card Process Explained",
+                ],
                 _ => vec!["Popular Video", "Trending Content", "Featured Video"],
             };
             titles.choose(rng).unwrap().to_string()
@@ -201,6 +513,10 @@ impl InterestUrlGenerator {
     }
 
     fn get_product_name<R: Rng>(&self, interest: &Option<InterestCategory>, rng: &mut R) -> String {
+        if let Some(product) = self.localized_title(|t| &t.product, interest, None, rng) {
+            return product;
+        }
+
         if let Some(cat) = interest {
             let products = match cat {
                 InterestCategory::Technology => vec!["Wireless Headphones", "Smart Watch", "Laptop Stand", "USB Cable"],
@@ -217,12 +533,52 @@ impl InterestUrlGenerator {
     }
 
     fn get_news_headline<R: Rng>(&self, interest: &Option<InterestCategory>, rng: &mut R) -> String {
+        if let Some(article) = self.sample_ingested(self.domains.articles(interest), rng) {
+            return article.title.clone();
+        }
+
         if let Some(cat) = interest {
             let headlines = match cat {
-                InterestCategory::Technology => vec!["Major Tech Company Announces New Product", "Breakthrough in AI Research", "Cybersecurity Alert"],
-                InterestCategory::Politics => vec!["Election Results Coming In", "Policy Change Announced", "Political Summit Concludes"],
-                InterestCategory::Sports => vec!["Championship Game Recap", "Player Breaks Record", "Team Makes Playoffs"],
-                InterestCategory::Science => vec!["New Scientific Discovery", "Research Findings Published", "Space Mission Update"],
+                InterestCategory::Technology => vec![
+                    "Major Tech Company Announces New Product",
+                    "Breakthrough in AI Research",
+                    "Cybersecurity Alert",
+                ],
+                InterestCategory::Politics => vec![
+                    "Election Results Coming In",
+                    "Policy Change Announced",
+                    "Political Summit Concludes",
+                ],
+                InterestCategory::Sports => vec![
+                    "Championship Game Recap",
+                    "Player Breaks Record",
+                    "Team Makes Playoffs",
+                ],
+                InterestCategory::Science => vec![
+                    "New Scientific Discovery",
+                    "Research Findings Published",
+                    "Space Mission Update",
+                ],
+                InterestCategory::HealthConditions => vec![
+                    "New Guidelines for Blood Pressure Screening",
+                    "Study Links Sleep to Heart Health",
+                    "Drug Shortage Update",
+                ],
+                InterestCategory::MentalHealth => vec![
+                    "Demand for Therapy Keeps Rising",
+                    "New Crisis Line Expands Services",
+                    "Study on Burnout at Work",
+                ],
+                InterestCategory::LegalAdvice => vec![
+                    "Court Rules on Tenant Protections",
+                    "New Consumer Protection Law Takes Effect",
+                    "Supreme Court Hears Key Case",
+                ],
+                InterestCategory::Immigration => vec![
+                    "Visa Processing Times Updated",
+                    "New Immigration Rules Announced",
+                    "Citizenship Ceremonies Resume",
+                ],
                 _ => vec!["Breaking News", "Latest Updates", "Today's Top Stories"],
             };
             headlines.choose(rng).unwrap().to_string()
@@ -231,14 +587,61 @@ impl InterestUrlGenerator {
         }
     }
 
-    fn get_research_topic<R: Rng>(&self, interest: &Option<InterestCategory>, rng: &mut R) -> String {
+    fn get_research_topic<R: Rng>(
+        &self,
+        interest: &Option<InterestCategory>,
+        rng: &mut R,
+    ) -> String {
         if let Some(cat) = interest {
             let topics = match cat {
-                InterestCategory::Science => vec!["Quantum Physics", "Climate Change", "Genetics", "Astronomy"],
-                InterestCategory::Technology => vec!["Machine Learning", "Blockchain", "Quantum Computing", "Cybersecurity"],
-                InterestCategory::Programming => vec!["Design Patterns", "Data Structures", "Algorithms", "Software Architecture"],
-                InterestCategory::DataScience => vec!["Statistical Analysis", "Data Visualization", "Predictive Modeling", "Big Data"],
-                _ => vec!["General Knowledge", "Encyclopedia", "Reference Material", "Study Guide"],
+                InterestCategory::Science => {
+                    vec!["Quantum Physics", "Climate Change", "Genetics", "Astronomy"]
+                }
+                InterestCategory::Technology => vec![
+                    "Machine Learning",
+                    "Blockchain",
+                    "Quantum Computing",
+                    "Cybersecurity",
+                ],
+                InterestCategory::Programming => vec![
+                    "Design Patterns",
+                    "Data Structures",
+                    "Algorithms",
+                    "Software Architecture",
+                ],
+                InterestCategory::DataScience => vec![
+                    "Statistical Analysis",
+                    "Data Visualization",
+                    "Predictive Modeling",
+                    "Big Data",
+                ],
+                InterestCategory::HealthConditions => vec![
+                    "Hypertension",
+                    "Type 2 Diabetes",
+                    "Migraine",
+                    "Hypothyroidism",
+                ],
+                InterestCategory::MentalHealth => vec![
+                    "Generalized Anxiety Disorder",
+                    "Cognitive Behavioral Therapy",
+                    "Major Depressive Disorder",
+                    "Insomnia",
+                ],
+                InterestCategory::LegalAdvice => vec![
+                    "Landlord-Tenant Law",
+                    "Power of Attorney",
+                    "Small Claims Court",
+                    "Probate",
+                ],
+                InterestCategory::Immigration => {
+                    vec!["Green Card", "Naturalization", "Asylum", "H-1B Visa"]
+                }
+                _ => vec![
+                    "General Knowledge",
+                    "Encyclopedia",
+                    "Reference Material",
+                    "Study Guide",
+                ],
             };
             topics.choose(rng).unwrap().to_string()
         } else {
@@ -246,35 +649,118 @@ impl InterestUrlGenerator {
         }
     }
 
+    /// Something to look up in a reference work: a named thing or a topic
+    fn get_localized_research_topic<R: Rng>(
+        &self,
+        interest: &Option<InterestCategory>,
+        subtopic: Option<&Subtopic>,
+        rng: &mut R,
+    ) -> String {
+        let lexicon = match subtopic {
+            Some(subtopic) => self.subtopic_lexicon(subtopic),
+            None => Cow::Borrowed(self.grammar.lexicon(interest.as_ref())),
+        };
+        let words = if rng.gen_bool(0.5) && !lexicon.entities.is_empty() {
+            &lexicon.entities
+        } else {
+            &lexicon.nouns
+        };
+        words
+            .choose(rng)
+            .map(|w| capitalize_first(w))
+            .unwrap_or_default()
+    }
+
     fn get_page_title<R: Rng>(&self, interest: &Option<InterestCategory>, rng: &mut R) -> String {
+        if let Some(page) = self.sample_ingested(self.domains.pages(interest), rng) {
+            return page.title.clone();
+        }
+
         if let Some(cat) = interest {
-            format!("{:?} Information", cat)
+            let formats = [
+                "{} Information",
+                "Beginner's Guide to {}",
+                "{} Tips and Tricks",
+                "Latest in {}",
+            ];
+            formats
+                .choose(rng)
+                .unwrap()
+                .replace("{}", &format!("{:?}", cat))
         } else {
             "General Page".to_string()
         }
     }
-
-    fn generate_video_id<R: Rng>(&self, rng: &mut R) -> String {
-        const CHARS: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
-        (0..11)
-            .map(|_| CHARS[rng.gen_range(0..CHARS.len())] as char)
-            .collect()
-    }
 }
 
 struct DomainDatabase {
+    search: Vec<&'static str>,
+    video: Vec<&'static str>,
     shopping: Vec<&'static str>,
     news: Vec<&'static str>,
     research: Vec<&'static str>,
+    /// Regional sites for an interest, used ahead of the built-in ones
+    interests: &'static HashMap<InterestCategory, Vec<String>>,
+    /// Ingested news articles by interest
+    articles: HashMap<InterestCategory, Vec<FeedEntry>>,
+    /// Ingested sitemap pages by interest
+    pages: HashMap<InterestCategory, Vec<FeedEntry>>,
 }
 
 impl DomainDatabase {
-    fn new() -> Self {
+    fn for_country(country: &'static CountryData) -> Self {
+        let hosts = |hosts: &'static [String]| hosts.iter().map(String::as_str).collect();
+        let domains = &country.domains;
         Self {
-            shopping: vec!["amazon.com", "ebay.com", "etsy.com", "walmart.com", "bestbuy.com"],
-            news: vec!["bbc.com", "cnn.com", "reuters.com", "theguardian.com", "nytimes.com"],
-            research: vec!["wikipedia.org", "britannica.com", "scholar.google.com", "arxiv.org"],
+            search: hosts(&domains.search),
+            video: hosts(&domains.video),
+            shopping: hosts(&domains.shopping),
+            news: hosts(&domains.news),
+            research: hosts(&domains.research),
+            interests: &domains.interests,
+            articles: HashMap::new(),
+            pages: HashMap::new(),
+        }
+    }
+
+    fn add_articles(&mut self, category: &InterestCategory, entries: Vec<FeedEntry>) -> usize {
+        Self::add_entries(self.articles.entry(category.clone()).or_default(), entries)
+    }
+
+    fn add_pages(&mut self, category: &InterestCategory, entries: Vec<FeedEntry>) -> usize {
+        Self::add_entries(self.pages.entry(category.clone()).or_default(), entries)
+    }
+
+    fn add_entries(existing: &mut Vec<FeedEntry>, entries: Vec<FeedEntry>) -> usize {
+        let before = existing.len();
+        for entry in entries {
+            if !existing.iter().any(|e| e.url == entry.url) {
+                existing.push(entry);
+            }
         }
+        existing.len() - before
+    }
+
+    fn articles(&self, interest: &Option<InterestCategory>) -> &[FeedEntry] {
+        interest
+            .as_ref()
+            .and_then(|cat| self.articles.get(cat))
+            .map_or(&[], Vec::as_slice)
+    }
+
+    fn pages(&self, interest: &Option<InterestCategory>) -> &[FeedEntry] {
+        interest
+            .as_ref()
+            .and_then(|cat| self.pages.get(cat))
+            .map_or(&[], Vec::as_slice)
+    }
+
+    fn get_search_domain<R: Rng>(&self, rng: &mut R) -> &str {
+        self.search.choose(rng).unwrap()
+    }
+
+    fn get_video_domain<R: Rng>(&self, rng: &mut R) -> &str {
+        self.video.choose(rng).unwrap()
     }
 
     fn get_shopping_domain<R: Rng>(&self, rng: &mut R) -> &str {
@@ -289,13 +775,73 @@ impl DomainDatabase {
         self.research.choose(rng).unwrap()
     }
 
-    fn get_interest_domain<R: Rng>(&self, interest: &Option<InterestCategory>, rng: &mut R) -> &str {
+    fn has_local_sites(&self, interest: &Option<InterestCategory>) -> bool {
+        interest
+            .as_ref()
+            .is_some_and(|cat| self.interests.contains_key(cat))
+    }
+
+    fn get_interest_domain<R: Rng>(
+        &self,
+        interest: &Option<InterestCategory>,
+        rng: &mut R,
+    ) -> &str {
+        let local = interest.as_ref().and_then(|cat| self.interests.get(cat));
+        if let Some(host) = local.and_then(|hosts| hosts.choose(rng)) {
+            return host;
+        }
         if let Some(cat) = interest {
             match cat {
-                InterestCategory::Technology => *vec!["techcrunch.com", "theverge.com", "arstechnica.com"].choose(rng).unwrap(),
-                InterestCategory::Gaming => *vec!["ign.com", "gamespot.com", "polygon.com"].choose(rng).unwrap(),
-                InterestCategory::Sports => *vec!["espn.com", "bleacherreport.com", "si.com"].choose(rng).unwrap(),
-                InterestCategory::Cooking => *vec!["allrecipes.com", "foodnetwork.com", "bonappetit.com"].choose(rng).unwrap(),
+                InterestCategory::Technology => {
+                    ["techcrunch.com", "www.theverge.com", "arstechnica.com"]
+                        .choose(rng)
+                        .unwrap()
+                }
+                InterestCategory::Gaming => ["www.ign.com", "www.gamespot.com", "www.polygon.com"]
+                    .choose(rng)
+                    .unwrap(),
+                InterestCategory::Sports => ["www.espn.com", "bleacherreport.com", "www.si.com"]
+                    .choose(rng)
+                    .unwrap(),
+                InterestCategory::Cooking => [
+                    "www.allrecipes.com",
+                    "www.foodnetwork.com",
+                    "www.bonappetit.com",
+                ]
+                .choose(rng)
+                .unwrap(),
+                InterestCategory::HealthConditions => [
+                    "www.mayoclinic.org",
+                    "www.nhs.uk",
+                    "medlineplus.gov",
+                    "www.webmd.com",
+                ]
+                .choose(rng)
+                .unwrap(),
+                InterestCategory::MentalHealth => [
+                    "www.nami.org",
+                    "www.mind.org.uk",
+                    "www.psychologytoday.com",
+                    "www.verywellmind.com",
+                ]
+                .choose(rng)
+                .unwrap(),
+                InterestCategory::LegalAdvice => [
+                    "www.nolo.com",
+                    "www.findlaw.com",
+                    "www.justia.com",
+                    "www.avvo.com",
+                ]
+                .choose(rng)
+                .unwrap(),
+                InterestCategory::Immigration => [
+                    "www.uscis.gov",
+                    "travel.state.gov",
+                    "www.boundless.com",
+                    "www.nolo.com",
+                ]
+                .choose(rng)
+                .unwrap(),
                 _ => "example.com",
             }
         } else {
@@ -303,3 +849,127 @@ impl DomainDatabase {
         }
     }
 }
+
+/// Title formats for subtopic content; placeholders draw from its lexicon
+const VIDEO_TITLES: &[&str] = &[
+    "{entity} Review: Is It Worth It?",
+    "{noun} for Beginners",
+    "How to {action} {noun}",
+    "{name}: Everything You Need to Know",
+    "I Tried {entity} for 30 Days",
+];
+
+const NEWS_HEADLINES: &[&str] = &[
+    "{entity} Announces Major Update",
+    "What's Next for {entity}",
+    "{name} Roundup: This Week's Biggest Stories",
+    "The Debate Over {noun} Heats Up",
+];
+
+const PAGE_TITLES: &[&str] = &[
+    "{noun}: A Complete Guide",
+    "Beginner's Guide to {noun}",
+    "{entity} Tips and Tricks",
+    "How to {action} {noun}",
+    "{name} Community Forum",
+];
+
+fn subtopic_title<R: Rng>(formats: &[&str], subtopic: &Subtopic, rng: &mut R) -> String {
+    let lexicon = &subtopic.lexicon;
+    let word = |words: &[String], rng: &mut R| {
+        words
+            .choose(rng)
+            .map_or_else(|| subtopic.name.clone(), |w| title_case(w))
+    };
+    let entity = lexicon
+        .entities
+        .choose(rng)
+        .cloned()
+        .unwrap_or_else(|| subtopic.name.clone());
+    let noun = word(&lexicon.nouns, rng);
+    let action = word(&lexicon.actions, rng);
+
+    formats
+        .choose(rng)
+        .unwrap()
+        .replace("{name}", &subtopic.name)
+        .replace("{entity}", &entity)
+        .replace("{noun}", &noun)
+        .replace("{action}", &action)
+}
+
+/// Fill one of `formats` from a lexicon, for languages other than English
+///
+/// Words keep the lexicon's own capitalisation; only the title's first
+/// letter is raised.
+fn localized_title<R: Rng>(
+    formats: &[String],
+    language: &LanguageData,
+    category: &InterestCategory,
+    lexicon: &Lexicon,
+    rng: &mut R,
+) -> String {
+    let name = language.category_name(category);
+    let word =
+        |words: &[String], rng: &mut R| words.choose(rng).cloned().unwrap_or_else(|| name.clone());
+    let entity = word(&lexicon.entities, rng);
+    let noun = word(&lexicon.nouns, rng);
+    let adjective = word(&lexicon.adjectives, rng);
+    let action = word(&lexicon.actions, rng);
+
+    let title = formats.choose(rng).map_or_else(
+        || name.clone(),
+        |format| {
+            format
+                .replace("{name}", &name)
+                .replace("{entity}", &entity)
+                .replace("{noun}", &noun)
+                .replace("{adjective}", &adjective)
+                .replace("{action}", &action)
+        },
+    );
+    capitalize_first(&title)
+}
+
+fn capitalize_first(text: &str) -> String {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// Capitalise each word, leaving the rest of the word alone
+fn title_case(text: &str) -> String {
+    text.split(' ')
+        .map(capitalize_first)
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+/// Site section used in news-style paths for an interest
+fn section_for(interest: &Option<InterestCategory>) -> &'static str {
+    match interest {
+        Some(InterestCategory::Technology)
+        | Some(InterestCategory::Programming)
+        | Some(InterestCategory::DataScience) => "technology",
+        Some(InterestCategory::Gaming) => "games",
+        Some(InterestCategory::Sports) | Some(InterestCategory::Fitness) => "sport",
+        Some(InterestCategory::Science) => "science",
+        Some(InterestCategory::Politics) => "politics",
+        Some(InterestCategory::Finance) => "business",
+        Some(InterestCategory::Travel) => "travel",
+        Some(InterestCategory::Cooking) => "food",
+        Some(InterestCategory::Music)
+        | Some(InterestCategory::Movies)
+        | Some(InterestCategory::Books)
+        | Some(InterestCategory::Art)
+        | Some(InterestCategory::Photography) => "culture",
+        Some(InterestCategory::Fashion)
+        | Some(InterestCategory::HomeImprovement)
+        | Some(InterestCategory::Gardening) => "lifestyle",
+        Some(InterestCategory::HealthConditions) | Some(InterestCategory::MentalHealth) => "health",
+        Some(InterestCategory::LegalAdvice) | Some(InterestCategory::Immigration) => "law",
+        Some(InterestCategory::News) | None => "world",
+    }
+}
@@ -1,14 +1,60 @@
+use rand::SeedableRng;
 use wasm_bindgen::prelude::*;
-use serde::{Deserialize, Serialize};
 
-mod profile;
 mod activity;
+mod contrast;
+mod device;
+mod drift;
+mod feeds;
+mod grammar;
+mod household;
+mod interest_model;
 mod interests;
+mod locale;
+mod migration;
+mod passphrase;
+mod persona;
+mod plausibility;
+mod priors;
+mod profile;
+mod rng;
+mod safety;
 mod schedule;
+mod spec;
+mod taxonomy;
+mod url_template;
+mod validation;
 
-pub use profile::{Profile, ProfileGenerator, Demographics, InterestCategory};
-pub use activity::{ActivitySimulator, BrowsingActivity, ActivityType};
+pub use activity::{ActivitySimulator, ActivityType, BrowsingActivity, SensitiveCover};
+pub use contrast::{AgeBand, ProfileSummary};
+pub use device::{
+    Browser, Device, DeviceProfile, DeviceUsage, FormFactor, OperatingSystem, Screen, VersionRange,
+};
+pub use drift::{DriftConfig, InterestGraph};
+pub use feeds::{FeedEntry, FeedError, FeedFormat, FeedSource};
+pub use grammar::{Lexicon, QueryFeatures, QueryForm, QueryGrammar, Specificity};
+pub use household::SharedHousehold;
+pub use interest_model::InterestModel;
+pub use interests::{CoverQuery, GeneratedUrl, IngestReport, InterestUrlGenerator};
+pub use locale::{Country, Language, Locale, SummerTime, Timezone};
+pub use migration::{MigrationError, SCHEMA_VERSION};
+pub use passphrase::{
+    PassphraseError, PersonaSeed, DEFAULT_KDF_ITERATIONS, MIN_KDF_ITERATIONS, SEED_DOMAIN,
+};
+pub use persona::{Hobby, Household, HouseholdKind, HouseholdMember, Persona, Relation};
+pub use plausibility::Plausibility;
+pub use priors::{CalibrationError, DemographicPriors, Marginals};
+pub use profile::{
+    ActivityLevel, BrowsingStyle, Demographics, EducationLevel, Gender, GeneratorVersion,
+    InterestAffinity, InterestCategory, LocationType, OccupationCategory, Profile,
+    ProfileGenerator,
+};
+pub use rng::{FastRng, LiveRng};
+pub use safety::{PathRule, RiskCategory, SafetyPolicy, Verdict};
 pub use schedule::{Schedule, TimePattern};
+pub use spec::{ProfileSpec, SpecError};
+pub use taxonomy::{Subtopic, Taxonomy};
+pub use validation::{Severity, ValidationIssue};
 
 /// Initialize the WASM module
 #[wasm_bindgen(start)]
@@ -20,31 +66,251 @@ pub fn init() {
 /// Generate a new random profile
 #[wasm_bindgen]
 pub fn generate_profile(seed: Option<u64>) -> JsValue {
-    let generator = ProfileGenerator::new(seed);
+    let mut generator = ProfileGenerator::new(seed);
     let profile = generator.generate();
     serde_wasm_bindgen::to_value(&profile).unwrap()
 }
 
+/// Generate a profile the way a given generator version did, so a shared
+/// seed keeps reproducing the same persona across releases
+#[wasm_bindgen]
+pub fn generate_profile_versioned(seed: u64, version: u32) -> Result<JsValue, JsValue> {
+    let version = GeneratorVersion::from_number(version)
+        .ok_or_else(|| JsValue::from_str(&format!("unknown generator version {}", version)))?;
+    let mut generator = ProfileGenerator::new_v(Some(seed), version);
+    Ok(serde_wasm_bindgen::to_value(&generator.generate())?)
+}
+
+/// Derive the seeds for a persona slot from a passphrase, so every install
+/// given the same passphrase generates the same persona and activities
+///
+/// `iterations` defaults to `DEFAULT_KDF_ITERATIONS`.
+#[wasm_bindgen]
+pub fn derive_persona_seed(
+    passphrase: &str,
+    slot: u32,
+    iterations: Option<u32>,
+) -> Result<JsValue, JsValue> {
+    let seed = PersonaSeed::derive_with_iterations(
+        passphrase,
+        slot,
+        iterations.unwrap_or(DEFAULT_KDF_ITERATIONS),
+    )
+    .map_err(|e| JsValue::from_str(&e.to_string()))?;
+    Ok(serde_wasm_bindgen::to_value(&seed)?)
+}
+
+/// Generate a profile living in a locale such as `de-DE`, or a bare
+/// country code such as `FR`
+#[wasm_bindgen]
+pub fn generate_profile_for_locale(locale: &str, seed: Option<u64>) -> Result<JsValue, JsValue> {
+    let locale = Locale::parse(locale)
+        .ok_or_else(|| JsValue::from_str(&format!("unsupported locale {}", locale)))?;
+    let mut generator = ProfileGenerator::new(seed).with_locale(locale);
+    Ok(serde_wasm_bindgen::to_value(&generator.generate())?)
+}
+
+/// Generate a profile that satisfies a `ProfileSpec`
+///
+/// Contradictory constraints, such as a 70-year-old student, are reported
+/// as an error message.
+#[wasm_bindgen]
+pub fn generate_profile_with_spec(
+    spec_json: JsValue,
+    seed: Option<u64>,
+) -> Result<JsValue, JsValue> {
+    let spec: ProfileSpec = serde_wasm_bindgen::from_value(spec_json)?;
+    let mut generator = ProfileGenerator::new(seed);
+    let profile = generator
+        .generate_with(&spec)
+        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+    Ok(serde_wasm_bindgen::to_value(&profile)?)
+}
+
+/// Generate a persona as unlike the real user as plausibly possible
+#[wasm_bindgen]
+pub fn generate_contrasting_profile(
+    summary_json: JsValue,
+    seed: Option<u64>,
+) -> Result<JsValue, JsValue> {
+    let summary: ProfileSummary = serde_wasm_bindgen::from_value(summary_json)?;
+    let mut generator = ProfileGenerator::new(seed);
+    let profile = generator.generate_contrasting(&summary);
+    Ok(serde_wasm_bindgen::to_value(&profile)?)
+}
+
+/// Tell the life story behind a profile; the same profile always gets the
+/// same story
+#[wasm_bindgen]
+pub fn persona_for_profile(profile_json: JsValue) -> Result<JsValue, JsValue> {
+    let profile: Profile = serde_wasm_bindgen::from_value(profile_json)?;
+    Ok(serde_wasm_bindgen::to_value(&Persona::from_profile(
+        profile,
+    ))?)
+}
+
+/// The devices a profile browses on, for setting headers consistently
+#[wasm_bindgen]
+pub fn device_profile_for_profile(profile_json: JsValue) -> Result<JsValue, JsValue> {
+    let profile: Profile = serde_wasm_bindgen::from_value(profile_json)?;
+    Ok(serde_wasm_bindgen::to_value(&DeviceProfile::from_profile(
+        &profile,
+    ))?)
+}
+
+/// Generate a population of personas spread across demographics and interests
+#[wasm_bindgen]
+pub fn generate_population(
+    n: usize,
+    diversity: f64,
+    seed: Option<u64>,
+) -> Result<JsValue, JsValue> {
+    let mut generator = ProfileGenerator::new(seed);
+    let population = generator.generate_population(n, diversity);
+    Ok(serde_wasm_bindgen::to_value(&population)?)
+}
+
 /// Generate browsing activities for a profile
+///
+/// Without a seed, activities come from the unpredictable live RNG.
 #[wasm_bindgen]
 pub fn generate_activities(
     profile_json: JsValue,
     duration_hours: u32,
+    seed: Option<u64>,
 ) -> JsValue {
     let profile: Profile = serde_wasm_bindgen::from_value(profile_json).unwrap();
-    let simulator = ActivitySimulator::new(profile);
-    let activities = simulator.generate_activities(duration_hours);
+    let activities = match seed {
+        Some(seed) => ActivitySimulator::new(profile)
+            .with_seed(seed)
+            .generate_activities(duration_hours),
+        None => ActivitySimulator::live(profile).generate_activities(duration_hours),
+    };
     serde_wasm_bindgen::to_value(&activities).unwrap()
 }
 
-/// Validate that a profile is internally consistent
+/// Generate the browsing on one device shared by several profiles, which
+/// take turns at it
+///
+/// Without a seed, activities come from the unpredictable live RNG.
 #[wasm_bindgen]
-pub fn validate_profile(profile_json: JsValue) -> bool {
-    if let Ok(profile) = serde_wasm_bindgen::from_value::<Profile>(profile_json) {
-        profile.is_valid()
-    } else {
-        false
+pub fn generate_household_activities(
+    profiles_json: JsValue,
+    duration_hours: u32,
+    seed: Option<u64>,
+) -> Result<JsValue, JsValue> {
+    let profiles: Vec<Profile> = serde_wasm_bindgen::from_value(profiles_json)?;
+    if profiles.is_empty() {
+        return Err(JsValue::from_str("a household needs at least one member"));
     }
+    let activities = match seed {
+        Some(seed) => SharedHousehold::new(profiles)
+            .with_seed(seed)
+            .generate_activities(duration_hours),
+        None => SharedHousehold::live(profiles).generate_activities(duration_hours),
+    };
+    Ok(serde_wasm_bindgen::to_value(&activities)?)
+}
+
+/// Generate browsing activities, drawing news and pages from local feed dumps
+///
+/// Feeds that fail to parse are reported as an error rather than ignored.
+#[wasm_bindgen]
+pub fn generate_activities_with_feeds(
+    profile_json: JsValue,
+    duration_hours: u32,
+    feeds_json: JsValue,
+) -> Result<JsValue, JsValue> {
+    let profile: Profile = serde_wasm_bindgen::from_value(profile_json)?;
+    let feeds: Vec<FeedSource> = serde_wasm_bindgen::from_value(feeds_json)?;
+
+    let mut url_generator = InterestUrlGenerator::for_locale(&profile.demographics.locale);
+    for feed in &feeds {
+        url_generator
+            .ingest(feed)
+            .map_err(|e| JsValue::from_str(&e.to_string()))?;
+    }
+
+    let mut simulator = ActivitySimulator::live(profile).with_url_generator(url_generator);
+    let activities = simulator.generate_activities(duration_hours);
+    Ok(serde_wasm_bindgen::to_value(&activities)?)
+}
+
+/// Generate browsing activities with sensitive-topic cover traffic mixed in
+///
+/// `cover_json` is a `SensitiveCover`; omitted fields take their defaults.
+/// Injected activities have `sensitive_cover` set.
+#[wasm_bindgen]
+pub fn generate_activities_with_sensitive_cover(
+    profile_json: JsValue,
+    duration_hours: u32,
+    cover_json: JsValue,
+) -> Result<JsValue, JsValue> {
+    let profile: Profile = serde_wasm_bindgen::from_value(profile_json)?;
+    let cover: SensitiveCover = serde_wasm_bindgen::from_value(cover_json)?;
+
+    let mut simulator = ActivitySimulator::live(profile).with_sensitive_cover(cover);
+    let activities = simulator.generate_activities(duration_hours);
+    Ok(serde_wasm_bindgen::to_value(&activities)?)
+}
+
+/// Judge whether a URL may be emitted or loaded under a safety policy
+#[wasm_bindgen]
+pub fn check_url_safety(url: &str, policy_json: JsValue) -> Result<JsValue, JsValue> {
+    let policy: SafetyPolicy = if policy_json.is_undefined() || policy_json.is_null() {
+        SafetyPolicy::default()
+    } else {
+        serde_wasm_bindgen::from_value(policy_json)?
+    };
+    Ok(serde_wasm_bindgen::to_value(&policy.check_str(url))?)
+}
+
+/// Upgrade a stored profile of any earlier schema version to the current one
+#[wasm_bindgen]
+pub fn migrate_profile(profile_json: JsValue) -> Result<JsValue, JsValue> {
+    let json: serde_json::Value = serde_wasm_bindgen::from_value(profile_json)?;
+    let profile = Profile::migrate(json).map_err(|e| JsValue::from_str(&e.to_string()))?;
+    Ok(serde_wasm_bindgen::to_value(&profile)?)
+}
+
+/// Check a profile, returning every issue found; an empty list means valid
+///
+/// A profile that doesn't deserialize at all is reported as a single error.
+#[wasm_bindgen]
+pub fn validate_profile(profile_json: JsValue) -> JsValue {
+    let issues = match serde_wasm_bindgen::from_value::<Profile>(profile_json) {
+        Ok(profile) => profile.validate().err().unwrap_or_default(),
+        Err(e) => vec![ValidationIssue::error("", format!("not a profile: {}", e))],
+    };
+    serde_wasm_bindgen::to_value(&issues).unwrap()
+}
+
+/// Score how typical a profile is of generated ones
+#[wasm_bindgen]
+pub fn profile_plausibility(profile_json: JsValue) -> Result<JsValue, JsValue> {
+    let profile: Profile = serde_wasm_bindgen::from_value(profile_json)?;
+    Ok(serde_wasm_bindgen::to_value(&profile.plausibility())?)
+}
+
+/// Generate cover queries in other categories shaped like a real search query
+///
+/// `locale`, such as `de-DE`, is the language the user searches in;
+/// `en-US` if omitted.
+#[wasm_bindgen]
+pub fn generate_cover_queries(
+    query: &str,
+    count: u32,
+    locale: Option<String>,
+) -> Result<JsValue, JsValue> {
+    let locale = match locale {
+        Some(tag) => Locale::parse(&tag)
+            .ok_or_else(|| JsValue::from_str(&format!("unsupported locale {}", tag)))?,
+        None => Locale::default(),
+    };
+    let generator = InterestUrlGenerator::for_locale(&locale);
+    let mut rng = LiveRng::from_entropy();
+    let cover = generator.cover_queries(query, None, count as usize, &mut rng);
+    Ok(serde_wasm_bindgen::to_value(&cover)?)
 }
 
 /// Get recommended activity schedule for a profile
@@ -61,7 +327,7 @@ mod tests {
 
     #[test]
     fn test_profile_generation() {
-        let generator = ProfileGenerator::new(Some(42));
+        let mut generator = ProfileGenerator::new(Some(42));
         let profile = generator.generate();
         assert!(profile.is_valid());
         assert!(!profile.name.is_empty());
@@ -69,9 +335,9 @@ mod tests {
 
     #[test]
     fn test_activity_generation() {
-        let generator = ProfileGenerator::new(Some(42));
+        let mut generator = ProfileGenerator::new(Some(42));
         let profile = generator.generate();
-        let simulator = ActivitySimulator::new(profile);
+        let mut simulator = ActivitySimulator::new(profile);
         let activities = simulator.generate_activities(24);
         assert!(!activities.is_empty());
     }
@@ -0,0 +1,411 @@
+use crate::grammar::{Lexicon, Template};
+use crate::profile::InterestCategory;
+use chrono::{DateTime, Datelike, NaiveDate, NaiveDateTime, Weekday};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::OnceLock;
+
+/// Names, domains, timezones and non-English vocabulary per locale
+const BUILTIN_LOCALES: &str = include_str!("../data/locales.json");
+
+/// Countries with bundled names, domains and demographic priors
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Country {
+    US,
+    GB,
+    DE,
+    FR,
+}
+
+impl Country {
+    pub const ALL: [Country; 4] = [Country::US, Country::GB, Country::DE, Country::FR];
+
+    /// ISO 3166 code, as used for [`DemographicPriors`](crate::DemographicPriors) regions
+    pub fn code(&self) -> &'static str {
+        match self {
+            Country::US => "US",
+            Country::GB => "GB",
+            Country::DE => "DE",
+            Country::FR => "FR",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<Country> {
+        Country::ALL
+            .into_iter()
+            .find(|country| country.code().eq_ignore_ascii_case(code))
+    }
+}
+
+/// Languages queries and titles can be written in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Language {
+    En,
+    De,
+    Fr,
+}
+
+impl Language {
+    pub const ALL: [Language; 3] = [Language::En, Language::De, Language::Fr];
+
+    /// ISO 639-1 code
+    pub fn code(&self) -> &'static str {
+        match self {
+            Language::En => "en",
+            Language::De => "de",
+            Language::Fr => "fr",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<Language> {
+        Language::ALL
+            .into_iter()
+            .find(|language| language.code().eq_ignore_ascii_case(code))
+    }
+}
+
+/// Where a persona lives and what language it browses in
+///
+/// The country decides names, hometowns, regional sites and the timezone;
+/// the language decides search queries and page titles. The two usually
+/// go together, see [`Locale::of_country`], but needn't.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Locale {
+    pub country: Country,
+    pub language: Language,
+}
+
+impl Default for Locale {
+    fn default() -> Self {
+        Locale::of_country(Country::US)
+    }
+}
+
+impl fmt::Display for Locale {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{}", self.language.code(), self.country.code())
+    }
+}
+
+impl Locale {
+    pub fn new(country: Country, language: Language) -> Self {
+        Locale { country, language }
+    }
+
+    /// A country with its usual language
+    pub fn of_country(country: Country) -> Self {
+        Locale {
+            country,
+            language: LocaleData::builtin().country(country).language,
+        }
+    }
+
+    /// Parse a tag such as `de-DE`, or a bare country code such as `DE`
+    pub fn parse(tag: &str) -> Option<Locale> {
+        match tag.split_once(['-', '_']) {
+            Some((language, country)) => Some(Locale::new(
+                Country::from_code(country)?,
+                Language::from_code(language)?,
+            )),
+            None => Country::from_code(tag).map(Locale::of_country),
+        }
+    }
+
+    /// The timezone a persona here most likely keeps
+    ///
+    /// Countries spanning several zones get their most populous one.
+    pub fn timezone(&self) -> &'static Timezone {
+        &self.country_data().timezone
+    }
+
+    pub(crate) fn country_data(&self) -> &'static CountryData {
+        LocaleData::builtin().country(self.country)
+    }
+
+    /// Grammar and vocabulary for the language, or `None` for English,
+    /// whose grammar is built into [`QueryGrammar`](crate::QueryGrammar)
+    pub(crate) fn language_data(&self) -> Option<&'static LanguageData> {
+        LocaleData::builtin().languages.get(&self.language)
+    }
+}
+
+/// A timezone by IANA name, with its offset in standard time and the
+/// rule it follows for summer time
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Timezone {
+    pub name: String,
+    /// Minutes east of UTC in standard time
+    pub utc_offset_minutes: i32,
+    /// `None` where clocks never change
+    #[serde(default)]
+    pub summer_time: Option<SummerTime>,
+}
+
+impl Timezone {
+    /// A bundled country's timezone by IANA name
+    pub fn named(name: &str) -> Option<&'static Timezone> {
+        Country::ALL
+            .into_iter()
+            .map(|country| Locale::of_country(country).timezone())
+            .find(|timezone| timezone.name == name)
+    }
+
+    /// Minutes east of UTC at a Unix timestamp, summer time included
+    pub fn utc_offset_at(&self, timestamp: i64) -> i32 {
+        let summer = self
+            .summer_time
+            .is_some_and(|rule| rule.in_force(timestamp, self.utc_offset_minutes));
+        self.utc_offset_minutes + if summer { 60 } else { 0 }
+    }
+}
+
+/// When clocks go forward an hour
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum SummerTime {
+    /// Last Sunday in March to last Sunday in October, at 01:00 UTC
+    EU,
+    /// Second Sunday in March to first Sunday in November, at 02:00 local
+    US,
+}
+
+impl SummerTime {
+    fn in_force(&self, timestamp: i64, standard_offset_minutes: i32) -> bool {
+        let Some(utc) = DateTime::from_timestamp(timestamp, 0) else {
+            return false;
+        };
+        let year = utc.year();
+        let sunday = |month, n| NaiveDate::from_weekday_of_month_opt(year, month, Weekday::Sun, n);
+        let last_sunday = |month| sunday(month, 5).or_else(|| sunday(month, 4));
+        let at = |date: Option<NaiveDate>, hour| {
+            date.and_then(|date| date.and_hms_opt(hour, 0, 0))
+                .map_or(0, |time| time.and_utc().timestamp())
+        };
+        let standard = standard_offset_minutes as i64 * 60;
+        let (start, end) = match self {
+            SummerTime::EU => (at(last_sunday(3), 1), at(last_sunday(10), 1)),
+            SummerTime::US => (
+                at(sunday(3, 2), 2) - standard,
+                at(sunday(11, 1), 2) - standard - 3600,
+            ),
+        };
+        (start..end).contains(&timestamp)
+    }
+}
+
+/// Local time in the named timezone, falling back to a fixed offset for
+/// zones that aren't bundled
+pub(crate) fn local_time(
+    timezone: &str,
+    standard_offset_minutes: i32,
+    timestamp: i64,
+) -> Option<NaiveDateTime> {
+    let offset = Timezone::named(timezone).map_or(standard_offset_minutes, |timezone| {
+        timezone.utc_offset_at(timestamp)
+    });
+    DateTime::from_timestamp(timestamp + offset as i64 * 60, 0).map(|local| local.naive_utc())
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct Names {
+    pub male: Vec<String>,
+    pub female: Vec<String>,
+    pub neutral: Vec<String>,
+    pub last: Vec<String>,
+}
+
+/// Sites a persona in one country visits, by kind
+#[derive(Debug, Deserialize)]
+pub(crate) struct Domains {
+    pub search: Vec<String>,
+    pub video: Vec<String>,
+    pub shopping: Vec<String>,
+    pub news: Vec<String>,
+    pub research: Vec<String>,
+    /// Local sites for an interest, used ahead of the built-in ones
+    #[serde(default)]
+    pub interests: HashMap<InterestCategory, Vec<String>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct CountryData {
+    language: Language,
+    timezone: Timezone,
+    pub names: Names,
+    /// Places that fill `#location#` in search queries
+    pub locations: Vec<String>,
+    pub domains: Domains,
+}
+
+/// Vocabulary for one category; entities it leaves out are the English ones
+#[derive(Debug, Deserialize)]
+struct RawLexicon {
+    #[serde(default)]
+    entities: Vec<String>,
+    nouns: Vec<String>,
+    adjectives: Vec<String>,
+    actions: Vec<String>,
+}
+
+/// Formats for generated page titles, filled from a category's lexicon
+/// like those for subtopics in English
+#[derive(Debug, Deserialize)]
+pub(crate) struct Titles {
+    pub video: Vec<String>,
+    pub news: Vec<String>,
+    pub page: Vec<String>,
+    pub product: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct LanguageData {
+    /// Whether adjectives follow the noun they qualify, as in French
+    pub adjective_after: bool,
+    templates: Vec<(u32, String)>,
+    /// Added for interests pursued somewhere physical
+    local_templates: Vec<(u32, String)>,
+    pub questions: Vec<String>,
+    /// Words that open a question, for recognising one
+    pub question_words: Vec<String>,
+    /// Function words that don't make a query any more specific
+    pub stopwords: Vec<String>,
+    pub prefixes: Vec<String>,
+    pub suffixes: Vec<String>,
+    /// What each category is called, for titles that name it
+    category_names: HashMap<InterestCategory, String>,
+    pub titles: Titles,
+    lexicons: HashMap<InterestCategory, RawLexicon>,
+}
+
+impl LanguageData {
+    pub fn templates(&'static self) -> Vec<Template> {
+        to_templates(&self.templates)
+    }
+
+    pub fn local_templates(&'static self) -> Vec<Template> {
+        to_templates(&self.local_templates)
+    }
+
+    pub fn category_name(&self, category: &InterestCategory) -> String {
+        self.category_names
+            .get(category)
+            .cloned()
+            .unwrap_or_else(|| format!("{:?}", category))
+    }
+
+    /// Vocabulary for a category, or the English one for `None` and for
+    /// categories the language doesn't cover
+    pub fn lexicon(&self, category: Option<&InterestCategory>) -> Lexicon {
+        let english = Lexicon::builtin(category);
+        let Some(raw) = category.and_then(|c| self.lexicons.get(c)) else {
+            return english;
+        };
+        Lexicon {
+            entities: if raw.entities.is_empty() {
+                english.entities
+            } else {
+                raw.entities.clone()
+            },
+            nouns: raw.nouns.clone(),
+            adjectives: raw.adjectives.clone(),
+            actions: raw.actions.clone(),
+            local: english.local,
+        }
+    }
+}
+
+fn to_templates(raw: &'static [(u32, String)]) -> Vec<Template> {
+    raw.iter()
+        .map(|(weight, pattern)| Template {
+            weight: *weight,
+            pattern,
+        })
+        .collect()
+}
+
+#[derive(Debug, Deserialize)]
+struct LocaleData {
+    countries: HashMap<Country, CountryData>,
+    languages: HashMap<Language, LanguageData>,
+}
+
+impl LocaleData {
+    fn builtin() -> &'static LocaleData {
+        static LOCALES: OnceLock<LocaleData> = OnceLock::new();
+        LOCALES.get_or_init(|| {
+            serde_json::from_str(BUILTIN_LOCALES).expect("built-in locales are valid")
+        })
+    }
+
+    fn country(&self, country: Country) -> &CountryData {
+        &self.countries[&country]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_every_locale_is_covered() {
+        let data = LocaleData::builtin();
+        for country in Country::ALL {
+            let country = data.country(country);
+            assert!(!country.names.last.is_empty() && !country.locations.is_empty());
+            assert!(!country.domains.search.is_empty() && !country.domains.research.is_empty());
+        }
+        for language in Language::ALL.iter().filter(|l| **l != Language::En) {
+            let data = &data.languages[language];
+            for category in InterestCategory::ALL.iter() {
+                assert!(
+                    data.lexicons.contains_key(category),
+                    "{:?} {:?}",
+                    language,
+                    category
+                );
+                assert!(data.category_names.contains_key(category));
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_and_display() {
+        let german = Locale::parse("de-DE").unwrap();
+        assert_eq!(german, Locale::new(Country::DE, Language::De));
+        assert_eq!(german.to_string(), "de-DE");
+        assert_eq!(Locale::parse("de-XX"), None);
+        assert_eq!(
+            Locale::parse("GB"),
+            Some(Locale::new(Country::GB, Language::En))
+        );
+        assert_eq!(Locale::parse("en_FR").unwrap().language, Language::En);
+        assert_eq!(german.timezone().name, "Europe/Berlin");
+    }
+
+    #[test]
+    fn test_summer_time() {
+        let at = |date: &str| {
+            NaiveDateTime::parse_from_str(date, "%Y-%m-%d %H:%M")
+                .unwrap()
+                .and_utc()
+                .timestamp()
+        };
+        let berlin = Locale::of_country(Country::DE).timezone();
+        assert_eq!(berlin.utc_offset_at(at("2026-01-15 12:00")), 60);
+        assert_eq!(berlin.utc_offset_at(at("2026-03-29 00:59")), 60);
+        assert_eq!(berlin.utc_offset_at(at("2026-03-29 01:00")), 120);
+        assert_eq!(berlin.utc_offset_at(at("2026-10-25 00:59")), 120);
+        assert_eq!(berlin.utc_offset_at(at("2026-10-25 01:00")), 60);
+
+        let new_york = Timezone::named("America/New_York").unwrap();
+        assert_eq!(new_york.utc_offset_at(at("2026-03-08 06:59")), -300);
+        assert_eq!(new_york.utc_offset_at(at("2026-03-08 07:00")), -240);
+        assert_eq!(new_york.utc_offset_at(at("2026-11-01 05:59")), -240);
+        assert_eq!(new_york.utc_offset_at(at("2026-11-01 06:00")), -300);
+        assert_eq!(
+            local_time("Asia/Tokyo", 540, 0).unwrap().to_string(),
+            "1970-01-01 09:00:00"
+        );
+    }
+}
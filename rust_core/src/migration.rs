@@ -0,0 +1,140 @@
+use crate::profile::Profile;
+use serde_json::{Map, Value};
+use std::fmt;
+
+/// Version of the profile JSON this build writes
+///
+/// Bump it together with a new entry in [`MIGRATIONS`] whenever a change to
+/// [`Profile`] would stop older JSON from deserializing as it did.
+pub const SCHEMA_VERSION: u32 = 2;
+
+/// Upgrades a profile object by one version, from its index to the next
+type Migration = fn(&mut Map<String, Value>) -> Result<(), String>;
+
+const MIGRATIONS: [Migration; SCHEMA_VERSION as usize] = [v0_to_v1, v1_to_v2];
+
+/// Why a stored profile couldn't be brought up to date
+#[derive(Debug)]
+pub enum MigrationError {
+    /// The JSON isn't an object, or its `schema_version` isn't a number
+    NotAProfile(String),
+    /// Written by a newer build that knows a version this one doesn't
+    Newer(u32),
+    /// An upgrade step failed on the shape it was given
+    Step { from: u32, message: String },
+    /// Upgraded, but still not a valid profile
+    Json(serde_json::Error),
+}
+
+impl fmt::Display for MigrationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MigrationError::NotAProfile(reason) => write!(f, "not a profile: {}", reason),
+            MigrationError::Newer(version) => write!(
+                f,
+                "schema version {} is newer than the supported {}",
+                version, SCHEMA_VERSION
+            ),
+            MigrationError::Step { from, message } => write!(
+                f,
+                "upgrading from schema version {} failed: {}",
+                from, message
+            ),
+            MigrationError::Json(e) => write!(f, "invalid profile after migration: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for MigrationError {}
+
+/// Upgrade profile JSON of any known schema version to a current [`Profile`]
+///
+/// JSON without a `schema_version` predates versioning and counts as
+/// version 0.
+pub(crate) fn migrate(json: Value) -> Result<Profile, MigrationError> {
+    let Value::Object(mut object) = json else {
+        return Err(MigrationError::NotAProfile("expected an object".into()));
+    };
+    let version = match object.get("schema_version") {
+        None => 0,
+        Some(v) => v
+            .as_u64()
+            .and_then(|v| u32::try_from(v).ok())
+            .ok_or_else(|| {
+                MigrationError::NotAProfile(format!("schema_version {} is not a version", v))
+            })?,
+    };
+    if version > SCHEMA_VERSION {
+        return Err(MigrationError::Newer(version));
+    }
+
+    for (from, step) in MIGRATIONS.iter().enumerate().skip(version as usize) {
+        step(&mut object).map_err(|message| MigrationError::Step {
+            from: from as u32,
+            message,
+        })?;
+        object.insert("schema_version".into(), Value::from(from as u32 + 1));
+    }
+    serde_json::from_value(Value::Object(object)).map_err(MigrationError::Json)
+}
+
+/// Interests used to be bare categories, and subtopics didn't exist
+fn v0_to_v1(profile: &mut Map<String, Value>) -> Result<(), String> {
+    let interests = profile
+        .get_mut("interests")
+        .and_then(Value::as_array_mut)
+        .ok_or("interests is missing or not a list")?;
+    let even = 1.0 / interests.len().max(1) as f64;
+    for interest in interests.iter_mut() {
+        if let Value::String(category) = interest {
+            let category = std::mem::take(category);
+            *interest = serde_json::json!({ "category": category, "weight": even });
+        }
+    }
+    profile
+        .entry("subtopics")
+        .or_insert_with(|| Value::Array(Vec::new()));
+    Ok(())
+}
+
+/// Profiles were all American before demographics had a locale
+fn v1_to_v2(profile: &mut Map<String, Value>) -> Result<(), String> {
+    let demographics = profile
+        .get_mut("demographics")
+        .and_then(Value::as_object_mut)
+        .ok_or("demographics is missing or not an object")?;
+    demographics
+        .entry("locale")
+        .or_insert_with(|| serde_json::json!({ "country": "US", "language": "en" }));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_current_profile_round_trips() {
+        let profile = crate::ProfileGenerator::new(Some(42)).generate();
+        let json = serde_json::to_value(&profile).unwrap();
+        assert_eq!(json["schema_version"], SCHEMA_VERSION);
+
+        let migrated = migrate(json.clone()).unwrap();
+        assert_eq!(serde_json::to_value(&migrated).unwrap(), json);
+    }
+
+    #[test]
+    fn test_rejects_newer_and_malformed() {
+        let newer = json!({ "schema_version": SCHEMA_VERSION + 1 });
+        assert!(matches!(migrate(newer), Err(MigrationError::Newer(_))));
+        assert!(matches!(
+            migrate(json!([1, 2])),
+            Err(MigrationError::NotAProfile(_))
+        ));
+        assert!(matches!(
+            migrate(json!({ "interests": "Gaming" })),
+            Err(MigrationError::Step { from: 0, .. })
+        ));
+    }
+}
@@ -0,0 +1,142 @@
+use pbkdf2::pbkdf2_hmac;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::fmt;
+
+/// Domain-separation string mixed into the salt
+///
+/// Part of the derivation format: changing it changes every persona.
+pub const SEED_DOMAIN: &str = "doubletrack.persona-seed.v1";
+
+/// PBKDF2 rounds unless configured otherwise, per OWASP's 2023 guidance for
+/// PBKDF2-HMAC-SHA256
+pub const DEFAULT_KDF_ITERATIONS: u32 = 600_000;
+
+/// Fewest rounds accepted, so a passphrase is never cheap to brute-force
+pub const MIN_KDF_ITERATIONS: u32 = 10_000;
+
+/// Seeds are cut to this many bits so they survive a round trip through a
+/// JavaScript number
+const SEED_BITS: u32 = 53;
+
+/// Seeds for one persona, derived from a passphrase
+///
+/// The derivation is:
+///
+/// ```text
+/// salt = SEED_DOMAIN || 0x00 || slot as u32, big-endian
+/// key  = PBKDF2-HMAC-SHA256(passphrase as UTF-8, salt, iterations, 32 bytes)
+/// profile  = key[0..8] as u64, little-endian, low 53 bits
+/// activity = key[8..16] as u64, little-endian, low 53 bits
+/// ```
+///
+/// The remaining 16 bytes are reserved. The passphrase is used exactly as
+/// given, without trimming or Unicode normalization, so installs must pass
+/// the same bytes and the same iteration count to agree.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PersonaSeed {
+    /// For [`ProfileGenerator::new`](crate::ProfileGenerator::new)
+    pub profile: u64,
+    /// For [`ActivitySimulator::with_seed`](crate::ActivitySimulator::with_seed)
+    pub activity: u64,
+}
+
+/// Why a passphrase couldn't be turned into seeds
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PassphraseError {
+    Empty,
+    TooFewIterations(u32),
+}
+
+impl fmt::Display for PassphraseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PassphraseError::Empty => write!(f, "passphrase is empty"),
+            PassphraseError::TooFewIterations(n) => write!(
+                f,
+                "{} iterations is below the minimum of {}",
+                n, MIN_KDF_ITERATIONS
+            ),
+        }
+    }
+}
+
+impl std::error::Error for PassphraseError {}
+
+impl PersonaSeed {
+    /// Seeds for persona `slot` under `passphrase`, with the default number
+    /// of iterations
+    ///
+    /// Deliberately slow: expect it to take a noticeable fraction of a
+    /// second in an optimized build.
+    pub fn derive(passphrase: &str, slot: u32) -> Result<PersonaSeed, PassphraseError> {
+        Self::derive_with_iterations(passphrase, slot, DEFAULT_KDF_ITERATIONS)
+    }
+
+    pub fn derive_with_iterations(
+        passphrase: &str,
+        slot: u32,
+        iterations: u32,
+    ) -> Result<PersonaSeed, PassphraseError> {
+        if passphrase.is_empty() {
+            return Err(PassphraseError::Empty);
+        }
+        if iterations < MIN_KDF_ITERATIONS {
+            return Err(PassphraseError::TooFewIterations(iterations));
+        }
+
+        let mut salt = Vec::with_capacity(SEED_DOMAIN.len() + 5);
+        salt.extend_from_slice(SEED_DOMAIN.as_bytes());
+        salt.push(0);
+        salt.extend_from_slice(&slot.to_be_bytes());
+
+        let mut key = [0u8; 32];
+        pbkdf2_hmac::<Sha256>(passphrase.as_bytes(), &salt, iterations, &mut key);
+        let seed = |bytes: &[u8]| {
+            let value = u64::from_le_bytes(bytes.try_into().expect("eight bytes"));
+            value & ((1 << SEED_BITS) - 1)
+        };
+        Ok(PersonaSeed {
+            profile: seed(&key[0..8]),
+            activity: seed(&key[8..16]),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PASSPHRASE: &str = "correct horse battery staple";
+
+    #[test]
+    fn test_matches_documented_format() {
+        // Computed independently with Python's hashlib.pbkdf2_hmac
+        let seed = PersonaSeed::derive_with_iterations(PASSPHRASE, 0, MIN_KDF_ITERATIONS).unwrap();
+        assert_eq!(
+            seed,
+            PersonaSeed {
+                profile: 841_233_725_071_528,
+                activity: 8_468_518_878_795_628,
+            }
+        );
+    }
+
+    #[test]
+    fn test_slots_and_passphrases_separate() {
+        let derive = |passphrase: &str, slot| {
+            PersonaSeed::derive_with_iterations(passphrase, slot, MIN_KDF_ITERATIONS).unwrap()
+        };
+        let base = derive(PASSPHRASE, 0);
+        assert_eq!(derive(PASSPHRASE, 0), base);
+        assert_ne!(derive(PASSPHRASE, 1), base);
+        assert_ne!(derive("correct horse battery stapler", 0), base);
+        assert!(base.profile < 1 << SEED_BITS && base.activity < 1 << SEED_BITS);
+
+        assert_eq!(PersonaSeed::derive("", 0), Err(PassphraseError::Empty));
+        assert_eq!(
+            PersonaSeed::derive_with_iterations(PASSPHRASE, 0, 1000),
+            Err(PassphraseError::TooFewIterations(1000))
+        );
+    }
+}
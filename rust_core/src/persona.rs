@@ -0,0 +1,540 @@
+use crate::contrast::AgeBand;
+use crate::locale::Country;
+use crate::profile::{
+    self, Demographics, EducationLevel, Gender, InterestCategory, LocationType, OccupationCategory,
+    Profile,
+};
+use crate::rng::derived_rng;
+use crate::taxonomy::Taxonomy;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+/// Occupation titles, employers, households, hometowns and hobbies
+const BUILTIN_BACKSTORIES: &str = include_str!("../data/personas.json");
+
+/// Keeps backstory draws apart from anything else derived from a profile id
+const PERSONA_DOMAIN: &str = "doubletrack.persona.v1";
+
+/// Youngest a parent is at a child's birth
+const MIN_PARENT_GAP: u8 = 20;
+
+/// Oldest a child still living at home is
+const MAX_CHILD_AGE: u8 = 22;
+
+/// Hobbies named in the bio; the rest are only listed
+const BIO_HOBBIES: usize = 3;
+
+/// Who a household is made of, besides the persona
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum HouseholdKind {
+    Alone,
+    Partner,
+    PartnerAndChildren,
+    SingleParent,
+    WithParents,
+    Roommates,
+}
+
+impl HouseholdKind {
+    pub const ALL: [HouseholdKind; 6] = [
+        HouseholdKind::Alone,
+        HouseholdKind::Partner,
+        HouseholdKind::PartnerAndChildren,
+        HouseholdKind::SingleParent,
+        HouseholdKind::WithParents,
+        HouseholdKind::Roommates,
+    ];
+
+    /// Whether someone of `age` can believably live this way
+    fn fits(&self, age: u8) -> bool {
+        match self {
+            HouseholdKind::PartnerAndChildren | HouseholdKind::SingleParent => age > MIN_PARENT_GAP,
+            HouseholdKind::WithParents => age.saturating_add(MIN_PARENT_GAP) <= 100,
+            _ => true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Relation {
+    Partner,
+    Child,
+    Parent,
+    Roommate,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HouseholdMember {
+    pub relation: Relation,
+    pub name: String,
+    pub age: u8,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Household {
+    pub kind: HouseholdKind,
+    pub members: Vec<HouseholdMember>,
+}
+
+/// What the persona actually does for one of its interests
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Hobby {
+    pub interest: InterestCategory,
+    /// Subtopic the hobby is about, usually one of the profile's own
+    pub subtopic: Option<String>,
+    /// Phrased to follow "spends free time", e.g. "baking sourdough loaves"
+    pub description: String,
+}
+
+/// A [`Profile`] with the life story that explains it
+///
+/// Every detail follows from the profile: the occupation title fits its
+/// education, the household its age, the hobbies its interests and
+/// subtopics. The story is drawn from the profile id, so the same profile
+/// always tells the same one and nothing beyond the profile needs storing.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Persona {
+    pub profile: Profile,
+    pub occupation_title: String,
+    /// Kind of organisation the persona works, studies or worked at
+    pub employer: String,
+    pub household: Household,
+    pub hometown: String,
+    /// One per interest, in order of the profile's affinity for it
+    pub hobbies: Vec<Hobby>,
+    pub bio: String,
+}
+
+/// A job title, open only to the listed education levels if any, and the
+/// kinds of employer that hire for it
+#[derive(Debug, Deserialize)]
+struct Occupation {
+    title: String,
+    #[serde(default)]
+    education: Vec<EducationLevel>,
+    employers: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Backstories {
+    occupations: HashMap<OccupationCategory, Vec<Occupation>>,
+    households: HashMap<AgeBand, HashMap<HouseholdKind, f64>>,
+    /// Multipliers on `households` for particular occupations
+    household_occupation: HashMap<OccupationCategory, HashMap<HouseholdKind, f64>>,
+    /// Keyed by country, then by the kind of place
+    hometowns: HashMap<Country, HashMap<LocationType, Vec<String>>>,
+    /// Keyed by subtopic id; a subtopic without hobbies uses its parent's
+    hobbies: HashMap<String, Vec<String>>,
+}
+
+impl Backstories {
+    fn builtin() -> &'static Backstories {
+        static BACKSTORIES: OnceLock<Backstories> = OnceLock::new();
+        BACKSTORIES.get_or_init(|| {
+            serde_json::from_str(BUILTIN_BACKSTORIES).expect("built-in backstories are valid")
+        })
+    }
+}
+
+impl Persona {
+    pub fn from_profile(profile: Profile) -> Persona {
+        let data = Backstories::builtin();
+        let mut rng = derived_rng(PERSONA_DOMAIN, &profile.id);
+        let demographics = &profile.demographics;
+
+        let occupation = occupation(
+            &data.occupations[&demographics.occupation_category],
+            &demographics.education_level,
+            &mut rng,
+        );
+        let occupation_title = occupation.title.clone();
+        let employer = occupation
+            .employers
+            .choose(&mut rng)
+            .cloned()
+            .unwrap_or_default();
+        let household = household(&profile, data, &mut rng);
+        let hometown = hometown(demographics, data, &mut rng);
+        let hobbies = hobbies(&profile, data, &mut rng);
+
+        let mut persona = Persona {
+            profile,
+            occupation_title,
+            employer,
+            household,
+            hometown,
+            hobbies,
+            bio: String::new(),
+        };
+        persona.bio = persona.write_bio();
+        persona
+    }
+
+    pub fn first_name(&self) -> &str {
+        self.profile
+            .name
+            .split_whitespace()
+            .next()
+            .unwrap_or_default()
+    }
+
+    fn write_bio(&self) -> String {
+        let demographics = &self.profile.demographics;
+        let (subject, possessive) = pronouns(&demographics.gender);
+        let age = demographics.age;
+        let article = if age == 18 || (80..90).contains(&age) {
+            "an"
+        } else {
+            "a"
+        };
+        let workplace = match demographics.occupation_category {
+            OccupationCategory::Retired => "formerly at",
+            _ => "at",
+        };
+        let location = match demographics.location_type {
+            LocationType::Urban => "in the city",
+            LocationType::Suburban => "in the suburbs",
+            LocationType::Rural => "in the countryside",
+        };
+
+        let mut bio = format!(
+            "{} is {} {}-year-old {} {} {}, living {} {}. {} grew up in {}",
+            self.first_name(),
+            article,
+            age,
+            self.occupation_title,
+            workplace,
+            self.employer,
+            location,
+            self.household.describe(possessive),
+            capitalize(subject),
+            self.hometown,
+        );
+        let hobbies: Vec<&str> = self
+            .hobbies
+            .iter()
+            .take(BIO_HOBBIES)
+            .map(|h| h.description.as_str())
+            .collect();
+        if !hobbies.is_empty() {
+            let spends = if subject == "they" { "spend" } else { "spends" };
+            bio.push_str(&format!(" and {} free time {}", spends, join(&hobbies)));
+        }
+        bio.push('.');
+        bio
+    }
+}
+
+impl Household {
+    /// Who the persona lives with, e.g. "with Sarah and their two children"
+    fn describe(&self, possessive: &str) -> String {
+        let first = |relation| {
+            self.members
+                .iter()
+                .find(|m| m.relation == relation)
+                .and_then(|m| m.name.split_whitespace().next())
+                .unwrap_or_default()
+        };
+        let count = |relation| {
+            self.members
+                .iter()
+                .filter(|m| m.relation == relation)
+                .count()
+        };
+        let children = |n: usize| match n {
+            1 => "child".to_string(),
+            n => format!("{} children", number(n)),
+        };
+
+        match self.kind {
+            HouseholdKind::Alone => format!("on {} own", possessive),
+            HouseholdKind::Partner => format!("with {}", first(Relation::Partner)),
+            HouseholdKind::PartnerAndChildren => format!(
+                "with {} and their {}",
+                first(Relation::Partner),
+                children(count(Relation::Child))
+            ),
+            HouseholdKind::SingleParent => {
+                format!("with {} {}", possessive, children(count(Relation::Child)))
+            }
+            HouseholdKind::WithParents => match count(Relation::Parent) {
+                1 => format!("with {} parent", possessive),
+                _ => format!("with {} parents", possessive),
+            },
+            HouseholdKind::Roommates => match count(Relation::Roommate) {
+                1 => "with a roommate".to_string(),
+                n => format!("with {} roommates", number(n)),
+            },
+        }
+    }
+}
+
+/// Any of `occupations` open to someone with `education`, or any at all
+/// if none is
+fn occupation<'a, R: Rng>(
+    occupations: &'a [Occupation],
+    education: &EducationLevel,
+    rng: &mut R,
+) -> &'a Occupation {
+    let fitting: Vec<&Occupation> = occupations
+        .iter()
+        .filter(|o| o.education.is_empty() || o.education.contains(education))
+        .collect();
+    fitting
+        .choose(rng)
+        .copied()
+        .or_else(|| occupations.choose(rng))
+        .expect("every occupation category has titles")
+}
+
+fn household<R: Rng>(profile: &Profile, data: &Backstories, rng: &mut R) -> Household {
+    let demographics = &profile.demographics;
+    let age = demographics.age;
+    let weights = &data.households[&AgeBand::of(age)];
+    let multipliers = data
+        .household_occupation
+        .get(&demographics.occupation_category);
+    let options: Vec<(HouseholdKind, f64)> = HouseholdKind::ALL
+        .iter()
+        .filter(|kind| kind.fits(age))
+        .map(|kind| {
+            let weight = weights.get(kind).copied().unwrap_or(0.0)
+                * multipliers
+                    .and_then(|m| m.get(kind))
+                    .copied()
+                    .unwrap_or(1.0);
+            (*kind, weight)
+        })
+        .collect();
+    let kind = options
+        .choose_weighted(rng, |(_, weight)| *weight)
+        .map(|(kind, _)| *kind)
+        .unwrap_or(HouseholdKind::Alone);
+
+    let locale = &profile.demographics.locale;
+    let surname = profile.name.split_whitespace().last().unwrap_or_default();
+    let mut members = Vec::new();
+    let mut add = |relation, name: String, age: u8| {
+        members.push(HouseholdMember {
+            relation,
+            name,
+            age,
+        })
+    };
+    if matches!(
+        kind,
+        HouseholdKind::Partner | HouseholdKind::PartnerAndChildren
+    ) {
+        let partner_age = (age as i32 + rng.gen_range(-5..=5)).clamp(18, 100) as u8;
+        let name = format!(
+            "{} {}",
+            profile::first_name(&member_gender(rng), locale, rng),
+            surname
+        );
+        add(Relation::Partner, name, partner_age);
+    }
+    if matches!(
+        kind,
+        HouseholdKind::PartnerAndChildren | HouseholdKind::SingleParent
+    ) {
+        let oldest = (age - MIN_PARENT_GAP).min(MAX_CHILD_AGE);
+        for _ in 0..rng.gen_range(1..=3) {
+            let name = format!(
+                "{} {}",
+                profile::first_name(&member_gender(rng), locale, rng),
+                surname
+            );
+            add(Relation::Child, name, rng.gen_range(0..=oldest));
+        }
+    }
+    if kind == HouseholdKind::WithParents {
+        for _ in 0..rng.gen_range(1..=2) {
+            let parent_age = age
+                .saturating_add(rng.gen_range(MIN_PARENT_GAP..=35))
+                .min(100);
+            let name = format!(
+                "{} {}",
+                profile::first_name(&member_gender(rng), locale, rng),
+                surname
+            );
+            add(Relation::Parent, name, parent_age);
+        }
+    }
+    if kind == HouseholdKind::Roommates {
+        for _ in 0..rng.gen_range(1..=3) {
+            let roommate_age = (age as i32 + rng.gen_range(-4..=4)).clamp(18, 100) as u8;
+            let name = format!(
+                "{} {}",
+                profile::first_name(&member_gender(rng), locale, rng),
+                profile::last_name(locale, rng)
+            );
+            add(Relation::Roommate, name, roommate_age);
+        }
+    }
+    members.sort_by_key(|m| std::cmp::Reverse(m.age));
+    Household { kind, members }
+}
+
+fn member_gender<R: Rng>(rng: &mut R) -> Gender {
+    if rng.gen_bool(0.04) {
+        Gender::NonBinary
+    } else if rng.gen_bool(0.5) {
+        Gender::Female
+    } else {
+        Gender::Male
+    }
+}
+
+/// Usually a place like the one the persona lives in now, in the same country
+fn hometown<R: Rng>(demographics: &Demographics, data: &Backstories, rng: &mut R) -> String {
+    let location = if rng.gen_bool(0.6) {
+        &demographics.location_type
+    } else {
+        LocationType::ALL
+            .choose(rng)
+            .expect("there are location types")
+    };
+    data.hometowns[&demographics.locale.country][location]
+        .choose(rng)
+        .cloned()
+        .unwrap_or_default()
+}
+
+fn hobbies<R: Rng>(profile: &Profile, data: &Backstories, rng: &mut R) -> Vec<Hobby> {
+    let taxonomy = Taxonomy::builtin();
+    let mut interests: Vec<_> = profile
+        .interests
+        .iter()
+        .filter(|i| !i.category.is_sensitive())
+        .collect();
+    interests.sort_by(|a, b| b.weight.total_cmp(&a.weight));
+
+    let mut hobbies = Vec::new();
+    for affinity in interests {
+        let interest = &affinity.category;
+        let own: Vec<&str> = profile
+            .subtopics
+            .iter()
+            .filter(|id| taxonomy.get(id).is_some_and(|s| &s.category == interest))
+            .map(String::as_str)
+            .collect();
+        let candidates: Vec<&str> = if own.is_empty() {
+            taxonomy.leaves(interest).map(|s| s.id.as_str()).collect()
+        } else {
+            own
+        };
+        let Some(&subtopic) = candidates.choose(rng) else {
+            continue;
+        };
+
+        // The subtopic's own hobbies, or else the nearest ancestor's
+        let mut id = Some(subtopic);
+        while let Some(current) = id {
+            if let Some(description) = data.hobbies.get(current).and_then(|h| h.choose(rng)) {
+                hobbies.push(Hobby {
+                    interest: interest.clone(),
+                    subtopic: Some(subtopic.to_string()),
+                    description: description.clone(),
+                });
+                break;
+            }
+            id = taxonomy.get(current).and_then(|s| s.parent.as_deref());
+        }
+    }
+    hobbies
+}
+
+/// Subject and possessive pronouns
+fn pronouns(gender: &Gender) -> (&'static str, &'static str) {
+    match gender {
+        Gender::Male => ("he", "his"),
+        Gender::Female => ("she", "her"),
+        Gender::NonBinary | Gender::PreferNotToSay => ("they", "their"),
+    }
+}
+
+fn number(n: usize) -> String {
+    match n {
+        2 => "two".to_string(),
+        3 => "three".to_string(),
+        n => n.to_string(),
+    }
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    chars
+        .next()
+        .map(|first| first.to_uppercase().chain(chars).collect())
+        .unwrap_or_default()
+}
+
+/// "a", "a and b", "a, b and c"
+fn join(items: &[&str]) -> String {
+    match items {
+        [] => String::new(),
+        [only] => only.to_string(),
+        [rest @ .., last] => format!("{} and {}", rest.join(", "), last),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::profile::ProfileGenerator;
+
+    #[test]
+    fn test_backstories_cover_every_occupation_and_subtopic() {
+        let data = Backstories::builtin();
+        for occupation in OccupationCategory::ALL.iter() {
+            let entries = &data.occupations[occupation];
+            assert!(!entries.is_empty());
+            assert!(entries.iter().all(|o| !o.employers.is_empty()));
+        }
+        for band in AgeBand::ALL.iter() {
+            assert!(data.households.contains_key(band));
+        }
+        let taxonomy = Taxonomy::builtin();
+        for interest in InterestCategory::ALL.iter().filter(|i| !i.is_sensitive()) {
+            for leaf in taxonomy.leaves(interest) {
+                let mut id = Some(leaf.id.as_str());
+                while id.is_some_and(|id| !data.hobbies.contains_key(id)) {
+                    id = id
+                        .and_then(|id| taxonomy.get(id))
+                        .and_then(|s| s.parent.as_deref());
+                }
+                assert!(id.is_some(), "no hobby for {}", leaf.id);
+            }
+        }
+    }
+
+    #[test]
+    fn test_persona_is_stable_and_consistent() {
+        let mut gen = ProfileGenerator::new(Some(42));
+        for _ in 0..50 {
+            let profile = gen.generate();
+            let persona = Persona::from_profile(profile.clone());
+            assert_eq!(persona.bio, Persona::from_profile(profile.clone()).bio);
+
+            let age = profile.demographics.age;
+            for member in &persona.household.members {
+                match member.relation {
+                    Relation::Child => assert!(member.age + MIN_PARENT_GAP <= age),
+                    Relation::Parent => assert!(member.age >= age + MIN_PARENT_GAP),
+                    _ => {}
+                }
+            }
+            assert_eq!(persona.hobbies.len(), profile.interests.len());
+            assert!(persona.bio.starts_with(persona.first_name()));
+        }
+
+        // Deserialised profiles aren't validated, so any age can come in
+        let mut profile = gen.generate();
+        profile.demographics.age = u8::MAX;
+        let persona = Persona::from_profile(profile);
+        assert_ne!(persona.household.kind, HouseholdKind::WithParents);
+    }
+}
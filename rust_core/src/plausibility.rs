@@ -0,0 +1,124 @@
+use crate::interest_model::InterestModel;
+use crate::locale::Country;
+use crate::priors::DemographicPriors;
+use crate::profile::{Profile, ProfileGenerator};
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+
+/// Profiles drawn to rank a log-likelihood against
+const REFERENCE_SIZE: usize = 1000;
+
+/// Fixed so that scores are the same from one run to the next
+const REFERENCE_SEED: u64 = 0x0d0b_1e7a_ac4e_5eed;
+
+/// How typical a profile is of the ones the generator draws
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Plausibility {
+    /// Log-probability of the profile's demographics, interests, browsing
+    /// style and activity level; negative infinity if it can't be drawn
+    pub log_likelihood: f64,
+    /// Share of generated profiles that are no more likely than this one,
+    /// from 0 to 100
+    pub percentile: f64,
+}
+
+/// Sorted log-likelihoods of profiles drawn from one set of priors and model
+pub(crate) struct Reference {
+    log_likelihoods: Vec<f64>,
+}
+
+impl Reference {
+    /// The reference for a country's bundled priors and the built-in model,
+    /// or for the default priors where the country has none
+    pub(crate) fn for_country(country: Country) -> &'static Reference {
+        static REFERENCES: [OnceLock<Reference>; Country::ALL.len()] =
+            [const { OnceLock::new() }; Country::ALL.len()];
+        let index = Country::ALL
+            .iter()
+            .position(|c| *c == country)
+            .expect("every country is in ALL");
+        REFERENCES[index]
+            .get_or_init(|| Reference::sample(priors_for(country), InterestModel::builtin()))
+    }
+
+    pub(crate) fn sample(priors: &DemographicPriors, model: &InterestModel) -> Reference {
+        let mut generator = ProfileGenerator::new(Some(REFERENCE_SEED))
+            .with_priors(priors.clone())
+            .with_model(model.clone());
+        let mut log_likelihoods: Vec<f64> = (0..REFERENCE_SIZE)
+            .map(|_| {
+                let profile = generator.generate();
+                generator.log_probability(&profile)
+            })
+            .collect();
+        log_likelihoods.sort_by(f64::total_cmp);
+        Reference { log_likelihoods }
+    }
+
+    pub(crate) fn score(&self, log_likelihood: f64) -> Plausibility {
+        let below = self
+            .log_likelihoods
+            .partition_point(|&reference| reference <= log_likelihood);
+        Plausibility {
+            log_likelihood,
+            percentile: 100.0 * below as f64 / self.log_likelihoods.len() as f64,
+        }
+    }
+}
+
+/// Bundled priors for a country, or the default ones
+pub(crate) fn priors_for(country: Country) -> &'static DemographicPriors {
+    DemographicPriors::for_region(country.code()).unwrap_or_else(DemographicPriors::builtin)
+}
+
+/// How typical `profile` is under its country's priors and the built-in model
+pub(crate) fn builtin(profile: &Profile) -> Plausibility {
+    let country = profile.demographics.locale.country;
+    let log_likelihood = priors_for(country).log_probability(&profile.demographics)
+        + InterestModel::builtin().log_probability(profile);
+    Reference::for_country(country).score(log_likelihood)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::profile::OccupationCategory;
+
+    #[test]
+    fn test_generated_profiles_spread_over_percentiles() {
+        let mut generator = ProfileGenerator::new(Some(42));
+        let percentiles: Vec<f64> = (0..200)
+            .map(|_| generator.generate().plausibility().percentile)
+            .collect();
+        let mean = percentiles.iter().sum::<f64>() / percentiles.len() as f64;
+        assert!((35.0..65.0).contains(&mean), "mean percentile {}", mean);
+        assert!(percentiles.iter().all(|p| (0.0..=100.0).contains(p)));
+    }
+
+    #[test]
+    fn test_impossible_profile_scores_zero() {
+        let mut profile = ProfileGenerator::new(Some(42)).generate();
+        profile.demographics.age = 19;
+        profile.demographics.occupation_category = OccupationCategory::Retired;
+
+        let plausibility = profile.plausibility();
+        assert!(plausibility.log_likelihood < -20.0 || plausibility.log_likelihood.is_infinite());
+        assert!(plausibility.percentile < 5.0);
+    }
+
+    #[test]
+    fn test_profiles_are_scored_against_their_own_country() {
+        let german = crate::Locale::parse("de-DE").unwrap();
+        let mut generator = ProfileGenerator::new(Some(7)).with_locale(german);
+        let profiles: Vec<Profile> = (0..100).map(|_| generator.generate()).collect();
+        for profile in &profiles {
+            assert_eq!(profile.plausibility(), generator.plausibility(profile));
+        }
+        let mean = profiles
+            .iter()
+            .map(|p| p.plausibility().percentile)
+            .sum::<f64>()
+            / 100.0;
+        assert!((35.0..65.0).contains(&mean), "mean percentile {}", mean);
+    }
+}
@@ -0,0 +1,625 @@
+use crate::locale::Locale;
+use crate::profile::{Demographics, EducationLevel, Gender, LocationType, OccupationCategory};
+use crate::spec::{self, ProfileSpec};
+use rand::seq::SliceRandom;
+use rand::Rng;
+use serde::de::Error as _;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::Hash;
+use std::ops::RangeInclusive;
+use std::sync::OnceLock;
+
+/// Built-in priors, one entry per region; the first is the default
+const BUILTIN_PRIORS: &str = include_str!("../data/demographics.json");
+
+/// Passes over the margins before fitting gives up
+const MAX_FIT_ROUNDS: usize = 500;
+
+/// Largest gap between a margin and its target that counts as fitted
+const FIT_TOLERANCE: f64 = 1e-6;
+
+#[derive(Debug, Deserialize)]
+struct RawPriors {
+    region: String,
+    /// `[min, max, share]` for each bracket, youngest first
+    age_brackets: Vec<(u8, u8, f64)>,
+    gender: HashMap<Gender, f64>,
+    location: HashMap<LocationType, f64>,
+    /// Education shares within each age bracket
+    education: Vec<HashMap<EducationLevel, f64>>,
+    /// Occupation shares within each age bracket
+    occupation: Vec<HashMap<OccupationCategory, f64>>,
+    /// How much more or less likely each education is for an occupation
+    /// than for the age bracket as a whole; missing entries count as 1
+    #[serde(default)]
+    occupation_education: HashMap<OccupationCategory, HashMap<EducationLevel, f64>>,
+}
+
+/// One combination of demographic values, as indices into each `ALL`
+#[derive(Debug, Clone)]
+struct Cell {
+    bracket: usize,
+    gender: usize,
+    location: usize,
+    education: usize,
+    occupation: usize,
+    weight: f64,
+}
+
+/// A set of cells that a target distribution is fitted over
+#[derive(Debug, Clone, Copy)]
+enum Margin {
+    Age,
+    Gender,
+    Location,
+    Education,
+    Occupation,
+    AgeEducation,
+    AgeOccupation,
+}
+
+impl Cell {
+    fn index(&self, margin: Margin) -> usize {
+        match margin {
+            Margin::Age => self.bracket,
+            Margin::Gender => self.gender,
+            Margin::Location => self.location,
+            Margin::Education => self.education,
+            Margin::Occupation => self.occupation,
+            Margin::AgeEducation => self.bracket * EducationLevel::ALL.len() + self.education,
+            Margin::AgeOccupation => self.bracket * OccupationCategory::ALL.len() + self.occupation,
+        }
+    }
+}
+
+/// One-way shares of each demographic field
+///
+/// Used both to read a prior's distribution and to calibrate it. Values
+/// left out of a supplied map get no share at all.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Marginals {
+    /// Shares per bracket, in the order of [`DemographicPriors::age_brackets`]
+    pub age: Option<Vec<f64>>,
+    pub gender: Option<HashMap<Gender, f64>>,
+    pub location: Option<HashMap<LocationType, f64>>,
+    pub education: Option<HashMap<EducationLevel, f64>>,
+    pub occupation: Option<HashMap<OccupationCategory, f64>>,
+}
+
+/// Joint distribution over age bracket, gender, location, education and
+/// occupation for one region
+///
+/// Regions are described in `data/demographics.json` by an age distribution,
+/// gender and location shares, education and occupation shares within each
+/// age bracket, and multipliers tying occupation to education. The joint
+/// table is fitted to all of those by iterative proportional fitting, and
+/// can be refitted to a caller's own marginals with [`calibrate`](Self::calibrate).
+/// Ages are uniform within a bracket.
+#[derive(Debug, Clone)]
+pub struct DemographicPriors {
+    region: String,
+    brackets: Vec<RangeInclusive<u8>>,
+    cells: Vec<Cell>,
+}
+
+impl DemographicPriors {
+    /// The default region's priors
+    pub fn builtin() -> &'static DemographicPriors {
+        &Self::bundled()[0]
+    }
+
+    /// Bundled priors for a region code such as `US` or `GB`
+    pub fn for_region(region: &str) -> Option<&'static DemographicPriors> {
+        Self::bundled()
+            .iter()
+            .find(|priors| priors.region.eq_ignore_ascii_case(region))
+    }
+
+    fn bundled() -> &'static [DemographicPriors] {
+        static PRIORS: OnceLock<Vec<DemographicPriors>> = OnceLock::new();
+        PRIORS.get_or_init(|| {
+            DemographicPriors::from_json(BUILTIN_PRIORS).expect("built-in priors are valid")
+        })
+    }
+
+    /// Load regions from JSON in the same shape as `data/demographics.json`
+    pub fn from_json(json: &str) -> Result<Vec<Self>, serde_json::Error> {
+        let raw: Vec<RawPriors> = serde_json::from_str(json)?;
+        if raw.is_empty() {
+            return Err(serde_json::Error::custom("no regions"));
+        }
+        raw.into_iter()
+            .map(|region| {
+                let name = region.region.clone();
+                Self::from_raw(region)
+                    .map_err(|e| serde_json::Error::custom(format!("{}: {}", name, e)))
+            })
+            .collect()
+    }
+
+    fn from_raw(raw: RawPriors) -> Result<Self, String> {
+        let mut next = *spec::VALID_AGES.start();
+        for &(min, max, _) in &raw.age_brackets {
+            if min != next || max < min {
+                return Err(format!(
+                    "age bracket {}-{} should start at {}",
+                    min, max, next
+                ));
+            }
+            next = max.saturating_add(1);
+        }
+        if next <= *spec::VALID_AGES.end() {
+            return Err(format!(
+                "age brackets stop short of {}",
+                spec::VALID_AGES.end()
+            ));
+        }
+        let brackets = raw.age_brackets.len();
+        if raw.education.len() != brackets || raw.occupation.len() != brackets {
+            return Err("education and occupation need one table per age bracket".to_string());
+        }
+
+        let age = normalize(raw.age_brackets.iter().map(|b| b.2).collect())
+            .ok_or("invalid age shares")?;
+        let gender = shares(&raw.gender, &Gender::ALL).ok_or("invalid gender shares")?;
+        let location =
+            shares(&raw.location, &LocationType::ALL).ok_or("invalid location shares")?;
+        let education = raw
+            .education
+            .iter()
+            .map(|table| shares(table, &EducationLevel::ALL))
+            .collect::<Option<Vec<_>>>()
+            .ok_or("invalid education shares")?;
+        let occupation = raw
+            .occupation
+            .iter()
+            .map(|table| shares(table, &OccupationCategory::ALL))
+            .collect::<Option<Vec<_>>>()
+            .ok_or("invalid occupation shares")?;
+        let affinity = |o: usize, e: usize| {
+            raw.occupation_education
+                .get(&OccupationCategory::ALL[o])
+                .and_then(|row| row.get(&EducationLevel::ALL[e]))
+                .copied()
+                .unwrap_or(1.0)
+                .max(0.0)
+        };
+
+        let mut cells = Vec::new();
+        for (b, age_share) in age.iter().enumerate() {
+            for (g, gender_share) in gender.iter().enumerate() {
+                for (l, location_share) in location.iter().enumerate() {
+                    for (e, education_share) in education[b].iter().enumerate() {
+                        for (o, occupation_share) in occupation[b].iter().enumerate() {
+                            cells.push(Cell {
+                                bracket: b,
+                                gender: g,
+                                location: l,
+                                education: e,
+                                occupation: o,
+                                weight: age_share
+                                    * gender_share
+                                    * location_share
+                                    * education_share
+                                    * occupation_share
+                                    * affinity(o, e),
+                            });
+                        }
+                    }
+                }
+            }
+        }
+
+        // The multipliers skew education and occupation within each bracket;
+        // fit back to the tables so only their association changes
+        let by_age = |table: &[Vec<f64>]| -> Vec<f64> {
+            table
+                .iter()
+                .zip(&age)
+                .flat_map(|(row, share)| row.iter().map(move |s| s * share))
+                .collect()
+        };
+        let targets = [
+            (Margin::AgeEducation, by_age(&education)),
+            (Margin::AgeOccupation, by_age(&occupation)),
+        ];
+        if !fit(&mut cells, &targets) {
+            return Err("occupation_education can't be reconciled with the tables".to_string());
+        }
+
+        Ok(DemographicPriors {
+            region: raw.region,
+            brackets: raw
+                .age_brackets
+                .iter()
+                .map(|&(min, max, _)| min..=max)
+                .collect(),
+            cells,
+        })
+    }
+
+    pub fn region(&self) -> &str {
+        &self.region
+    }
+
+    /// Where people drawn from these priors live: the region's country
+    /// with its usual language, or the default locale for regions without
+    /// bundled names and domains
+    pub fn locale(&self) -> Locale {
+        Locale::parse(&self.region).unwrap_or_default()
+    }
+
+    pub fn age_brackets(&self) -> &[RangeInclusive<u8>] {
+        &self.brackets
+    }
+
+    /// Current share of every value of every field
+    pub fn marginals(&self) -> Marginals {
+        Marginals {
+            age: Some(margin(&self.cells, Margin::Age, self.brackets.len())),
+            gender: Some(labelled(&self.cells, Margin::Gender, &Gender::ALL)),
+            location: Some(labelled(&self.cells, Margin::Location, &LocationType::ALL)),
+            education: Some(labelled(
+                &self.cells,
+                Margin::Education,
+                &EducationLevel::ALL,
+            )),
+            occupation: Some(labelled(
+                &self.cells,
+                Margin::Occupation,
+                &OccupationCategory::ALL,
+            )),
+        }
+    }
+
+    /// Refit the joint table to match `targets` by iterative proportional
+    /// fitting
+    ///
+    /// Fields left as `None` are held at their current shares. Associations
+    /// between fields, such as students being young, are kept as far as the
+    /// targets allow. On error the priors are left as they were.
+    pub fn calibrate(&mut self, targets: &Marginals) -> Result<(), CalibrationError> {
+        let current = self.marginals();
+        let mut margins = Vec::new();
+
+        let age = targets
+            .age
+            .as_ref()
+            .or(current.age.as_ref())
+            .expect("marginals are complete");
+        if age.len() != self.brackets.len() {
+            return Err(CalibrationError::AgeBrackets {
+                expected: self.brackets.len(),
+                got: age.len(),
+            });
+        }
+        let age = normalize(age.clone()).ok_or(CalibrationError::Empty("age"))?;
+        margins.push((Margin::Age, age));
+
+        let gender = targets.gender.as_ref().or(current.gender.as_ref());
+        let gender = gender.and_then(|g| shares(g, &Gender::ALL));
+        margins.push((
+            Margin::Gender,
+            gender.ok_or(CalibrationError::Empty("gender"))?,
+        ));
+
+        let location = targets.location.as_ref().or(current.location.as_ref());
+        let location = location.and_then(|l| shares(l, &LocationType::ALL));
+        margins.push((
+            Margin::Location,
+            location.ok_or(CalibrationError::Empty("location"))?,
+        ));
+
+        let education = targets.education.as_ref().or(current.education.as_ref());
+        let education = education.and_then(|e| shares(e, &EducationLevel::ALL));
+        margins.push((
+            Margin::Education,
+            education.ok_or(CalibrationError::Empty("education"))?,
+        ));
+
+        let occupation = targets.occupation.as_ref().or(current.occupation.as_ref());
+        let occupation = occupation.and_then(|o| shares(o, &OccupationCategory::ALL));
+        margins.push((
+            Margin::Occupation,
+            occupation.ok_or(CalibrationError::Empty("occupation"))?,
+        ));
+
+        for (margin_kind, target) in &margins {
+            let current = margin(&self.cells, *margin_kind, target.len());
+            if let Some(i) = (0..target.len()).find(|&i| target[i] > 0.0 && current[i] <= 0.0) {
+                return Err(CalibrationError::Unreachable(
+                    self.describe(*margin_kind, i),
+                ));
+            }
+        }
+
+        let mut cells = self.cells.clone();
+        if !fit(&mut cells, &margins) {
+            return Err(CalibrationError::NotConverged);
+        }
+        self.cells = cells;
+        Ok(())
+    }
+
+    fn describe(&self, margin: Margin, index: usize) -> String {
+        match margin {
+            Margin::Age => {
+                let bracket = &self.brackets[index];
+                format!("ages {}-{}", bracket.start(), bracket.end())
+            }
+            Margin::Gender => format!("{:?}", Gender::ALL[index]),
+            Margin::Location => format!("{:?}", LocationType::ALL[index]),
+            Margin::Education => format!("{:?}", EducationLevel::ALL[index]),
+            Margin::Occupation => format!("{:?}", OccupationCategory::ALL[index]),
+            Margin::AgeEducation | Margin::AgeOccupation => format!("cell {}", index),
+        }
+    }
+
+    /// Log-probability of exactly these demographics, ages being uniform
+    /// within their bracket
+    pub fn log_probability(&self, demographics: &Demographics) -> f64 {
+        let bracket = self
+            .brackets
+            .iter()
+            .position(|b| b.contains(&demographics.age));
+        let (Some(bracket), Some(gender), Some(location), Some(education), Some(occupation)) = (
+            bracket,
+            Gender::ALL.iter().position(|g| g == &demographics.gender),
+            LocationType::ALL
+                .iter()
+                .position(|l| l == &demographics.location_type),
+            EducationLevel::ALL
+                .iter()
+                .position(|e| e == &demographics.education_level),
+            OccupationCategory::ALL
+                .iter()
+                .position(|o| o == &demographics.occupation_category),
+        ) else {
+            return f64::NEG_INFINITY;
+        };
+
+        self.cells
+            .iter()
+            .find(|c| {
+                c.bracket == bracket
+                    && c.gender == gender
+                    && c.location == location
+                    && c.education == education
+                    && c.occupation == occupation
+            })
+            .map_or(f64::NEG_INFINITY, |cell| {
+                (cell.weight / span(&self.brackets[bracket])).ln()
+            })
+    }
+
+    /// Draw demographics consistent with `spec`, within `ages`
+    ///
+    /// Pinned fields rule out every cell that disagrees with them, and each
+    /// remaining cell is weighted by how much of its age bracket is still
+    /// feasible for its occupation and education.
+    pub(crate) fn sample<R: Rng>(
+        &self,
+        spec: &ProfileSpec,
+        ages: &RangeInclusive<u8>,
+        rng: &mut R,
+    ) -> Demographics {
+        let candidates: Vec<(&Cell, RangeInclusive<u8>, f64)> = self
+            .cells
+            .iter()
+            .filter_map(|cell| {
+                let occupation = &OccupationCategory::ALL[cell.occupation];
+                let education = &EducationLevel::ALL[cell.education];
+                if spec
+                    .gender
+                    .as_ref()
+                    .is_some_and(|g| g != &Gender::ALL[cell.gender])
+                    || spec
+                        .location_type
+                        .as_ref()
+                        .is_some_and(|l| l != &LocationType::ALL[cell.location])
+                    || spec.occupation.as_ref().is_some_and(|o| o != occupation)
+                    || spec
+                        .education_level
+                        .as_ref()
+                        .is_some_and(|e| e != education)
+                {
+                    return None;
+                }
+
+                let bracket = &self.brackets[cell.bracket];
+                let feasible = spec::narrow(bracket, ages)
+                    .and_then(|a| spec::narrow(&a, &spec::occupation_ages(occupation)))
+                    .and_then(|a| {
+                        spec::narrow(&a, &(spec::education_min_age(education)..=u8::MAX))
+                    })?;
+                let share = span(&feasible) / span(bracket);
+                Some((cell, feasible, cell.weight * share))
+            })
+            .collect();
+
+        let (cell, feasible, _) = match candidates.choose_weighted(rng, |c| c.2) {
+            Ok(chosen) => chosen,
+            // Every consistent cell has zero weight: the spec asks for
+            // something the priors say never happens, so ignore them
+            Err(_) => candidates
+                .choose(rng)
+                .expect("a validated spec leaves at least one cell"),
+        };
+
+        Demographics {
+            age: rng.gen_range(feasible.clone()),
+            gender: Gender::ALL[cell.gender].clone(),
+            location_type: LocationType::ALL[cell.location].clone(),
+            occupation_category: OccupationCategory::ALL[cell.occupation].clone(),
+            education_level: EducationLevel::ALL[cell.education].clone(),
+            locale: self.locale(),
+        }
+    }
+}
+
+/// Why priors couldn't be calibrated
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CalibrationError {
+    /// The age targets don't have one share per bracket
+    AgeBrackets { expected: usize, got: usize },
+    /// Every share of a field is zero
+    Empty(&'static str),
+    /// A value is given a share but no cell can have it
+    Unreachable(String),
+    /// The targets contradict each other
+    NotConverged,
+}
+
+impl fmt::Display for CalibrationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CalibrationError::AgeBrackets { expected, got } => {
+                write!(f, "expected {} age shares, got {}", expected, got)
+            }
+            CalibrationError::Empty(field) => write!(f, "{} shares are all zero", field),
+            CalibrationError::Unreachable(value) => {
+                write!(f, "{} has a share but the priors rule it out", value)
+            }
+            CalibrationError::NotConverged => write!(f, "targets are inconsistent"),
+        }
+    }
+}
+
+impl std::error::Error for CalibrationError {}
+
+/// Scale `cells` until every margin matches its target, alternating between
+/// them; false if they never all agree
+fn fit(cells: &mut [Cell], margins: &[(Margin, Vec<f64>)]) -> bool {
+    for _ in 0..MAX_FIT_ROUNDS {
+        let mut worst: f64 = 0.0;
+        for (kind, target) in margins {
+            let current = margin(cells, *kind, target.len());
+            for (c, t) in current.iter().zip(target) {
+                worst = worst.max((c - t).abs());
+            }
+            for cell in cells.iter_mut() {
+                let i = cell.index(*kind);
+                cell.weight = if current[i] > 0.0 {
+                    cell.weight * target[i] / current[i]
+                } else {
+                    0.0
+                };
+            }
+        }
+        if worst < FIT_TOLERANCE {
+            return true;
+        }
+    }
+    false
+}
+
+fn margin(cells: &[Cell], kind: Margin, len: usize) -> Vec<f64> {
+    let mut sums = vec![0.0; len];
+    for cell in cells {
+        sums[cell.index(kind)] += cell.weight;
+    }
+    sums
+}
+
+fn labelled<T: Clone + Eq + Hash>(cells: &[Cell], kind: Margin, all: &[T]) -> HashMap<T, f64> {
+    all.iter()
+        .cloned()
+        .zip(margin(cells, kind, all.len()))
+        .collect()
+}
+
+/// Normalised shares in `all` order; values missing from the map get none
+fn shares<T: Eq + Hash>(map: &HashMap<T, f64>, all: &[T]) -> Option<Vec<f64>> {
+    normalize(
+        all.iter()
+            .map(|v| map.get(v).copied().unwrap_or(0.0))
+            .collect(),
+    )
+}
+
+fn normalize(mut shares: Vec<f64>) -> Option<Vec<f64>> {
+    if shares.iter().any(|s| !s.is_finite() || *s < 0.0) {
+        return None;
+    }
+    let total: f64 = shares.iter().sum();
+    if total <= 0.0 {
+        return None;
+    }
+    shares.iter_mut().for_each(|s| *s /= total);
+    Some(shares)
+}
+
+fn span(ages: &RangeInclusive<u8>) -> f64 {
+    (*ages.end() as f64) - (*ages.start() as f64) + 1.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_priors_match_their_tables() {
+        let us = DemographicPriors::for_region("us").unwrap();
+        assert_eq!(DemographicPriors::builtin().region(), "US");
+        assert!(DemographicPriors::for_region("GB").is_some());
+        assert!(DemographicPriors::for_region("XX").is_none());
+
+        let marginals = us.marginals();
+        let location = marginals.location.unwrap();
+        assert!((location[&LocationType::Urban] - 0.31).abs() < 1e-4);
+        assert!((marginals.age.unwrap()[0] - 0.12).abs() < 1e-4);
+
+        // The multipliers tie education to occupation without moving the
+        // per-bracket tables
+        let share = |occupation: usize, education: usize| -> f64 {
+            let cells = us.cells.iter().filter(|c| c.occupation == occupation);
+            let total: f64 = cells.clone().map(|c| c.weight).sum();
+            cells
+                .filter(|c| c.education == education)
+                .map(|c| c.weight)
+                .sum::<f64>()
+                / total
+        };
+        let (technology, trades, bachelor) = (0, 6, 2);
+        assert!(share(technology, bachelor) > 2.0 * share(trades, bachelor));
+    }
+
+    #[test]
+    fn test_calibrate_to_marginals() {
+        let mut priors = DemographicPriors::builtin().clone();
+        let targets = Marginals {
+            location: Some(HashMap::from([
+                (LocationType::Urban, 0.6),
+                (LocationType::Suburban, 0.3),
+                (LocationType::Rural, 0.1),
+            ])),
+            occupation: Some(
+                OccupationCategory::ALL
+                    .iter()
+                    .map(|o| (o.clone(), 1.0))
+                    .collect(),
+            ),
+            ..Marginals::default()
+        };
+        priors.calibrate(&targets).unwrap();
+
+        let marginals = priors.marginals();
+        assert!((marginals.location.unwrap()[&LocationType::Urban] - 0.6).abs() < 1e-4);
+        let occupation = marginals.occupation.unwrap();
+        assert!((occupation[&OccupationCategory::Student] - 1.0 / 9.0).abs() < 1e-4);
+
+        assert_eq!(
+            priors.calibrate(&Marginals {
+                age: Some(vec![1.0; 3]),
+                ..Marginals::default()
+            }),
+            Err(CalibrationError::AgeBrackets {
+                expected: 7,
+                got: 3
+            })
+        );
+    }
+}
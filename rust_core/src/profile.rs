@@ -1,29 +1,192 @@
-use serde::{Deserialize, Serialize};
-use rand::{Rng, SeedableRng};
-use rand::rngs::SmallRng;
+use crate::contrast::{AgeBand, ProfileSummary};
+use crate::interest_model::InterestModel;
+use crate::locale::Locale;
+use crate::migration::{self, MigrationError, SCHEMA_VERSION};
+use crate::plausibility::{self, Plausibility, Reference};
+use crate::priors::DemographicPriors;
+use crate::rng::LiveRng;
+use crate::spec::{ProfileSpec, SpecError};
+use crate::taxonomy::{Subtopic, Taxonomy};
+use crate::validation::{self, Severity, ValidationIssue};
 use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use rand_distr::{Distribution, Gamma};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::borrow::Cow;
+use std::ops::RangeInclusive;
+use std::sync::OnceLock;
+
+/// Personas drawn by [`ProfileGenerator::generate_contrasting`] before
+/// keeping the most distant
+const CONTRAST_CANDIDATES: usize = 8;
+
+/// Most candidates [`ProfileGenerator::generate_population`] draws per slot,
+/// reached at full diversity
+const POPULATION_CANDIDATES: usize = 16;
+
+/// Profiles [`ProfileGenerator::generate_with`] draws before settling for
+/// the most plausible one when none reaches the minimum
+const PLAUSIBILITY_ATTEMPTS: usize = 16;
 
 /// A fictional browsing profile
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Profile {
+    /// Shape of the JSON the profile was written as; 0 predates versioning
+    ///
+    /// Stored profiles should be loaded with [`Profile::migrate`], which
+    /// upgrades them to [`SCHEMA_VERSION`](crate::SCHEMA_VERSION).
+    #[serde(default)]
+    pub schema_version: u32,
     pub id: String,
     pub name: String,
     pub demographics: Demographics,
-    pub interests: Vec<InterestCategory>,
+    /// Interests with how much of the profile's time each one takes
+    ///
+    /// Older profiles stored bare categories; those load with equal weights.
+    #[serde(deserialize_with = "deserialize_interests")]
+    pub interests: Vec<InterestAffinity>,
+    /// Ids of specific [`Subtopic`](crate::Subtopic)s within `interests`
+    #[serde(default)]
+    pub subtopics: Vec<String>,
     pub browsing_style: BrowsingStyle,
     pub activity_level: ActivityLevel,
     pub created_at: i64,
 }
 
 impl Profile {
+    /// Load profile JSON written by this or any earlier version
+    pub fn migrate(json: serde_json::Value) -> Result<Profile, MigrationError> {
+        migration::migrate(json)
+    }
+
     /// Check if the profile is internally consistent
+    ///
+    /// Implausible but usable profiles pass; see [`validate`](Self::validate)
+    /// for what is wrong with one that doesn't.
     pub fn is_valid(&self) -> bool {
-        !self.name.is_empty()
-            && !self.interests.is_empty()
-            && self.interests.len() <= 10
-            && self.demographics.age >= 18
-            && self.demographics.age <= 100
+        validation::check(self)
+            .iter()
+            .all(|issue| issue.severity < Severity::Error)
+    }
+
+    /// Every problem with the profile, from outright errors to combinations
+    /// that are merely implausible, such as a retired 20-year-old
+    pub fn validate(&self) -> Result<(), Vec<ValidationIssue>> {
+        let issues = validation::check(self);
+        if issues.is_empty() {
+            Ok(())
+        } else {
+            Err(issues)
+        }
+    }
+
+    /// How typical the profile is under its country's bundled priors and
+    /// the built-in model
+    ///
+    /// Nothing here is wrong as such, unlike [`validate`](Self::validate):
+    /// a low percentile means a persona stands out from the crowd it is
+    /// meant to hide in, which makes it easier to dismiss as noise. See
+    /// [`ProfileGenerator::plausibility`] for other priors.
+    pub fn plausibility(&self) -> Plausibility {
+        plausibility::builtin(self)
+    }
+
+    /// The profile's interest categories, in order
+    pub fn categories(&self) -> impl Iterator<Item = &InterestCategory> {
+        self.interests.iter().map(|interest| &interest.category)
+    }
+
+    pub fn has_interest(&self, category: &InterestCategory) -> bool {
+        self.categories().any(|c| c == category)
+    }
+
+    /// Share of time spent on `category`, or 0 if it isn't an interest
+    pub fn affinity(&self, category: &InterestCategory) -> f64 {
+        let total: f64 = self.interests.iter().map(|i| i.weight).sum();
+        if total <= 0.0 {
+            return 0.0;
+        }
+        self.interests
+            .iter()
+            .filter(|i| &i.category == category)
+            .map(|i| i.weight)
+            .sum::<f64>()
+            / total
     }
+
+    /// How different two profiles are, from 0 (alike in every respect) to 1
+    ///
+    /// Age band, occupation, browsing style and interests count equally.
+    /// Interests are compared by how much affinity the two profiles share.
+    pub fn distance(&self, other: &Profile) -> f64 {
+        let age = AgeBand::of(self.demographics.age).distance(&AgeBand::of(other.demographics.age));
+        let occupation = differs(
+            &self.demographics.occupation_category,
+            &other.demographics.occupation_category,
+        );
+        let style = differs(&self.browsing_style, &other.browsing_style);
+        let shared: f64 = self
+            .categories()
+            .map(|category| self.affinity(category).min(other.affinity(category)))
+            .sum();
+
+        (age + occupation + style + (1.0 - shared.min(1.0))) / 4.0
+    }
+}
+
+fn differs<T: PartialEq>(a: &T, b: &T) -> f64 {
+    if a == b {
+        0.0
+    } else {
+        1.0
+    }
+}
+
+/// How strongly a profile is drawn to one interest
+///
+/// Weights are relative; generated profiles normalise them to sum to 1.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InterestAffinity {
+    pub category: InterestCategory,
+    pub weight: f64,
+}
+
+impl InterestAffinity {
+    /// Equal weights, for interests that came without any
+    pub fn uniform(categories: impl IntoIterator<Item = InterestCategory>) -> Vec<Self> {
+        let categories: Vec<InterestCategory> = categories.into_iter().collect();
+        let weight = 1.0 / categories.len().max(1) as f64;
+        categories
+            .into_iter()
+            .map(|category| InterestAffinity { category, weight })
+            .collect()
+    }
+}
+
+/// Accept both `{"category": ..., "weight": ...}` and the older bare category
+fn deserialize_interests<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Vec<InterestAffinity>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Entry {
+        Weighted(InterestAffinity),
+        Bare(InterestCategory),
+    }
+
+    let entries = Vec::<Entry>::deserialize(deserializer)?;
+    let even = 1.0 / entries.len().max(1) as f64;
+    Ok(entries
+        .into_iter()
+        .map(|entry| match entry {
+            Entry::Weighted(affinity) => affinity,
+            Entry::Bare(category) => InterestAffinity {
+                category,
+                weight: even,
+            },
+        })
+        .collect())
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -33,9 +196,12 @@ pub struct Demographics {
     pub location_type: LocationType,
     pub occupation_category: OccupationCategory,
     pub education_level: EducationLevel,
+    /// Where the persona lives and the language it browses in
+    #[serde(default)]
+    pub locale: Locale,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Gender {
     Male,
     Female,
@@ -43,14 +209,31 @@ pub enum Gender {
     PreferNotToSay,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl Gender {
+    pub const ALL: [Gender; 4] = [
+        Gender::Male,
+        Gender::Female,
+        Gender::NonBinary,
+        Gender::PreferNotToSay,
+    ];
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum LocationType {
     Urban,
     Suburban,
     Rural,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl LocationType {
+    pub const ALL: [LocationType; 3] = [
+        LocationType::Urban,
+        LocationType::Suburban,
+        LocationType::Rural,
+    ];
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum OccupationCategory {
     Technology,
     Healthcare,
@@ -63,7 +246,21 @@ pub enum OccupationCategory {
     Student,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl OccupationCategory {
+    pub const ALL: [OccupationCategory; 9] = [
+        OccupationCategory::Technology,
+        OccupationCategory::Healthcare,
+        OccupationCategory::Education,
+        OccupationCategory::Finance,
+        OccupationCategory::Creative,
+        OccupationCategory::Service,
+        OccupationCategory::Trades,
+        OccupationCategory::Retired,
+        OccupationCategory::Student,
+    ];
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum EducationLevel {
     HighSchool,
     SomeCollege,
@@ -72,7 +269,17 @@ pub enum EducationLevel {
     Doctorate,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl EducationLevel {
+    pub const ALL: [EducationLevel; 5] = [
+        EducationLevel::HighSchool,
+        EducationLevel::SomeCollege,
+        EducationLevel::Bachelor,
+        EducationLevel::Master,
+        EducationLevel::Doctorate,
+    ];
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum InterestCategory {
     Technology,
     Gaming,
@@ -94,9 +301,57 @@ pub enum InterestCategory {
     Photography,
     Programming,
     DataScience,
+    // Sensitive topics: never part of a generated profile, only used as
+    // opt-in cover traffic
+    HealthConditions,
+    MentalHealth,
+    LegalAdvice,
+    Immigration,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl InterestCategory {
+    /// Every category, in declaration order
+    pub const ALL: [InterestCategory; 24] = [
+        InterestCategory::Technology,
+        InterestCategory::Gaming,
+        InterestCategory::Sports,
+        InterestCategory::Fitness,
+        InterestCategory::Cooking,
+        InterestCategory::Travel,
+        InterestCategory::Fashion,
+        InterestCategory::Music,
+        InterestCategory::Movies,
+        InterestCategory::Books,
+        InterestCategory::Art,
+        InterestCategory::Science,
+        InterestCategory::Politics,
+        InterestCategory::News,
+        InterestCategory::Finance,
+        InterestCategory::HomeImprovement,
+        InterestCategory::Gardening,
+        InterestCategory::Photography,
+        InterestCategory::Programming,
+        InterestCategory::DataScience,
+        InterestCategory::HealthConditions,
+        InterestCategory::MentalHealth,
+        InterestCategory::LegalAdvice,
+        InterestCategory::Immigration,
+    ];
+
+    /// Topics people research privately and may want cover for
+    pub const SENSITIVE: [InterestCategory; 4] = [
+        InterestCategory::HealthConditions,
+        InterestCategory::MentalHealth,
+        InterestCategory::LegalAdvice,
+        InterestCategory::Immigration,
+    ];
+
+    pub fn is_sensitive(&self) -> bool {
+        Self::SENSITIVE.contains(self)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum BrowsingStyle {
     Focused,      // Few tabs, deep reading
     Explorer,     // Many tabs, broad browsing
@@ -104,7 +359,16 @@ pub enum BrowsingStyle {
     Casual,       // Mix of everything
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl BrowsingStyle {
+    pub const ALL: [BrowsingStyle; 4] = [
+        BrowsingStyle::Focused,
+        BrowsingStyle::Explorer,
+        BrowsingStyle::Researcher,
+        BrowsingStyle::Casual,
+    ];
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum ActivityLevel {
     Low,       // 10-30 activities per day
     Medium,    // 30-70 activities per day
@@ -112,264 +376,443 @@ pub enum ActivityLevel {
     VeryHigh,  // 150+ activities per day
 }
 
-pub struct ProfileGenerator {
-    rng: SmallRng,
+impl ActivityLevel {
+    pub const ALL: [ActivityLevel; 4] = [
+        ActivityLevel::Low,
+        ActivityLevel::Medium,
+        ActivityLevel::High,
+        ActivityLevel::VeryHigh,
+    ];
+}
+
+/// Algorithm a [`ProfileGenerator`] draws profiles with
+///
+/// A seed reproduces the same persona only under the same version. Once
+/// released, a version is frozen and covered by golden tests: any change to
+/// what a seed produces, whether in the sampling code or the bundled priors
+/// and model, belongs in a new version, with the old one kept as it was.
+/// Custom [priors](ProfileGenerator::with_priors) or a
+/// [model](ProfileGenerator::with_model) are outside that guarantee.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum GeneratorVersion {
+    /// ChaCha8 seeded through `seed_from_u64`, demographics from the
+    /// bundled priors, the rest from the bundled interest model
+    V1,
+}
+
+impl GeneratorVersion {
+    pub const ALL: [GeneratorVersion; 1] = [GeneratorVersion::V1];
+
+    /// What [`ProfileGenerator::new`] uses
+    pub const LATEST: GeneratorVersion = GeneratorVersion::V1;
+
+    /// The version numbered `n`, counting from 1
+    pub fn from_number(n: u32) -> Option<GeneratorVersion> {
+        GeneratorVersion::ALL
+            .get((n as usize).checked_sub(1)?)
+            .copied()
+    }
+
+    pub fn number(&self) -> u32 {
+        *self as u32 + 1
+    }
+}
+
+/// Draws profiles from `R`
+///
+/// The default, ChaCha8 seeded through [`new`](Self::new), is portable,
+/// unlike `SmallRng`, whose algorithm differs between 32- and 64-bit
+/// targets, so a seed gives the same persona in WASM as natively.
+pub struct ProfileGenerator<R = ChaCha8Rng> {
+    version: GeneratorVersion,
+    rng: R,
+    priors: Cow<'static, DemographicPriors>,
+    model: Cow<'static, InterestModel>,
+    min_plausibility: Option<f64>,
+    /// Overrides the locale the priors' region implies
+    locale: Option<Locale>,
+    /// Drawn on first use, for priors or a model other than the built-in ones
+    reference: OnceLock<Reference>,
 }
 
 impl ProfileGenerator {
     pub fn new(seed: Option<u64>) -> Self {
+        Self::new_v(seed, GeneratorVersion::LATEST)
+    }
+
+    /// A generator that draws profiles the way `version` did
+    pub fn new_v(seed: Option<u64>, version: GeneratorVersion) -> Self {
         let rng = if let Some(s) = seed {
-            SmallRng::seed_from_u64(s)
+            ChaCha8Rng::seed_from_u64(s)
         } else {
-            SmallRng::from_entropy()
+            ChaCha8Rng::from_entropy()
         };
 
-        Self { rng }
+        Self {
+            version,
+            ..Self::from_rng(rng)
+        }
     }
+}
 
-    pub fn generate(&mut self) -> Profile {
-        let demographics = self.generate_demographics();
-        let interests = self.generate_interests(&demographics);
-        let browsing_style = self.generate_browsing_style(&interests);
-        let activity_level = self.generate_activity_level(&demographics);
+impl ProfileGenerator<LiveRng> {
+    /// An unseeded generator whose draws can't be predicted from earlier ones
+    pub fn live() -> Self {
+        Self::from_rng(LiveRng::from_entropy())
+    }
+}
 
-        Profile {
-            id: self.generate_id(),
-            name: self.generate_name(&demographics),
-            demographics,
-            interests,
-            browsing_style,
-            activity_level,
-            created_at: chrono::Utc::now().timestamp(),
+impl<R: Rng> ProfileGenerator<R> {
+    /// Draw from any RNG, such as a [`FastRng`](crate::FastRng) in tests
+    ///
+    /// Only [`new`](ProfileGenerator::new) and
+    /// [`new_v`](ProfileGenerator::new_v) are covered by the
+    /// [`GeneratorVersion`] guarantee.
+    pub fn from_rng(rng: R) -> Self {
+        Self {
+            version: GeneratorVersion::LATEST,
+            rng,
+            priors: Cow::Borrowed(DemographicPriors::builtin()),
+            model: Cow::Borrowed(InterestModel::builtin()),
+            min_plausibility: None,
+            locale: None,
+            reference: OnceLock::new(),
         }
     }
 
-    fn generate_id(&mut self) -> String {
-        format!("profile_{:016x}", self.rng.gen::<u64>())
+    pub fn version(&self) -> GeneratorVersion {
+        self.version
     }
 
-    fn generate_name(&mut self, demographics: &Demographics) -> String {
-        let first_names_male = vec![
-            "James", "John", "Robert", "Michael", "William", "David", "Richard",
-            "Joseph", "Thomas", "Christopher", "Daniel", "Matthew", "Anthony",
-        ];
-
-        let first_names_female = vec![
-            "Mary", "Patricia", "Jennifer", "Linda", "Elizabeth", "Barbara",
-            "Susan", "Jessica", "Sarah", "Karen", "Nancy", "Lisa", "Margaret",
-        ];
-
-        let first_names_neutral = vec![
-            "Alex", "Jordan", "Taylor", "Casey", "Riley", "Morgan", "Avery",
-            "Quinn", "Sam", "Charlie", "Jamie", "Drew",
-        ];
-
-        let last_names = vec![
-            "Smith", "Johnson", "Williams", "Brown", "Jones", "Garcia", "Miller",
-            "Davis", "Rodriguez", "Martinez", "Hernandez", "Lopez", "Gonzalez",
-            "Wilson", "Anderson", "Thomas", "Taylor", "Moore", "Jackson", "Martin",
-        ];
-
-        let first_name = match demographics.gender {
-            Gender::Male => first_names_male.choose(&mut self.rng).unwrap(),
-            Gender::Female => first_names_female.choose(&mut self.rng).unwrap(),
-            Gender::NonBinary | Gender::PreferNotToSay => {
-                first_names_neutral.choose(&mut self.rng).unwrap()
-            }
-        };
+    /// Draw demographics from other priors, such as another region's or
+    /// ones calibrated to a particular population
+    pub fn with_priors(mut self, priors: DemographicPriors) -> Self {
+        self.priors = Cow::Owned(priors);
+        self.reference = OnceLock::new();
+        self
+    }
 
-        let last_name = last_names.choose(&mut self.rng).unwrap();
-        format!("{} {}", first_name, last_name)
+    /// Generate personas living in `locale`'s country and browsing in its
+    /// language
+    ///
+    /// Demographics come from the country's bundled priors, replacing any
+    /// set with [`with_priors`](Self::with_priors).
+    pub fn with_locale(mut self, locale: Locale) -> Self {
+        if let Some(priors) = DemographicPriors::for_region(locale.country.code()) {
+            self.priors = Cow::Borrowed(priors);
+            self.reference = OnceLock::new();
+        }
+        self.locale = Some(locale);
+        self
     }
 
-    fn generate_demographics(&mut self) -> Demographics {
-        let age = self.rng.gen_range(18..=75);
+    /// Draw interests, browsing style and activity level from another model
+    pub fn with_model(mut self, model: InterestModel) -> Self {
+        self.model = Cow::Owned(model);
+        self.reference = OnceLock::new();
+        self
+    }
 
-        let gender = match self.rng.gen_range(0..=100) {
-            0..=48 => Gender::Male,
-            49..=97 => Gender::Female,
-            98 => Gender::NonBinary,
-            _ => Gender::PreferNotToSay,
-        };
+    /// Redraw profiles below this plausibility percentile, from 0 to 100
+    ///
+    /// Percentiles are relative to unconstrained profiles, so a narrow spec
+    /// may never reach a high minimum; after a bounded number of draws the
+    /// most plausible one is kept.
+    pub fn with_min_plausibility(mut self, percentile: f64) -> Self {
+        self.min_plausibility = Some(percentile.clamp(0.0, 100.0));
+        self
+    }
 
-        let location_type = match self.rng.gen_range(0..=100) {
-            0..=49 => LocationType::Urban,
-            50..=84 => LocationType::Suburban,
-            _ => LocationType::Rural,
-        };
+    /// Log-probability of drawing `profile`'s demographics, interests,
+    /// browsing style and activity level from an empty spec
+    pub fn log_probability(&self, profile: &Profile) -> f64 {
+        self.priors.log_probability(&profile.demographics) + self.model.log_probability(profile)
+    }
 
-        let occupation_category = match age {
-            18..=22 => OccupationCategory::Student,
-            65.. => {
-                if self.rng.gen_bool(0.7) {
-                    OccupationCategory::Retired
-                } else {
-                    self.random_occupation()
-                }
+    /// How typical `profile` is of what this generator draws
+    pub fn plausibility(&self, profile: &Profile) -> Plausibility {
+        let reference = match (&self.priors, &self.model) {
+            // Borrowed priors are always a country's bundled ones
+            (Cow::Borrowed(priors), Cow::Borrowed(_)) => {
+                Reference::for_country(priors.locale().country)
             }
-            _ => self.random_occupation(),
+            _ => self
+                .reference
+                .get_or_init(|| Reference::sample(&self.priors, &self.model)),
         };
-
-        let education_level = match age {
-            18..=21 => {
-                if self.rng.gen_bool(0.7) {
-                    EducationLevel::HighSchool
-                } else {
-                    EducationLevel::SomeCollege
-                }
-            }
-            22..=24 => {
-                match self.rng.gen_range(0..=100) {
-                    0..=30 => EducationLevel::SomeCollege,
-                    31..=80 => EducationLevel::Bachelor,
-                    _ => EducationLevel::Master,
-                }
-            }
-            _ => {
-                match self.rng.gen_range(0..=100) {
-                    0..=20 => EducationLevel::HighSchool,
-                    21..=40 => EducationLevel::SomeCollege,
-                    41..=75 => EducationLevel::Bachelor,
-                    76..=95 => EducationLevel::Master,
-                    _ => EducationLevel::Doctorate,
-                }
-            }
-        };
-
-        Demographics {
-            age,
-            gender,
-            location_type,
-            occupation_category,
-            education_level,
-        }
+        reference.score(self.log_probability(profile))
     }
 
-    fn random_occupation(&mut self) -> OccupationCategory {
-        let occupations = vec![
-            OccupationCategory::Technology,
-            OccupationCategory::Healthcare,
-            OccupationCategory::Education,
-            OccupationCategory::Finance,
-            OccupationCategory::Creative,
-            OccupationCategory::Service,
-            OccupationCategory::Trades,
-        ];
-        occupations.choose(&mut self.rng).unwrap().clone()
+    pub fn generate(&mut self) -> Profile {
+        self.generate_with(&ProfileSpec::default())
+            .expect("an empty spec has no contradictions")
     }
 
-    fn generate_interests(&mut self, demographics: &Demographics) -> Vec<InterestCategory> {
-        let mut interests = Vec::new();
+    /// Generate a profile that satisfies `spec`
+    ///
+    /// Pinned fields are used as given and the rest follow from them the same
+    /// way they would for an unconstrained profile, so a retired spec still
+    /// tends towards gardening and travel.
+    ///
+    /// With a [minimum plausibility](Self::with_min_plausibility), profiles
+    /// below it are redrawn.
+    pub fn generate_with(&mut self, spec: &ProfileSpec) -> Result<Profile, SpecError> {
+        let ages = spec.validate()?;
+        let Some(min) = self.min_plausibility else {
+            return Ok(self.draw(spec, ages));
+        };
 
-        // Base interests on occupation
-        let occupation_interests = match demographics.occupation_category {
-            OccupationCategory::Technology => {
-                vec![InterestCategory::Technology, InterestCategory::Programming, InterestCategory::DataScience]
-            }
-            OccupationCategory::Healthcare => {
-                vec![InterestCategory::Fitness, InterestCategory::Science]
-            }
-            OccupationCategory::Education => {
-                vec![InterestCategory::Books, InterestCategory::Science]
-            }
-            OccupationCategory::Finance => {
-                vec![InterestCategory::Finance, InterestCategory::News]
+        let mut best: Option<(f64, Profile)> = None;
+        for _ in 0..PLAUSIBILITY_ATTEMPTS {
+            let candidate = self.draw(spec, ages.clone());
+            let percentile = self.plausibility(&candidate).percentile;
+            if percentile >= min {
+                return Ok(candidate);
             }
-            OccupationCategory::Creative => {
-                vec![InterestCategory::Art, InterestCategory::Music, InterestCategory::Photography]
+            if best.as_ref().is_none_or(|(p, _)| percentile > *p) {
+                best = Some((percentile, candidate));
             }
-            OccupationCategory::Student => {
-                vec![InterestCategory::Gaming, InterestCategory::Movies, InterestCategory::Music]
-            }
-            OccupationCategory::Retired => {
-                vec![InterestCategory::Gardening, InterestCategory::Travel, InterestCategory::Cooking]
-            }
-            _ => vec![InterestCategory::News],
-        };
+        }
+        Ok(best
+            .map(|(_, profile)| profile)
+            .expect("at least one candidate is drawn"))
+    }
 
-        // Add 1-2 occupation-related interests
-        let num_occupation_interests = self.rng.gen_range(1..=2.min(occupation_interests.len()));
-        interests.extend(
-            occupation_interests
-                .choose_multiple(&mut self.rng, num_occupation_interests)
-                .cloned()
-        );
+    fn draw(&mut self, spec: &ProfileSpec, ages: RangeInclusive<u8>) -> Profile {
+        match self.version {
+            GeneratorVersion::V1 => self.draw_v1(spec, ages),
+        }
+    }
 
-        // Add random interests
-        let all_interests = vec![
-            InterestCategory::Technology, InterestCategory::Gaming, InterestCategory::Sports,
-            InterestCategory::Fitness, InterestCategory::Cooking, InterestCategory::Travel,
-            InterestCategory::Fashion, InterestCategory::Music, InterestCategory::Movies,
-            InterestCategory::Books, InterestCategory::Art, InterestCategory::Science,
-            InterestCategory::Politics, InterestCategory::News, InterestCategory::Finance,
-            InterestCategory::HomeImprovement, InterestCategory::Gardening,
-            InterestCategory::Photography, InterestCategory::Programming,
-            InterestCategory::DataScience,
-        ];
-
-        let num_random_interests = self.rng.gen_range(2..=5);
-        for interest in all_interests.choose_multiple(&mut self.rng, num_random_interests) {
-            if !interests.contains(interest) {
-                interests.push(interest.clone());
-            }
+    fn draw_v1(&mut self, spec: &ProfileSpec, ages: RangeInclusive<u8>) -> Profile {
+        let mut demographics = self.generate_demographics(spec, ages);
+        if let Some(locale) = self.locale {
+            demographics.locale = locale;
         }
+        let interests = self
+            .model
+            .sample_interests(&demographics, spec, &mut self.rng);
+        let browsing_style = match &spec.browsing_style {
+            Some(style) => style.clone(),
+            None => self
+                .model
+                .sample_browsing_style(&demographics, &interests, &mut self.rng),
+        };
+        let activity_level = match &spec.activity_level {
+            Some(level) => level.clone(),
+            None => self
+                .model
+                .sample_activity_level(&demographics, &interests, &mut self.rng),
+        };
 
-        interests
+        let mut profile = Profile {
+            schema_version: SCHEMA_VERSION,
+            id: self.generate_id(),
+            name: self.generate_name(&demographics),
+            demographics,
+            interests: InterestAffinity::uniform(interests),
+            subtopics: Vec::new(),
+            browsing_style,
+            activity_level,
+            created_at: chrono::Utc::now().timestamp(),
+        };
+        // Drawn last so the rest of a seeded profile stays as it was
+        let categories: Vec<InterestCategory> = profile.categories().cloned().collect();
+        profile.subtopics = self.generate_subtopics(&categories);
+        self.weight_interests(&mut profile.interests);
+        profile
     }
 
-    fn generate_browsing_style(&mut self, interests: &[InterestCategory]) -> BrowsingStyle {
-        // Tech-savvy profiles tend to be explorers or researchers
-        if interests.contains(&InterestCategory::Technology)
-            || interests.contains(&InterestCategory::Programming) {
-            if self.rng.gen_bool(0.6) {
-                return BrowsingStyle::Explorer;
-            } else {
-                return BrowsingStyle::Researcher;
+    /// Generate a persona as unlike the real user as plausibly possible
+    ///
+    /// Candidates are drawn from the summary's contrasting spec, each with a
+    /// different occupation from the user's, and the one furthest from the
+    /// summary by [`ProfileSummary::distance`] is kept. Every candidate comes
+    /// from [`generate_with`](Self::generate_with), so the result is as
+    /// internally consistent as any other profile.
+    pub fn generate_contrasting(&mut self, real: &ProfileSummary) -> Profile {
+        let base = real.contrasting_spec();
+        let occupations = real.contrasting_occupations(&base);
+
+        let mut best: Option<(f64, Profile)> = None;
+        for _ in 0..CONTRAST_CANDIDATES {
+            let mut spec = base.clone();
+            if let Some(occupation) = occupations.choose(&mut self.rng) {
+                spec = spec.occupation(occupation.clone());
+            }
+            let candidate = self
+                .generate_with(&spec)
+                .expect("contrasting specs only pick feasible occupations");
+            let distance = real.distance(&candidate);
+            if best.as_ref().is_none_or(|(d, _)| distance > *d) {
+                best = Some((distance, candidate));
             }
         }
+        best.map(|(_, profile)| profile)
+            .expect("at least one candidate is drawn")
+    }
 
-        // Science/academic interests lean towards researcher or focused
-        if interests.contains(&InterestCategory::Science)
-            || interests.contains(&InterestCategory::DataScience) {
-            if self.rng.gen_bool(0.5) {
-                return BrowsingStyle::Researcher;
-            } else {
-                return BrowsingStyle::Focused;
+    /// Generate `n` profiles spread across occupation, age band, browsing
+    /// style and interests
+    ///
+    /// A `diversity` of 0 gives `n` independent draws. Anything higher
+    /// stratifies the first profiles: each gets a different occupation, and
+    /// age bands and browsing styles are dealt out as evenly as those
+    /// occupations allow, so a population of nine or more covers every
+    /// occupation, band and style. Each slot then draws more candidates the
+    /// higher the diversity and keeps the one furthest from its nearest
+    /// neighbour already in the population.
+    pub fn generate_population(&mut self, n: usize, diversity: f64) -> Vec<Profile> {
+        let diversity = diversity.clamp(0.0, 1.0);
+        let candidates = 1 + (diversity * (POPULATION_CANDIDATES - 1) as f64).round() as usize;
+        let strata = if diversity > 0.0 {
+            self.population_strata()
+        } else {
+            Vec::new()
+        };
+        let unconstrained = ProfileSpec::default();
+
+        let mut population: Vec<Profile> = Vec::with_capacity(n);
+        for slot in 0..n {
+            let spec = strata.get(slot).unwrap_or(&unconstrained);
+            let mut best: Option<(f64, Profile)> = None;
+            for _ in 0..candidates {
+                let candidate = self
+                    .generate_with(spec)
+                    .expect("population strata are feasible");
+                let nearest = population
+                    .iter()
+                    .map(|member| member.distance(&candidate))
+                    .fold(f64::INFINITY, f64::min);
+                if best.as_ref().is_none_or(|(d, _)| nearest > *d) {
+                    best = Some((nearest, candidate));
+                }
             }
+            population.extend(best.map(|(_, profile)| profile));
         }
+        population
+    }
 
-        // Default to weighted random
-        match self.rng.gen_range(0..=100) {
-            0..=25 => BrowsingStyle::Focused,
-            26..=50 => BrowsingStyle::Explorer,
-            51..=70 => BrowsingStyle::Researcher,
-            _ => BrowsingStyle::Casual,
+    /// One spec per occupation, in random order, each given the least-used
+    /// age band and browsing style it can have
+    fn population_strata(&mut self) -> Vec<ProfileSpec> {
+        let mut occupations = OccupationCategory::ALL.to_vec();
+        occupations.shuffle(&mut self.rng);
+        let mut band_uses = [0usize; AgeBand::ALL.len()];
+        let mut style_uses = [0usize; BrowsingStyle::ALL.len()];
+
+        let mut strata = Vec::with_capacity(occupations.len());
+        for occupation in occupations {
+            let base = ProfileSpec::new().occupation(occupation);
+            let bands: Vec<usize> = (0..AgeBand::ALL.len())
+                .filter(|&i| {
+                    let (min, max) = AgeBand::ALL[i].ages();
+                    base.clone().age_range(min, max).validate().is_ok()
+                })
+                .collect();
+            let band = self.least_used(&bands, &band_uses);
+            let style = self.least_used(
+                &(0..BrowsingStyle::ALL.len()).collect::<Vec<_>>(),
+                &style_uses,
+            );
+            band_uses[band] += 1;
+            style_uses[style] += 1;
+
+            let (min, max) = AgeBand::ALL[band].ages();
+            strata.push(
+                base.age_range(min, max)
+                    .browsing_style(BrowsingStyle::ALL[style].clone()),
+            );
         }
+        strata
     }
 
-    fn generate_activity_level(&mut self, demographics: &Demographics) -> ActivityLevel {
-        // Younger people tend to be more active online
-        let age_factor = match demographics.age {
-            18..=25 => 0.8,
-            26..=35 => 0.7,
-            36..=50 => 0.5,
-            51..=65 => 0.3,
-            _ => 0.2,
-        };
+    /// Random pick among the `options` used least so far
+    fn least_used(&mut self, options: &[usize], uses: &[usize]) -> usize {
+        let fewest = options.iter().map(|&i| uses[i]).min().unwrap_or_default();
+        let tied: Vec<usize> = options
+            .iter()
+            .copied()
+            .filter(|&i| uses[i] == fewest)
+            .collect();
+        *tied
+            .choose(&mut self.rng)
+            .expect("every occupation fits some age band")
+    }
 
-        // Students and tech workers are more active
-        let occupation_factor = match demographics.occupation_category {
-            OccupationCategory::Technology | OccupationCategory::Student => 0.2,
-            _ => 0.0,
-        };
+    fn generate_id(&mut self) -> String {
+        format!("profile_{:016x}", self.rng.gen::<u64>())
+    }
+
+    fn generate_name(&mut self, demographics: &Demographics) -> String {
+        let first_name = first_name(&demographics.gender, &demographics.locale, &mut self.rng);
+        let last_name = last_name(&demographics.locale, &mut self.rng);
+        format!("{} {}", first_name, last_name)
+    }
 
-        let activity_score = self.rng.gen::<f32>() + age_factor + occupation_factor;
+    fn generate_demographics(
+        &mut self,
+        spec: &ProfileSpec,
+        ages: RangeInclusive<u8>,
+    ) -> Demographics {
+        self.priors.sample(spec, &ages, &mut self.rng)
+    }
 
-        match activity_score {
-            x if x < 0.5 => ActivityLevel::Low,
-            x if x < 1.0 => ActivityLevel::Medium,
-            x if x < 1.5 => ActivityLevel::High,
-            _ => ActivityLevel::VeryHigh,
+    fn generate_subtopics(&mut self, interests: &[InterestCategory]) -> Vec<String> {
+        let taxonomy = Taxonomy::builtin();
+        let mut subtopics = Vec::new();
+        for interest in interests {
+            let leaves: Vec<&Subtopic> = taxonomy.leaves(interest).collect();
+            if leaves.is_empty() {
+                continue;
+            }
+            let count = self.rng.gen_range(1..=2.min(leaves.len()));
+            subtopics.extend(
+                leaves
+                    .choose_multiple(&mut self.rng, count)
+                    .map(|subtopic| subtopic.id.clone()),
+            );
         }
+        subtopics
     }
+
+    /// Skew weights so one or two dominant hobbies take most of the time
+    ///
+    /// Dominant interests draw from a Gamma with a mean eight times that of
+    /// the rest, which typically leaves the top two with 70-95% of activity.
+    fn weight_interests(&mut self, interests: &mut [InterestAffinity]) {
+        let dominant_count = self.rng.gen_range(1..=2).min(interests.len());
+        let dominant: Vec<usize> =
+            rand::seq::index::sample(&mut self.rng, interests.len(), dominant_count).into_vec();
+
+        let major = Gamma::new(8.0, 1.0).expect("valid gamma parameters");
+        let minor = Gamma::new(2.0, 0.5).expect("valid gamma parameters");
+        for (i, interest) in interests.iter_mut().enumerate() {
+            interest.weight = if dominant.contains(&i) {
+                major.sample(&mut self.rng)
+            } else {
+                minor.sample(&mut self.rng)
+            };
+        }
+
+        let total: f64 = interests.iter().map(|i| i.weight).sum();
+        for interest in interests.iter_mut() {
+            interest.weight /= total;
+        }
+    }
+}
+
+/// A first name for someone of `gender` living in `locale`'s country
+pub(crate) fn first_name<R: Rng>(gender: &Gender, locale: &Locale, rng: &mut R) -> &'static str {
+    let names = &locale.country_data().names;
+    let names = match gender {
+        Gender::Male => &names.male,
+        Gender::Female => &names.female,
+        Gender::NonBinary | Gender::PreferNotToSay => &names.neutral,
+    };
+    names.choose(rng).unwrap()
+}
+
+pub(crate) fn last_name<R: Rng>(locale: &Locale, rng: &mut R) -> &'static str {
+    locale.country_data().names.last.choose(rng).unwrap()
 }
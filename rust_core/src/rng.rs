@@ -0,0 +1,23 @@
+use rand::rngs::SmallRng;
+use rand::SeedableRng;
+use rand_chacha::{ChaCha20Rng, ChaCha8Rng};
+
+/// Fast and reproducible from a seed, but predictable: an observer who sees
+/// a few outputs can work out the rest. For tests and seeded runs.
+pub type FastRng = SmallRng;
+
+/// ChaCha20, a cryptographically secure generator: seeing some decoys
+/// tells an observer nothing about the next ones. For live simulation.
+pub type LiveRng = ChaCha20Rng;
+
+/// A reproducible RNG for details that follow from an id, such as a
+/// persona's backstory; `domain` keeps different uses of one id apart
+pub(crate) fn derived_rng(domain: &str, id: &str) -> ChaCha8Rng {
+    // FNV-1a, which unlike `DefaultHasher` is stable across releases
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in domain.bytes().chain([0]).chain(id.bytes()) {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+    }
+    ChaCha8Rng::seed_from_u64(hash)
+}
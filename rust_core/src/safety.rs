@@ -0,0 +1,322 @@
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+/// Kinds of site decoy traffic must never touch
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum RiskCategory {
+    Adult,
+    Gambling,
+    Extremist,
+    Malware,
+}
+
+impl RiskCategory {
+    pub const ALL: [RiskCategory; 4] = [
+        RiskCategory::Adult,
+        RiskCategory::Gambling,
+        RiskCategory::Extremist,
+        RiskCategory::Malware,
+    ];
+
+    /// Known domains in this category; subdomains match too
+    fn domains(&self) -> &'static [&'static str] {
+        match self {
+            RiskCategory::Adult => &[
+                "pornhub.com",
+                "xvideos.com",
+                "xnxx.com",
+                "xhamster.com",
+                "onlyfans.com",
+                "chaturbate.com",
+                "redtube.com",
+                "youporn.com",
+            ],
+            RiskCategory::Gambling => &[
+                "bet365.com",
+                "draftkings.com",
+                "fanduel.com",
+                "pokerstars.com",
+                "williamhill.com",
+                "betfair.com",
+                "stake.com",
+                "888casino.com",
+                "caesars.com",
+            ],
+            RiskCategory::Extremist => &["stormfront.org", "dailystormer.su", "8kun.top"],
+            RiskCategory::Malware => &[
+                "testsafebrowsing.appspot.com",
+                "malware.wicar.org",
+                "eicar.org",
+                "amtso.org",
+            ],
+        }
+    }
+
+    /// Host fragments that give a site away even when it isn't listed
+    fn host_keywords(&self) -> &'static [&'static str] {
+        match self {
+            RiskCategory::Adult => &["porn", "xxx", "nsfw", "hentai"],
+            RiskCategory::Gambling => &["casino", "poker", "sportsbook", "betting", "gambl"],
+            RiskCategory::Extremist | RiskCategory::Malware => &[],
+        }
+    }
+}
+
+/// What may be done with a generated URL
+///
+/// Ordered from most to least permissive so the strictest of several
+/// matching rules can be taken with `max`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Verdict {
+    /// Fine to emit and to actually load
+    Allow,
+    /// Fine to emit as a simulated visit, but not to load
+    SimulateOnly,
+    /// Must not be emitted at all
+    Deny,
+}
+
+/// Glob over a URL's path and query; `*` matches any run of characters
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PathRule {
+    pub pattern: String,
+    pub verdict: Verdict,
+}
+
+/// Paths where a real visit would change state: sign-outs, purchases and so on
+const SIDE_EFFECT_PATTERNS: &[&str] = &[
+    "*/logout*",
+    "*/log-out*",
+    "*/signout*",
+    "*/sign-out*",
+    "*/sign_out*",
+    "*/logoff*",
+    "*action=logout*",
+    "*/checkout*",
+    "*/cart/add*",
+    "*/add-to-cart*",
+    "*/addtocart*",
+    "*/purchase*",
+    "*/buy-now*",
+    "*/order/place*",
+    "*/unsubscribe*",
+    "*/delete*",
+    "*/account/close*",
+    "*/oauth/revoke*",
+    "*/cancel-subscription*",
+];
+
+/// Rules applied to every URL the generator emits
+///
+/// Precedence, strictest first: user denylist, path rules, built-in
+/// category denylists, and finally `load_only_allowlisted`. The user
+/// allowlist never overrides a blocked category; a category can only be
+/// opted out of by removing it from `blocked_categories`. Hosts under the
+/// reserved `example` names are never loaded since there is nothing real
+/// behind them.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SafetyPolicy {
+    /// Categories never emitted, whatever `allow_domains` says; all of
+    /// them by default
+    pub blocked_categories: Vec<RiskCategory>,
+    pub deny_domains: Vec<String>,
+    pub allow_domains: Vec<String>,
+    /// Extra rules on top of the built-in side-effect rules
+    pub path_rules: Vec<PathRule>,
+    /// Only allowlisted domains are safe to actually load
+    pub load_only_allowlisted: bool,
+}
+
+impl Default for SafetyPolicy {
+    fn default() -> Self {
+        Self {
+            blocked_categories: RiskCategory::ALL.to_vec(),
+            deny_domains: Vec::new(),
+            allow_domains: Vec::new(),
+            path_rules: Vec::new(),
+            load_only_allowlisted: false,
+        }
+    }
+}
+
+impl SafetyPolicy {
+    /// Judge a URL string; anything that doesn't parse is denied
+    pub fn check_str(&self, url: &str) -> Verdict {
+        Url::parse(url).map_or(Verdict::Deny, |url| self.check(&url))
+    }
+
+    pub fn check(&self, url: &Url) -> Verdict {
+        let Some(host) = url.host_str().map(str::to_lowercase) else {
+            return Verdict::Deny;
+        };
+
+        if self.deny_domains.iter().any(|d| domain_matches(&host, d)) {
+            return Verdict::Deny;
+        }
+
+        let target = match url.query() {
+            Some(query) => format!("{}?{}", url.path(), query),
+            None => url.path().to_string(),
+        }
+        .to_lowercase();
+        let path_verdict = SIDE_EFFECT_PATTERNS
+            .iter()
+            .map(|pattern| (*pattern, Verdict::Deny))
+            .chain(
+                self.path_rules
+                    .iter()
+                    .map(|r| (r.pattern.as_str(), r.verdict)),
+            )
+            .filter(|(pattern, _)| glob_match(&pattern.to_lowercase(), &target))
+            .map(|(_, verdict)| verdict)
+            .max()
+            .unwrap_or(Verdict::Allow);
+        if path_verdict == Verdict::Deny {
+            return Verdict::Deny;
+        }
+
+        if self.is_risky(&host) {
+            return Verdict::Deny;
+        }
+
+        let reserved = host == "example.com"
+            || host == "example.org"
+            || host == "example.net"
+            || host.ends_with(".example")
+            || ["example.com", "example.org", "example.net"]
+                .iter()
+                .any(|d| host.ends_with(&format!(".{}", d)));
+        let allowlisted = self.allow_domains.iter().any(|d| domain_matches(&host, d));
+        if reserved || (self.load_only_allowlisted && !allowlisted) {
+            return Verdict::SimulateOnly;
+        }
+
+        path_verdict
+    }
+
+    fn is_risky(&self, host: &str) -> bool {
+        self.blocked_categories.iter().any(|category| {
+            category.domains().iter().any(|d| domain_matches(host, d))
+                || category.host_keywords().iter().any(|k| host.contains(k))
+        })
+    }
+}
+
+/// Whether `host` is `domain` or one of its subdomains
+fn domain_matches(host: &str, domain: &str) -> bool {
+    let domain = domain.trim().trim_start_matches("*.").to_lowercase();
+    host == domain || host.ends_with(&format!(".{}", domain))
+}
+
+/// Match `text` against a pattern where `*` stands for any run of characters
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = text.strip_prefix(first) else {
+        return false;
+    };
+
+    let parts: Vec<&str> = parts.collect();
+    let Some((last, middle)) = parts.split_last() else {
+        // No '*' at all: the whole text must match
+        return rest.is_empty();
+    };
+
+    for part in middle {
+        match rest.find(part) {
+            Some(i) => rest = &rest[i + part.len()..],
+            None => return false,
+        }
+    }
+    rest.len() >= last.len() && rest.ends_with(last)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_denylists() {
+        let policy = SafetyPolicy::default();
+
+        assert_eq!(policy.check_str("https://www.bet365.com/"), Verdict::Deny);
+        assert_eq!(
+            policy.check_str("https://free-casino-games.net/"),
+            Verdict::Deny
+        );
+        assert_eq!(
+            policy.check_str("https://stormfront.org/forum"),
+            Verdict::Deny
+        );
+        assert_eq!(policy.check_str("https://www.essex.ac.uk/"), Verdict::Allow);
+        assert_eq!(
+            policy.check_str("https://www.bbc.com/news/world-123"),
+            Verdict::Allow
+        );
+        assert_eq!(
+            policy.check_str("https://example.com/page"),
+            Verdict::SimulateOnly
+        );
+        assert_eq!(policy.check_str("not a url"), Verdict::Deny);
+    }
+
+    #[test]
+    fn test_side_effect_paths_and_user_lists() {
+        let mut policy = SafetyPolicy::default();
+        assert_eq!(
+            policy.check_str("https://www.amazon.com/gp/checkout/select"),
+            Verdict::Deny
+        );
+        assert_eq!(
+            policy.check_str("https://mail.example.org/?action=LOGOUT"),
+            Verdict::Deny
+        );
+
+        policy.deny_domains.push("reddit.com".to_string());
+        policy.allow_domains.push("draftkings.com".to_string());
+        policy.allow_domains.push("bbc.co.uk".to_string());
+        policy.path_rules.push(PathRule {
+            pattern: "/watch*".to_string(),
+            verdict: Verdict::SimulateOnly,
+        });
+        policy.load_only_allowlisted = false;
+
+        assert_eq!(
+            policy.check_str("https://old.reddit.com/r/rust"),
+            Verdict::Deny
+        );
+        assert_eq!(
+            policy.check_str("https://sportsbook.draftkings.com/"),
+            Verdict::Deny
+        );
+        assert_eq!(
+            policy.check_str("https://www.youtube.com/watch?v=abc"),
+            Verdict::SimulateOnly
+        );
+
+        policy.load_only_allowlisted = true;
+        assert_eq!(
+            policy.check_str("https://www.bbc.com/"),
+            Verdict::SimulateOnly
+        );
+        assert_eq!(policy.check_str("https://www.bbc.co.uk/"), Verdict::Allow);
+
+        policy
+            .blocked_categories
+            .retain(|c| *c != RiskCategory::Gambling);
+        assert_eq!(
+            policy.check_str("https://sportsbook.draftkings.com/"),
+            Verdict::Allow
+        );
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("*/logout*", "/account/logout?next=/"));
+        assert!(glob_match("/watch*", "/watch?v=1"));
+        assert!(!glob_match("/watch*", "/embed/watch"));
+        assert!(glob_match("/exact", "/exact"));
+        assert!(!glob_match("/exact", "/exact/more"));
+    }
+}
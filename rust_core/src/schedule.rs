@@ -1,11 +1,18 @@
+use crate::locale::local_time;
+use crate::profile::{ActivityLevel, OccupationCategory, Profile};
+use chrono::{Datelike, Timelike, Weekday};
 use serde::{Deserialize, Serialize};
-use crate::profile::{Profile, ActivityLevel, OccupationCategory};
 
 /// Represents a schedule for when activities should occur
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Schedule {
+    /// Hours in each pattern are local to `timezone`
     pub time_patterns: Vec<TimePattern>,
+    /// Minutes east of UTC, in standard time
     pub timezone_offset: i32,
+    /// IANA name of the persona's timezone, e.g. `Europe/Berlin`
+    #[serde(default)]
+    pub timezone: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -55,9 +62,11 @@ impl Schedule {
             patterns.push(pattern);
         }
 
+        let timezone = profile.demographics.locale.timezone();
         Schedule {
             time_patterns: patterns,
-            timezone_offset: 0, // UTC by default
+            timezone_offset: timezone.utc_offset_minutes,
+            timezone: timezone.name.clone(),
         }
     }
 
@@ -131,6 +140,32 @@ impl Schedule {
         }
     }
 
+    /// How intensely the persona browses at a Unix timestamp, or 0 outside
+    /// its active hours
+    ///
+    /// Summer time applies where the bundled timezone of that name has it.
+    pub fn intensity_at(&self, timestamp: i64) -> f32 {
+        let Some(local) = local_time(&self.timezone, self.timezone_offset, timestamp) else {
+            return 0.0;
+        };
+        let day = match local.weekday() {
+            Weekday::Mon => DayOfWeek::Monday,
+            Weekday::Tue => DayOfWeek::Tuesday,
+            Weekday::Wed => DayOfWeek::Wednesday,
+            Weekday::Thu => DayOfWeek::Thursday,
+            Weekday::Fri => DayOfWeek::Friday,
+            Weekday::Sat => DayOfWeek::Saturday,
+            Weekday::Sun => DayOfWeek::Sunday,
+        };
+        if !self.is_active_hour(day, local.hour() as u8) {
+            return 0.0;
+        }
+        self.time_patterns
+            .iter()
+            .find(|pattern| matches_day(pattern.day_of_week, day))
+            .map_or(0.0, |pattern| pattern.activity_intensity)
+    }
+
     /// Check if the given hour is within an active period
     pub fn is_active_hour(&self, day: DayOfWeek, hour: u8) -> bool {
         for pattern in &self.time_patterns {
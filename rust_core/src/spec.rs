@@ -0,0 +1,295 @@
+use crate::profile::{
+    ActivityLevel, BrowsingStyle, EducationLevel, Gender, InterestCategory, LocationType,
+    OccupationCategory,
+};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::ops::RangeInclusive;
+
+/// Ages the generator draws from when the spec doesn't say
+const DEFAULT_AGES: RangeInclusive<u8> = 18..=75;
+
+/// Ages a profile may have at all
+pub(crate) const VALID_AGES: RangeInclusive<u8> = 18..=100;
+
+/// Most interests a profile may have, matching [`Profile::is_valid`](crate::Profile::is_valid)
+pub(crate) const MAX_INTERESTS: usize = 10;
+
+/// Constraints on a generated profile; anything left unset is drawn as usual
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProfileSpec {
+    /// Inclusive bounds on age
+    pub age_range: Option<(u8, u8)>,
+    pub gender: Option<Gender>,
+    pub location_type: Option<LocationType>,
+    pub occupation: Option<OccupationCategory>,
+    pub education_level: Option<EducationLevel>,
+    pub required_interests: Vec<InterestCategory>,
+    pub forbidden_interests: Vec<InterestCategory>,
+    pub browsing_style: Option<BrowsingStyle>,
+    pub activity_level: Option<ActivityLevel>,
+}
+
+impl ProfileSpec {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn age_range(mut self, min: u8, max: u8) -> Self {
+        self.age_range = Some((min, max));
+        self
+    }
+
+    pub fn age(self, age: u8) -> Self {
+        self.age_range(age, age)
+    }
+
+    pub fn gender(mut self, gender: Gender) -> Self {
+        self.gender = Some(gender);
+        self
+    }
+
+    pub fn location_type(mut self, location_type: LocationType) -> Self {
+        self.location_type = Some(location_type);
+        self
+    }
+
+    pub fn occupation(mut self, occupation: OccupationCategory) -> Self {
+        self.occupation = Some(occupation);
+        self
+    }
+
+    pub fn education_level(mut self, education_level: EducationLevel) -> Self {
+        self.education_level = Some(education_level);
+        self
+    }
+
+    pub fn require_interest(mut self, interest: InterestCategory) -> Self {
+        if !self.required_interests.contains(&interest) {
+            self.required_interests.push(interest);
+        }
+        self
+    }
+
+    pub fn forbid_interest(mut self, interest: InterestCategory) -> Self {
+        if !self.forbidden_interests.contains(&interest) {
+            self.forbidden_interests.push(interest);
+        }
+        self
+    }
+
+    pub fn browsing_style(mut self, browsing_style: BrowsingStyle) -> Self {
+        self.browsing_style = Some(browsing_style);
+        self
+    }
+
+    pub fn activity_level(mut self, activity_level: ActivityLevel) -> Self {
+        self.activity_level = Some(activity_level);
+        self
+    }
+
+    /// Check the constraints agree with each other
+    ///
+    /// Returns the ages left once occupation and education are taken into
+    /// account, which is where the generator draws from.
+    pub fn validate(&self) -> Result<RangeInclusive<u8>, SpecError> {
+        let mut ages = match self.age_range {
+            Some((min, max)) => {
+                if min > max || !VALID_AGES.contains(&min) || !VALID_AGES.contains(&max) {
+                    return Err(SpecError::InvalidAgeRange { min, max });
+                }
+                min..=max
+            }
+            None => DEFAULT_AGES,
+        };
+
+        if let Some(occupation) = &self.occupation {
+            ages = narrow(&ages, &occupation_ages(occupation)).ok_or_else(|| {
+                SpecError::OccupationAge {
+                    occupation: occupation.clone(),
+                    min: *ages.start(),
+                    max: *ages.end(),
+                }
+            })?;
+        }
+
+        if let Some(education) = &self.education_level {
+            let feasible = education_min_age(education)..=*VALID_AGES.end();
+            ages = narrow(&ages, &feasible).ok_or_else(|| SpecError::EducationAge {
+                education: education.clone(),
+                min: *ages.start(),
+                max: *ages.end(),
+            })?;
+        }
+
+        if let Some(interest) = self.required_interests.iter().find(|i| i.is_sensitive()) {
+            return Err(SpecError::SensitiveInterest(interest.clone()));
+        }
+        if let Some(interest) = self
+            .required_interests
+            .iter()
+            .find(|i| self.forbidden_interests.contains(i))
+        {
+            return Err(SpecError::ConflictingInterest(interest.clone()));
+        }
+        if self.required_interests.len() > MAX_INTERESTS {
+            return Err(SpecError::TooManyInterests(self.required_interests.len()));
+        }
+        let allowed = InterestCategory::ALL
+            .iter()
+            .filter(|i| !i.is_sensitive() && !self.forbidden_interests.contains(i))
+            .count();
+        if allowed == 0 {
+            return Err(SpecError::NoInterestsLeft);
+        }
+
+        Ok(ages)
+    }
+
+    pub(crate) fn allows_interest(&self, interest: &InterestCategory) -> bool {
+        !interest.is_sensitive() && !self.forbidden_interests.contains(interest)
+    }
+}
+
+/// Why a [`ProfileSpec`] can't be satisfied
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SpecError {
+    /// Bounds are reversed or outside 18-100
+    InvalidAgeRange {
+        min: u8,
+        max: u8,
+    },
+    /// Nobody in the age range plausibly has this occupation
+    OccupationAge {
+        occupation: OccupationCategory,
+        min: u8,
+        max: u8,
+    },
+    /// Nobody in the age range has finished this level of education
+    EducationAge {
+        education: EducationLevel,
+        min: u8,
+        max: u8,
+    },
+    /// An interest is both required and forbidden
+    ConflictingInterest(InterestCategory),
+    /// Sensitive topics are cover traffic, never profile interests
+    SensitiveInterest(InterestCategory),
+    TooManyInterests(usize),
+    /// Every everyday interest is forbidden
+    NoInterestsLeft,
+}
+
+impl fmt::Display for SpecError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SpecError::InvalidAgeRange { min, max } => {
+                write!(f, "age range {}-{} is not within 18-100", min, max)
+            }
+            SpecError::OccupationAge {
+                occupation,
+                min,
+                max,
+            } => write!(f, "no {:?} profile can be aged {}-{}", occupation, min, max),
+            SpecError::EducationAge {
+                education,
+                min,
+                max,
+            } => write!(
+                f,
+                "nobody aged {}-{} has a {:?} education",
+                min, max, education
+            ),
+            SpecError::ConflictingInterest(interest) => {
+                write!(f, "{:?} is both required and forbidden", interest)
+            }
+            SpecError::SensitiveInterest(interest) => {
+                write!(f, "{:?} is only used for cover traffic", interest)
+            }
+            SpecError::TooManyInterests(count) => write!(
+                f,
+                "{} required interests is more than the limit of {}",
+                count, MAX_INTERESTS
+            ),
+            SpecError::NoInterestsLeft => write!(f, "every interest is forbidden"),
+        }
+    }
+}
+
+impl std::error::Error for SpecError {}
+
+/// Ages at which an occupation is plausible
+pub(crate) fn occupation_ages(occupation: &OccupationCategory) -> RangeInclusive<u8> {
+    match occupation {
+        OccupationCategory::Student => 18..=35,
+        OccupationCategory::Retired => 50..=*VALID_AGES.end(),
+        _ => 18..=80,
+    }
+}
+
+/// Youngest age the generator gives each education level
+pub(crate) fn education_min_age(education: &EducationLevel) -> u8 {
+    match education {
+        EducationLevel::HighSchool | EducationLevel::SomeCollege => 18,
+        EducationLevel::Bachelor | EducationLevel::Master => 22,
+        EducationLevel::Doctorate => 25,
+    }
+}
+
+pub(crate) fn narrow(a: &RangeInclusive<u8>, b: &RangeInclusive<u8>) -> Option<RangeInclusive<u8>> {
+    let start = *a.start().max(b.start());
+    let end = *a.end().min(b.end());
+    (start <= end).then_some(start..=end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_narrows_ages() {
+        assert_eq!(ProfileSpec::new().validate(), Ok(DEFAULT_AGES));
+        assert_eq!(
+            ProfileSpec::new()
+                .occupation(OccupationCategory::Retired)
+                .validate(),
+            Ok(50..=75)
+        );
+        assert_eq!(
+            ProfileSpec::new()
+                .age_range(20, 40)
+                .education_level(EducationLevel::Doctorate)
+                .validate(),
+            Ok(25..=40)
+        );
+    }
+
+    #[test]
+    fn test_validate_rejects_contradictions() {
+        assert!(matches!(
+            ProfileSpec::new()
+                .age(70)
+                .occupation(OccupationCategory::Student)
+                .validate(),
+            Err(SpecError::OccupationAge { .. })
+        ));
+        assert!(matches!(
+            ProfileSpec::new().age_range(40, 30).validate(),
+            Err(SpecError::InvalidAgeRange { .. })
+        ));
+        assert_eq!(
+            ProfileSpec::new()
+                .require_interest(InterestCategory::Music)
+                .forbid_interest(InterestCategory::Music)
+                .validate(),
+            Err(SpecError::ConflictingInterest(InterestCategory::Music))
+        );
+        assert_eq!(
+            ProfileSpec::new()
+                .require_interest(InterestCategory::Immigration)
+                .validate(),
+            Err(SpecError::SensitiveInterest(InterestCategory::Immigration))
+        );
+    }
+}
@@ -0,0 +1,210 @@
+use crate::grammar::Lexicon;
+use crate::profile::InterestCategory;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+/// Built-in subtopic tree, one entry per category that has one
+const BUILTIN_TAXONOMY: &str = include_str!("../data/taxonomy.json");
+
+/// A specific interest within a category, such as Rust under Programming
+///
+/// Vocabulary and domains are resolved at load time: fields a node leaves
+/// out are inherited from its parent, and the category's built-in
+/// [`Lexicon`] sits at the root.
+#[derive(Debug, Clone)]
+pub struct Subtopic {
+    /// Path from the category, e.g. `programming/javascript/react`
+    pub id: String,
+    pub name: String,
+    pub category: InterestCategory,
+    pub lexicon: Lexicon,
+    /// Sites that cover this subtopic; empty when neither it nor any
+    /// ancestor lists any
+    pub domains: Vec<String>,
+    pub parent: Option<String>,
+    pub children: Vec<String>,
+}
+
+impl Subtopic {
+    pub fn is_leaf(&self) -> bool {
+        self.children.is_empty()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct RawCategory {
+    category: InterestCategory,
+    subtopics: Vec<RawNode>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawNode {
+    slug: String,
+    name: String,
+    #[serde(default)]
+    entities: Vec<String>,
+    #[serde(default)]
+    nouns: Vec<String>,
+    #[serde(default)]
+    adjectives: Vec<String>,
+    #[serde(default)]
+    actions: Vec<String>,
+    local: Option<bool>,
+    #[serde(default)]
+    domains: Vec<String>,
+    #[serde(default)]
+    children: Vec<RawNode>,
+}
+
+/// Tree of subtopics under each [`InterestCategory`]
+///
+/// Subtopics are kept in document order so that sampling from a seeded RNG
+/// is reproducible.
+#[derive(Debug, Clone)]
+pub struct Taxonomy {
+    subtopics: Vec<Subtopic>,
+    index: HashMap<String, usize>,
+}
+
+impl Taxonomy {
+    /// The tree shipped in `data/taxonomy.json`
+    pub fn builtin() -> &'static Taxonomy {
+        static TAXONOMY: OnceLock<Taxonomy> = OnceLock::new();
+        TAXONOMY.get_or_init(|| {
+            Taxonomy::from_json(BUILTIN_TAXONOMY).expect("built-in taxonomy is valid")
+        })
+    }
+
+    /// Load a tree from JSON in the same shape as `data/taxonomy.json`
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        let raw: Vec<RawCategory> = serde_json::from_str(json)?;
+
+        let mut taxonomy = Taxonomy {
+            subtopics: Vec::new(),
+            index: HashMap::new(),
+        };
+        for entry in raw {
+            let root = Lexicon::builtin(Some(&entry.category));
+            let prefix = crate::url_template::slug(&format!("{:?}", entry.category));
+            for node in entry.subtopics {
+                taxonomy.add(&entry.category, node, &prefix, None, &root, &[]);
+            }
+        }
+        Ok(taxonomy)
+    }
+
+    fn add(
+        &mut self,
+        category: &InterestCategory,
+        node: RawNode,
+        prefix: &str,
+        parent: Option<&str>,
+        inherited: &Lexicon,
+        inherited_domains: &[String],
+    ) -> String {
+        let id = format!("{}/{}", prefix, node.slug);
+        let pick = |own: Vec<String>, parent: &[String]| {
+            if own.is_empty() {
+                parent.to_vec()
+            } else {
+                own
+            }
+        };
+        let lexicon = Lexicon {
+            entities: pick(node.entities, &inherited.entities),
+            nouns: pick(node.nouns, &inherited.nouns),
+            adjectives: pick(node.adjectives, &inherited.adjectives),
+            actions: pick(node.actions, &inherited.actions),
+            local: node.local.unwrap_or(inherited.local),
+        };
+        let domains = pick(node.domains, inherited_domains);
+
+        let position = self.subtopics.len();
+        self.subtopics.push(Subtopic {
+            id: id.clone(),
+            name: node.name,
+            category: category.clone(),
+            lexicon: lexicon.clone(),
+            domains: domains.clone(),
+            parent: parent.map(str::to_string),
+            children: Vec::new(),
+        });
+        self.index.insert(id.clone(), position);
+
+        let children: Vec<String> = node
+            .children
+            .into_iter()
+            .map(|child| self.add(category, child, &id, Some(&id), &lexicon, &domains))
+            .collect();
+        self.subtopics[position].children = children;
+        id
+    }
+
+    pub fn get(&self, id: &str) -> Option<&Subtopic> {
+        self.index.get(id).map(|&i| &self.subtopics[i])
+    }
+
+    /// Every subtopic under a category, parents before their children
+    pub fn subtopics<'a>(
+        &'a self,
+        category: &'a InterestCategory,
+    ) -> impl Iterator<Item = &'a Subtopic> + 'a {
+        self.subtopics
+            .iter()
+            .filter(move |subtopic| &subtopic.category == category)
+    }
+
+    /// The most specific subtopics under a category
+    pub fn leaves<'a>(
+        &'a self,
+        category: &'a InterestCategory,
+    ) -> impl Iterator<Item = &'a Subtopic> + 'a {
+        self.subtopics(category)
+            .filter(|subtopic| subtopic.is_leaf())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_taxonomy_covers_everyday_categories() {
+        let taxonomy = Taxonomy::builtin();
+        for category in InterestCategory::ALL.iter().filter(|c| !c.is_sensitive()) {
+            assert!(
+                taxonomy.leaves(category).count() >= 2,
+                "{:?} needs subtopics",
+                category
+            );
+            for subtopic in taxonomy.subtopics(category) {
+                assert!(!subtopic.lexicon.entities.is_empty(), "{}", subtopic.id);
+                assert!(!subtopic.domains.is_empty(), "{}", subtopic.id);
+                for host in &subtopic.domains {
+                    crate::url_template::validate(&format!("https://{}/", host)).unwrap();
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_children_inherit_from_parents() {
+        let taxonomy = Taxonomy::builtin();
+        let react = taxonomy.get("programming/javascript/react").unwrap();
+        let javascript = taxonomy.get("programming/javascript").unwrap();
+
+        assert_eq!(react.parent.as_deref(), Some("programming/javascript"));
+        assert!(!javascript.is_leaf());
+        assert!(react.lexicon.entities.contains(&"Next.js".to_string()));
+        // Neither node overrides actions, so both fall back to the category's
+        assert_eq!(react.lexicon.actions, javascript.lexicon.actions);
+        assert_eq!(
+            react.lexicon.actions,
+            Lexicon::builtin(Some(&InterestCategory::Programming)).actions
+        );
+        assert!(!taxonomy
+            .leaves(&InterestCategory::Programming)
+            .any(|s| s.id == "programming/javascript"));
+    }
+}
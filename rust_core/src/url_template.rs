@@ -0,0 +1,800 @@
+use chrono::{Datelike, Duration, NaiveDate};
+use rand::Rng;
+use std::fmt;
+use url::Url;
+
+/// Describes how a site lays out its deep links and page titles
+///
+/// Path and query patterns are literal text with `{placeholder}` slots:
+///
+/// - `{text}` the raw text (search query, headline, product name)
+/// - `{slug}` lowercase hyphenated slug of the text, at most eight words
+/// - `{Slug}` Title-Case hyphenated slug of the text
+/// - `{wiki}` Wikipedia-style `Underscored_Title`
+/// - `{section}` site section derived from the interest
+/// - `{yyyy}`, `{yy}`, `{mm}`, `{dd}`, `{mon}` parts of the publication date
+/// - `{num:N}` N digit decimal ID, `{alnum:N}` / `{ALNUM:N}` lower/upper
+///   case alphanumeric ID, `{b36:N}` base36 ID, `{b64:N}` URL-safe base64 ID
+///
+/// Substituted values are percent-encoded by the URL parser, never by hand.
+#[derive(Debug, Clone, Copy)]
+pub struct SiteTemplate {
+    /// Display name used in page titles
+    pub name: &'static str,
+    pub path: &'static str,
+    /// Query parameters as (name, value pattern) pairs
+    pub query: &'static [(&'static str, &'static str)],
+    /// Page title pattern; only `{text}` and `{name}` are substituted
+    pub title: &'static str,
+}
+
+/// Values available to a template's placeholders
+#[derive(Debug, Clone)]
+pub struct TemplateContext<'a> {
+    pub text: &'a str,
+    pub section: &'a str,
+    pub date: NaiveDate,
+}
+
+impl<'a> TemplateContext<'a> {
+    /// Context dated somewhere within the month up to `today`
+    pub fn recent<R: Rng>(text: &'a str, section: &'a str, today: NaiveDate, rng: &mut R) -> Self {
+        Self {
+            text,
+            section,
+            date: today - Duration::days(rng.gen_range(0..30)),
+        }
+    }
+}
+
+/// Why a URL can't be emitted
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UrlError {
+    /// The text doesn't parse as an absolute URL
+    Parse(url::ParseError),
+    /// The URL isn't http or https, e.g. `ftp:` or `javascript:`
+    Scheme(String),
+}
+
+impl fmt::Display for UrlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UrlError::Parse(error) => write!(f, "invalid URL: {}", error),
+            UrlError::Scheme(scheme) => write!(f, "unsupported scheme {}:", scheme),
+        }
+    }
+}
+
+impl std::error::Error for UrlError {}
+
+impl From<url::ParseError> for UrlError {
+    fn from(error: url::ParseError) -> Self {
+        UrlError::Parse(error)
+    }
+}
+
+const GENERIC: SiteTemplate = SiteTemplate {
+    name: "",
+    path: "/{slug}",
+    query: &[],
+    title: "{text} | {name}",
+};
+
+impl SiteTemplate {
+    /// Build a URL on `host` from this template
+    pub fn render<R: Rng>(
+        &self,
+        host: &str,
+        ctx: &TemplateContext,
+        rng: &mut R,
+    ) -> Result<Url, UrlError> {
+        let mut url = Url::parse(&format!("https://{}/", host))?;
+
+        let segments: Vec<String> = self
+            .path
+            .trim_start_matches('/')
+            .split('/')
+            .map(|segment| expand(segment, ctx, rng))
+            .collect();
+        url.path_segments_mut()
+            .map_err(|_| url::ParseError::RelativeUrlWithCannotBeABaseBase)?
+            .clear()
+            .extend(segments);
+
+        if !self.query.is_empty() {
+            let mut pairs = url.query_pairs_mut();
+            for (name, value) in self.query {
+                pairs.append_pair(name, &expand(value, ctx, rng));
+            }
+        }
+
+        validate(url.as_str())
+    }
+
+    /// Build the page title for `text` on `host`
+    pub fn render_title(&self, host: &str, text: &str) -> String {
+        let name = if self.name.is_empty() {
+            site_name(host)
+        } else {
+            self.name.to_string()
+        };
+        self.title.replace("{text}", text).replace("{name}", &name)
+    }
+}
+
+/// Parse `url` and check it is an absolute http(s) URL with a host
+pub fn validate(url: &str) -> Result<Url, UrlError> {
+    let parsed = Url::parse(url)?;
+    match parsed.scheme() {
+        "http" | "https" if parsed.host_str().is_some() => Ok(parsed),
+        "http" | "https" => Err(UrlError::Parse(url::ParseError::EmptyHost)),
+        scheme => Err(UrlError::Scheme(scheme.to_string())),
+    }
+}
+
+/// Look up the template for a host, falling back to a generic `/{slug}` layout
+pub fn site_template(host: &str) -> SiteTemplate {
+    match host {
+        // Search engines
+        "www.google.com" => SiteTemplate {
+            name: "Google",
+            path: "/search",
+            query: &[("q", "{text}")],
+            title: "{text} - Google Search",
+        },
+        "www.bing.com" => SiteTemplate {
+            name: "Bing",
+            path: "/search",
+            query: &[("q", "{text}"), ("form", "QBLH")],
+            title: "{text} - Search",
+        },
+        "duckduckgo.com" => SiteTemplate {
+            name: "DuckDuckGo",
+            path: "/",
+            query: &[("q", "{text}"), ("ia", "web")],
+            title: "{text} at DuckDuckGo",
+        },
+        "www.google.co.uk" => SiteTemplate {
+            name: "Google",
+            path: "/search",
+            query: &[("q", "{text}")],
+            title: "{text} - Google Search",
+        },
+        "www.google.de" => SiteTemplate {
+            name: "Google",
+            path: "/search",
+            query: &[("q", "{text}")],
+            title: "{text} - Google Suche",
+        },
+        "www.google.fr" => SiteTemplate {
+            name: "Google",
+            path: "/search",
+            query: &[("q", "{text}")],
+            title: "{text} - Recherche Google",
+        },
+        "www.ecosia.org" => SiteTemplate {
+            name: "Ecosia",
+            path: "/search",
+            query: &[("q", "{text}")],
+            title: "{text} - Ecosia",
+        },
+        "www.qwant.com" => SiteTemplate {
+            name: "Qwant",
+            path: "/",
+            query: &[("q", "{text}"), ("t", "web")],
+            title: "{text} - Qwant",
+        },
+
+        // Video
+        "www.youtube.com" => SiteTemplate {
+            name: "YouTube",
+            path: "/watch",
+            query: &[("v", "{b64:11}")],
+            title: "{text} - YouTube",
+        },
+        "vimeo.com" => SiteTemplate {
+            name: "Vimeo",
+            path: "/{num:9}",
+            query: &[],
+            title: "{text} on Vimeo",
+        },
+        "www.ardmediathek.de" => SiteTemplate {
+            name: "ARD Mediathek",
+            path: "/video/{slug}/{b64:24}",
+            query: &[],
+            title: "{text} - {name}",
+        },
+        "www.dailymotion.com" => SiteTemplate {
+            name: "Dailymotion",
+            path: "/video/x{b36:6}",
+            query: &[],
+            title: "{text} - Vidéo Dailymotion",
+        },
+
+        // Shopping
+        "www.amazon.com" => SiteTemplate {
+            name: "Amazon.com",
+            path: "/{Slug}/dp/B0{ALNUM:8}",
+            query: &[],
+            title: "Amazon.com: {text}",
+        },
+        "www.ebay.com" => SiteTemplate {
+            name: "eBay",
+            path: "/itm/{num:12}",
+            query: &[],
+            title: "{text} | eBay",
+        },
+        "www.etsy.com" => SiteTemplate {
+            name: "Etsy",
+            path: "/listing/{num:10}/{slug}",
+            query: &[],
+            title: "{text} - Etsy",
+        },
+        "www.walmart.com" => SiteTemplate {
+            name: "Walmart.com",
+            path: "/ip/{slug}/{num:9}",
+            query: &[],
+            title: "{text} - Walmart.com",
+        },
+        "www.bestbuy.com" => SiteTemplate {
+            name: "Best Buy",
+            path: "/site/{slug}/{num:7}.p",
+            query: &[],
+            title: "{text} - Best Buy",
+        },
+        "www.amazon.co.uk" => SiteTemplate {
+            name: "Amazon.co.uk",
+            path: "/{Slug}/dp/B0{ALNUM:8}",
+            query: &[],
+            title: "{text} : Amazon.co.uk",
+        },
+        "www.amazon.de" => SiteTemplate {
+            name: "Amazon.de",
+            path: "/{Slug}/dp/B0{ALNUM:8}",
+            query: &[],
+            title: "{text} : Amazon.de",
+        },
+        "www.amazon.fr" => SiteTemplate {
+            name: "Amazon.fr",
+            path: "/{Slug}/dp/B0{ALNUM:8}",
+            query: &[],
+            title: "{text} : Amazon.fr",
+        },
+        "www.ebay.co.uk" | "www.ebay.de" | "www.ebay.fr" => SiteTemplate {
+            name: "eBay",
+            path: "/itm/{num:12}",
+            query: &[],
+            title: "{text} | eBay",
+        },
+        "www.argos.co.uk" => SiteTemplate {
+            name: "Argos",
+            path: "/product/{num:7}",
+            query: &[],
+            title: "Buy {text} | {name}",
+        },
+        "www.johnlewis.com" => SiteTemplate {
+            name: "John Lewis & Partners",
+            path: "/{slug}/p{num:9}",
+            query: &[],
+            title: "{text} | {name}",
+        },
+        "www.otto.de" => SiteTemplate {
+            name: "OTTO",
+            path: "/p/{slug}-{ALNUM:9}/",
+            query: &[],
+            title: "{text} online kaufen | {name}",
+        },
+        "www.zalando.de" => SiteTemplate {
+            name: "Zalando",
+            path: "/{slug}-{ALNUM:9}.html",
+            query: &[],
+            title: "{text} | {name}",
+        },
+        "www.idealo.de" => SiteTemplate {
+            name: "idealo",
+            path: "/preisvergleich/OffersOfProduct/{num:7}_-{Slug}.html",
+            query: &[],
+            title: "{text} ab € Preisvergleich bei {name}.de",
+        },
+        "www.leboncoin.fr" => SiteTemplate {
+            name: "leboncoin",
+            path: "/ad/{slug}/{num:10}",
+            query: &[],
+            title: "{text} - {name}",
+        },
+        "www.cdiscount.com" => SiteTemplate {
+            name: "Cdiscount",
+            path: "/{slug}/f-{num:7}-{alnum:12}.html",
+            query: &[],
+            title: "{text} - {name}",
+        },
+        "www.fnac.com" => SiteTemplate {
+            name: "Fnac",
+            path: "/a{num:8}/{Slug}",
+            query: &[],
+            title: "{text} - Achat & prix | {name}",
+        },
+
+        // News
+        "www.bbc.com" => SiteTemplate {
+            name: "BBC News",
+            path: "/news/{section}-{num:8}",
+            query: &[],
+            title: "{text} - {name}",
+        },
+        "www.cnn.com" => SiteTemplate {
+            name: "CNN",
+            path: "/{yyyy}/{mm}/{dd}/{section}/{slug}/index.html",
+            query: &[],
+            title: "{text} | {name}",
+        },
+        "www.reuters.com" => SiteTemplate {
+            name: "Reuters",
+            path: "/{section}/{slug}-{yyyy}-{mm}-{dd}/",
+            query: &[],
+            title: "{text} | {name}",
+        },
+        "www.theguardian.com" => SiteTemplate {
+            name: "The Guardian",
+            path: "/{section}/{yyyy}/{mon}/{dd}/{slug}",
+            query: &[],
+            title: "{text} | {name}",
+        },
+        "www.nytimes.com" => SiteTemplate {
+            name: "The New York Times",
+            path: "/{yyyy}/{mm}/{dd}/{section}/{slug}.html",
+            query: &[],
+            title: "{text} - {name}",
+        },
+        "www.bbc.co.uk" => SiteTemplate {
+            name: "BBC News",
+            path: "/news/{section}-{num:8}",
+            query: &[],
+            title: "{text} - {name}",
+        },
+        "www.thetimes.co.uk" => SiteTemplate {
+            name: "The Times",
+            path: "/article/{slug}-{alnum:9}",
+            query: &[],
+            title: "{text} | {name}",
+        },
+        "www.independent.co.uk" => SiteTemplate {
+            name: "The Independent",
+            path: "/{section}/{slug}-b{num:7}.html",
+            query: &[],
+            title: "{text} | {name}",
+        },
+        "www.telegraph.co.uk" => SiteTemplate {
+            name: "The Telegraph",
+            path: "/{section}/{yyyy}/{mm}/{dd}/{slug}/",
+            query: &[],
+            title: "{text}",
+        },
+        "www.spiegel.de" => SiteTemplate {
+            name: "DER SPIEGEL",
+            path: "/{slug}-a-{alnum:8}-{alnum:4}-{alnum:4}",
+            query: &[],
+            title: "{text} - {name}",
+        },
+        "www.zeit.de" => SiteTemplate {
+            name: "ZEIT ONLINE",
+            path: "/{yyyy}/{num:2}/{slug}",
+            query: &[],
+            title: "{text} | {name}",
+        },
+        "www.tagesschau.de" => SiteTemplate {
+            name: "tagesschau.de",
+            path: "/{slug}-{num:3}.html",
+            query: &[],
+            title: "{text} | {name}",
+        },
+        "www.faz.net" => SiteTemplate {
+            name: "FAZ",
+            path: "/aktuell/{slug}-{num:9}.html",
+            query: &[],
+            title: "{text} | {name}",
+        },
+        "www.sueddeutsche.de" => SiteTemplate {
+            name: "Süddeutsche Zeitung",
+            path: "/{slug}-{num:1}.{num:7}",
+            query: &[],
+            title: "{text} - SZ.de",
+        },
+        "www.lemonde.fr" => SiteTemplate {
+            name: "Le Monde",
+            path: "/article/{yyyy}/{mm}/{dd}/{slug}_{num:7}_{num:4}.html",
+            query: &[],
+            title: "{text}",
+        },
+        "www.lefigaro.fr" => SiteTemplate {
+            name: "Le Figaro",
+            path: "/actualite/{slug}-{yyyy}{mm}{dd}",
+            query: &[],
+            title: "{text} | {name}",
+        },
+        "www.liberation.fr" => SiteTemplate {
+            name: "Libération",
+            path: "/{slug}_{ALNUM:26}/",
+            query: &[],
+            title: "{text} – {name}",
+        },
+        "www.francetvinfo.fr" => SiteTemplate {
+            name: "Franceinfo",
+            path: "/{slug}_{num:7}.html",
+            query: &[],
+            title: "{text} | {name}",
+        },
+        "www.ouest-france.fr" => SiteTemplate {
+            name: "Ouest-France",
+            path: "/{slug}-{alnum:8}-{alnum:4}-{alnum:4}",
+            query: &[],
+            title: "{text} | {name}",
+        },
+
+        // Reference
+        "en.wikipedia.org" => SiteTemplate {
+            name: "Wikipedia",
+            path: "/wiki/{wiki}",
+            query: &[],
+            title: "{text} - {name}",
+        },
+        "www.britannica.com" => SiteTemplate {
+            name: "Britannica",
+            path: "/topic/{slug}",
+            query: &[],
+            title: "{text} | {name}",
+        },
+        "scholar.google.com" => SiteTemplate {
+            name: "Google Scholar",
+            path: "/scholar",
+            query: &[("hl", "en"), ("q", "{text}")],
+            title: "{text} - {name}",
+        },
+        "scholar.google.co.uk" => SiteTemplate {
+            name: "Google Scholar",
+            path: "/scholar",
+            query: &[("hl", "en"), ("q", "{text}")],
+            title: "{text} - {name}",
+        },
+        "scholar.google.de" => SiteTemplate {
+            name: "Google Scholar",
+            path: "/scholar",
+            query: &[("hl", "de"), ("q", "{text}")],
+            title: "{text} - {name}",
+        },
+        "scholar.google.fr" => SiteTemplate {
+            name: "Google Scholar",
+            path: "/scholar",
+            query: &[("hl", "fr"), ("q", "{text}")],
+            title: "{text} - {name}",
+        },
+        "de.wikipedia.org" => SiteTemplate {
+            name: "Wikipedia",
+            path: "/wiki/{wiki}",
+            query: &[],
+            title: "{text} – {name}",
+        },
+        "fr.wikipedia.org" => SiteTemplate {
+            name: "Wikipédia",
+            path: "/wiki/{wiki}",
+            query: &[],
+            title: "{text} — {name}",
+        },
+        "www.duden.de" => SiteTemplate {
+            name: "Duden",
+            path: "/rechtschreibung/{wiki}",
+            query: &[],
+            title: "Duden | {text} | Rechtschreibung, Bedeutung, Definition",
+        },
+        "www.larousse.fr" => SiteTemplate {
+            name: "Larousse",
+            path: "/encyclopedie/divers/{wiki}/{num:6}",
+            query: &[],
+            title: "{text} - {name}",
+        },
+        "arxiv.org" => SiteTemplate {
+            name: "arXiv",
+            path: "/abs/{yy}{mm}.{num:5}",
+            query: &[],
+            title: "{text} | {name}",
+        },
+
+        // Interest sites
+        "techcrunch.com" => SiteTemplate {
+            name: "TechCrunch",
+            path: "/{yyyy}/{mm}/{dd}/{slug}/",
+            query: &[],
+            title: "{text} | {name}",
+        },
+        "www.theverge.com" => SiteTemplate {
+            name: "The Verge",
+            path: "/news/{num:6}/{slug}",
+            query: &[],
+            title: "{text} | {name}",
+        },
+        "arstechnica.com" => SiteTemplate {
+            name: "Ars Technica",
+            path: "/{section}/{yyyy}/{mm}/{slug}/",
+            query: &[],
+            title: "{text} - {name}",
+        },
+        "www.ign.com" => SiteTemplate {
+            name: "IGN",
+            path: "/articles/{slug}",
+            query: &[],
+            title: "{text} - {name}",
+        },
+        "www.gamespot.com" => SiteTemplate {
+            name: "GameSpot",
+            path: "/articles/{slug}/1100-{num:7}/",
+            query: &[],
+            title: "{text} - {name}",
+        },
+        "www.polygon.com" => SiteTemplate {
+            name: "Polygon",
+            path: "/{num:8}/{slug}",
+            query: &[],
+            title: "{text} - {name}",
+        },
+        "www.espn.com" => SiteTemplate {
+            name: "ESPN",
+            path: "/{section}/story/_/id/{num:8}/{slug}",
+            query: &[],
+            title: "{text} - {name}",
+        },
+        "bleacherreport.com" => SiteTemplate {
+            name: "Bleacher Report",
+            path: "/articles/{num:8}-{slug}",
+            query: &[],
+            title: "{text} | {name}",
+        },
+        "www.si.com" => SiteTemplate {
+            name: "Sports Illustrated",
+            path: "/{section}/{slug}",
+            query: &[],
+            title: "{text} - {name}",
+        },
+        "www.allrecipes.com" => SiteTemplate {
+            name: "Allrecipes",
+            path: "/recipe/{num:5}/{slug}/",
+            query: &[],
+            title: "{text} Recipe",
+        },
+        "www.foodnetwork.com" => SiteTemplate {
+            name: "Food Network",
+            path: "/recipes/{slug}-{num:7}",
+            query: &[],
+            title: "{text} | {name}",
+        },
+        "www.bonappetit.com" => SiteTemplate {
+            name: "Bon Appétit",
+            path: "/recipe/{slug}",
+            query: &[],
+            title: "{text} | {name}",
+        },
+
+        _ => GENERIC,
+    }
+}
+
+/// Human-readable site name derived from a host, e.g. `www.example.com` -> `Example`
+pub fn site_name(host: &str) -> String {
+    let label = host
+        .split('.')
+        .find(|label| *label != "www")
+        .unwrap_or(host);
+    capitalize(label)
+}
+
+/// Expand every `{placeholder}` in a pattern
+fn expand<R: Rng>(pattern: &str, ctx: &TemplateContext, rng: &mut R) -> String {
+    let mut out = String::new();
+    let mut rest = pattern;
+
+    while let Some(start) = rest.find('{') {
+        out.push_str(&rest[..start]);
+        let Some(len) = rest[start..].find('}') else {
+            break;
+        };
+        let placeholder = &rest[start + 1..start + len];
+        out.push_str(&expand_placeholder(placeholder, ctx, rng));
+        rest = &rest[start + len + 1..];
+    }
+
+    out.push_str(rest);
+    out
+}
+
+fn expand_placeholder<R: Rng>(placeholder: &str, ctx: &TemplateContext, rng: &mut R) -> String {
+    let (name, arg) = match placeholder.split_once(':') {
+        Some((name, arg)) => (name, arg.parse::<usize>().unwrap_or(8)),
+        None => (placeholder, 0),
+    };
+
+    match name {
+        "text" => ctx.text.to_string(),
+        "slug" => slug(ctx.text),
+        "Slug" => title_slug(ctx.text),
+        "wiki" => wiki_title(ctx.text),
+        "section" => ctx.section.to_string(),
+        "yyyy" => format!("{:04}", ctx.date.year()),
+        "yy" => format!("{:02}", ctx.date.year() % 100),
+        "mm" => format!("{:02}", ctx.date.month()),
+        "dd" => format!("{:02}", ctx.date.day()),
+        "mon" => ctx.date.format("%b").to_string().to_lowercase(),
+        "num" => {
+            // Leading zeros would make IDs look padded rather than sequential
+            let first = rng.gen_range(1..=9).to_string();
+            first + &random_chars(b"0123456789", arg.saturating_sub(1), rng)
+        }
+        "alnum" => random_chars(b"abcdefghijklmnopqrstuvwxyz0123456789", arg, rng),
+        "ALNUM" => random_chars(b"ABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789", arg, rng),
+        "b36" => random_chars(b"0123456789abcdefghijklmnopqrstuvwxyz", arg, rng),
+        "b64" => random_chars(
+            b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_",
+            arg,
+            rng,
+        ),
+        // Unknown placeholders are left as written so mistakes show up in tests
+        _ => format!("{{{}}}", placeholder),
+    }
+}
+
+fn random_chars<R: Rng>(alphabet: &[u8], len: usize, rng: &mut R) -> String {
+    (0..len)
+        .map(|_| alphabet[rng.gen_range(0..alphabet.len())] as char)
+        .collect()
+}
+
+/// Split text into slug words, dropping apostrophes so "Chef's" stays one word
+fn slug_words(text: &str) -> Vec<String> {
+    text.chars()
+        .filter(|c| *c != '\'' && *c != '’')
+        .collect::<String>()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Lowercase hyphenated slug, e.g. "Player Breaks Record!" -> "player-breaks-record"
+pub fn slug(text: &str) -> String {
+    slug_words(text)
+        .iter()
+        .take(8)
+        .map(|word| word.to_lowercase())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+fn title_slug(text: &str) -> String {
+    slug_words(text)
+        .iter()
+        .map(|word| capitalize(word))
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+fn wiki_title(text: &str) -> String {
+    capitalize(&text.split_whitespace().collect::<Vec<_>>().join("_"))
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::SmallRng;
+    use rand::SeedableRng;
+
+    fn context(text: &str) -> TemplateContext<'_> {
+        TemplateContext {
+            text,
+            section: "technology",
+            date: NaiveDate::from_ymd_opt(2024, 3, 7).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_search_query_is_percent_encoded() {
+        let mut rng = SmallRng::seed_from_u64(1);
+        let url = site_template("www.google.com")
+            .render("www.google.com", &context("c++ & rust über"), &mut rng)
+            .unwrap();
+
+        assert_eq!(
+            url.as_str(),
+            "https://www.google.com/search?q=c%2B%2B+%26+rust+%C3%BCber"
+        );
+        let (_, query) = url.query_pairs().next().unwrap();
+        assert_eq!(query, "c++ & rust über");
+    }
+
+    #[test]
+    fn test_dated_path_and_ids() {
+        let mut rng = SmallRng::seed_from_u64(2);
+        let ctx = context("Breakthrough in AI Research");
+
+        let nyt = site_template("www.nytimes.com")
+            .render("www.nytimes.com", &ctx, &mut rng)
+            .unwrap();
+        assert_eq!(
+            nyt.path(),
+            "/2024/03/07/technology/breakthrough-in-ai-research.html"
+        );
+
+        let guardian = site_template("www.theguardian.com")
+            .render("www.theguardian.com", &ctx, &mut rng)
+            .unwrap();
+        assert_eq!(
+            guardian.path(),
+            "/technology/2024/mar/07/breakthrough-in-ai-research"
+        );
+
+        let bbc = site_template("www.bbc.com")
+            .render("www.bbc.com", &ctx, &mut rng)
+            .unwrap();
+        let id = bbc.path().trim_start_matches("/news/technology-");
+        assert_eq!(id.len(), 8);
+        assert!(id.chars().all(|c| c.is_ascii_digit()));
+    }
+
+    #[test]
+    fn test_slug_rules() {
+        assert_eq!(
+            slug("Chef's Special: Quick & Easy!"),
+            "chefs-special-quick-easy"
+        );
+        assert_eq!(title_slug("wireless headphones"), "Wireless-Headphones");
+        assert_eq!(wiki_title("quantum physics"), "Quantum_physics");
+        assert_eq!(site_name("www.example.com"), "Example");
+    }
+
+    #[test]
+    fn test_validate_names_the_problem() {
+        assert!(validate("https://example.com/a").is_ok());
+        assert_eq!(
+            validate("ftp://example.com/a"),
+            Err(UrlError::Scheme("ftp".to_string()))
+        );
+        assert_eq!(
+            validate("javascript:alert(1)"),
+            Err(UrlError::Scheme("javascript".to_string()))
+        );
+        assert_eq!(
+            validate("not a url"),
+            Err(UrlError::Parse(url::ParseError::RelativeUrlWithoutBase))
+        );
+    }
+
+    #[test]
+    fn test_recent_dates_follow_today() {
+        let today = NaiveDate::from_ymd_opt(2024, 3, 7).unwrap();
+        let mut rng = SmallRng::seed_from_u64(4);
+        for _ in 0..50 {
+            let ctx = TemplateContext::recent("x", "news", today, &mut rng);
+            assert!(ctx.date <= today && today - ctx.date < Duration::days(30));
+        }
+    }
+
+    #[test]
+    fn test_generic_fallback_encodes_non_ascii() {
+        let mut rng = SmallRng::seed_from_u64(3);
+        let template = site_template("unknown.example");
+        let url = template
+            .render("unknown.example", &context("Café Guide"), &mut rng)
+            .unwrap();
+
+        assert_eq!(url.as_str(), "https://unknown.example/caf%C3%A9-guide");
+        assert_eq!(
+            template.render_title("unknown.example", "Café Guide"),
+            "Café Guide | Unknown"
+        );
+    }
+}
@@ -0,0 +1,216 @@
+use crate::migration::SCHEMA_VERSION;
+use crate::profile::{EducationLevel, OccupationCategory, Profile};
+use crate::spec::{self, MAX_INTERESTS};
+use crate::taxonomy::Taxonomy;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Allowance for clock skew before a creation time counts as in the future
+const CLOCK_SKEW_SECONDS: i64 = 24 * 60 * 60;
+
+/// How serious a [`ValidationIssue`] is, least serious first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Severity {
+    /// Usable, but not a person anyone would believe in
+    Implausible,
+    /// The profile can't be used as it is
+    Error,
+}
+
+/// One problem with a profile, tied to the field it concerns
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ValidationIssue {
+    pub severity: Severity,
+    /// Path into the serialized profile, e.g. `interests[2].category`
+    pub field: String,
+    pub message: String,
+}
+
+impl ValidationIssue {
+    pub fn error(field: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Error,
+            field: field.into(),
+            message: message.into(),
+        }
+    }
+
+    pub fn implausible(field: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Implausible,
+            field: field.into(),
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for ValidationIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.field.is_empty() {
+            write!(f, "{}", self.message)
+        } else {
+            write!(f, "{}: {}", self.field, self.message)
+        }
+    }
+}
+
+/// Every problem with `profile`, in field order
+pub(crate) fn check(profile: &Profile) -> Vec<ValidationIssue> {
+    let mut issues = Vec::new();
+
+    if profile.schema_version > SCHEMA_VERSION {
+        issues.push(ValidationIssue::error(
+            "schema_version",
+            format!(
+                "{} is newer than the supported {}",
+                profile.schema_version, SCHEMA_VERSION
+            ),
+        ));
+    }
+    if profile.id.trim().is_empty() {
+        issues.push(ValidationIssue::error("id", "is empty"));
+    }
+    if profile.name.trim().is_empty() {
+        issues.push(ValidationIssue::error("name", "is empty"));
+    }
+    check_demographics(profile, &mut issues);
+    check_interests(profile, &mut issues);
+    check_subtopics(profile, &mut issues);
+
+    if profile.created_at > chrono::Utc::now().timestamp() + CLOCK_SKEW_SECONDS {
+        issues.push(ValidationIssue::implausible(
+            "created_at",
+            "is in the future",
+        ));
+    }
+    issues
+}
+
+fn check_demographics(profile: &Profile, issues: &mut Vec<ValidationIssue>) {
+    let demographics = &profile.demographics;
+    let age = demographics.age;
+    if !spec::VALID_AGES.contains(&age) {
+        issues.push(ValidationIssue::error(
+            "demographics.age",
+            format!(
+                "{} is outside {}-{}",
+                age,
+                spec::VALID_AGES.start(),
+                spec::VALID_AGES.end()
+            ),
+        ));
+        // The checks below would only restate this
+        return;
+    }
+
+    let occupation = &demographics.occupation_category;
+    let ages = spec::occupation_ages(occupation);
+    if !ages.contains(&age) {
+        issues.push(ValidationIssue::implausible(
+            "demographics.occupation_category",
+            format!(
+                "{:?} at {} is unusual; expected ages {}-{}",
+                occupation,
+                age,
+                ages.start(),
+                ages.end()
+            ),
+        ));
+    }
+
+    let education = &demographics.education_level;
+    let min_age = spec::education_min_age(education);
+    if age < min_age {
+        issues.push(ValidationIssue::implausible(
+            "demographics.education_level",
+            format!("{:?} before the age of {} is unusual", education, min_age),
+        ));
+    }
+
+    if *occupation == OccupationCategory::Student && *education == EducationLevel::Doctorate {
+        issues.push(ValidationIssue::implausible(
+            "demographics.education_level",
+            "a Student already holding a Doctorate is unusual",
+        ));
+    }
+}
+
+fn check_interests(profile: &Profile, issues: &mut Vec<ValidationIssue>) {
+    let interests = &profile.interests;
+    if interests.is_empty() {
+        issues.push(ValidationIssue::error("interests", "has no entries"));
+        return;
+    }
+    if interests.len() > MAX_INTERESTS {
+        issues.push(ValidationIssue::error(
+            "interests",
+            format!(
+                "has {} entries; at most {} are allowed",
+                interests.len(),
+                MAX_INTERESTS
+            ),
+        ));
+    }
+
+    for (i, interest) in interests.iter().enumerate() {
+        let field = format!("interests[{}]", i);
+        if interests[..i]
+            .iter()
+            .any(|earlier| earlier.category == interest.category)
+        {
+            issues.push(ValidationIssue::error(
+                format!("{}.category", field),
+                format!("{:?} is listed more than once", interest.category),
+            ));
+        }
+        if interest.category.is_sensitive() {
+            issues.push(ValidationIssue::error(
+                format!("{}.category", field),
+                format!("{:?} is only used for cover traffic", interest.category),
+            ));
+        }
+        if !interest.weight.is_finite() || interest.weight < 0.0 {
+            issues.push(ValidationIssue::error(
+                format!("{}.weight", field),
+                format!("{} is not a non-negative number", interest.weight),
+            ));
+        }
+    }
+
+    if interests.iter().all(|interest| interest.weight == 0.0) {
+        issues.push(ValidationIssue::error(
+            "interests",
+            "every weight is zero, so no interest would ever be browsed",
+        ));
+    }
+}
+
+fn check_subtopics(profile: &Profile, issues: &mut Vec<ValidationIssue>) {
+    let taxonomy = Taxonomy::builtin();
+    for (i, id) in profile.subtopics.iter().enumerate() {
+        let field = format!("subtopics[{}]", i);
+        if profile.subtopics[..i].contains(id) {
+            issues.push(ValidationIssue::error(
+                field,
+                format!("{} is listed more than once", id),
+            ));
+            continue;
+        }
+        match taxonomy.get(id) {
+            None => issues.push(ValidationIssue::error(
+                field,
+                format!("{} is not a known subtopic", id),
+            )),
+            Some(subtopic) if !profile.has_interest(&subtopic.category) => {
+                issues.push(ValidationIssue::implausible(
+                    field,
+                    format!(
+                        "{} belongs to {:?}, which isn't one of the profile's interests",
+                        id, subtopic.category
+                    ),
+                ))
+            }
+            Some(_) => {}
+        }
+    }
+}
@@ -1,5 +1,8 @@
 use doubletrack_core::*;
 
+/// Midnight UTC on Wednesday 2026-10-07
+const WEDNESDAY: i64 = 1_791_331_200;
+
 #[test]
 fn test_profile_generation_deterministic() {
     // Same seed should produce same profile
@@ -187,3 +190,736 @@ fn test_browsing_style_variety() {
     // Should have at least 2 different browsing styles
     assert!(styles.len() >= 2);
 }
+
+#[test]
+fn test_activity_urls_parse() {
+    let mut gen = ProfileGenerator::new(Some(7));
+    let profile = gen.generate();
+    let mut simulator = ActivitySimulator::new(profile);
+
+    for activity in simulator.generate_activities_from(WEDNESDAY, 24) {
+        let url = url::Url::parse(&activity.url).expect("generated URL should parse");
+        assert!(url.host_str().is_some());
+        assert!(!activity.url.contains(' '));
+    }
+}
+
+#[test]
+fn test_cover_queries_match_real_query_shape() {
+    use rand::SeedableRng;
+
+    let generator = InterestUrlGenerator::new();
+    let grammar = QueryGrammar::new(2024);
+    let mut rng = rand::rngs::SmallRng::seed_from_u64(5);
+
+    let real = "how do i fix a leaky faucet";
+    let target = grammar.features(real);
+    let cover = generator.cover_queries(real, None, 8, &mut rng);
+
+    assert_eq!(cover.len(), 8);
+    for item in &cover {
+        assert!(!matches!(item.category, InterestCategory::HomeImprovement));
+        let features = grammar.features(&item.query);
+        assert_eq!(features.form, target.form, "{}", item.query);
+        assert!(
+            features.tokens.abs_diff(target.tokens) <= 2,
+            "{}",
+            item.query
+        );
+    }
+}
+
+#[test]
+fn test_ingested_feed_content_is_sampled() {
+    let rss = r#"<rss version="2.0"><channel>
+        <item><title>Ten tomatoes to try this spring</title><link>https://gardens.example/tomatoes</link></item>
+        <item><title>Pruning roses in winter</title><link>https://gardens.example/roses</link></item>
+    </channel></rss>"#;
+
+    let mut url_generator = InterestUrlGenerator::new();
+    let report = url_generator
+        .ingest(&FeedSource {
+            category: InterestCategory::Gardening,
+            xml: rss.to_string(),
+        })
+        .unwrap();
+    assert_eq!(report.format, FeedFormat::Rss);
+    assert_eq!(report.added, 2);
+
+    let mut gen = ProfileGenerator::new(Some(42));
+    let mut profile = gen.generate();
+    profile.interests = InterestAffinity::uniform([InterestCategory::Gardening]);
+    let mut simulator = ActivitySimulator::new(profile)
+        .with_seed(2)
+        .with_url_generator(url_generator);
+
+    let activities = simulator.generate_activities_from(WEDNESDAY, 48);
+    assert!(activities
+        .iter()
+        .any(|a| a.url.starts_with("https://gardens.example/")));
+}
+
+#[test]
+fn test_safety_policy_regenerates_denied_urls() {
+    let policy = SafetyPolicy {
+        deny_domains: vec![
+            "google.com".to_string(),
+            "bing.com".to_string(),
+            "duckduckgo.com".to_string(),
+        ],
+        ..SafetyPolicy::default()
+    };
+
+    let mut gen = ProfileGenerator::new(Some(42));
+    let profile = gen.generate();
+    let expected = ActivitySimulator::new(profile.clone())
+        .with_seed(5)
+        .generate_activities_from(WEDNESDAY, 24)
+        .len();
+    let mut simulator = ActivitySimulator::new(profile)
+        .with_seed(5)
+        .with_policy(policy.clone());
+    let activities = simulator.generate_activities_from(WEDNESDAY, 24);
+
+    // Filtered URLs are replaced rather than dropped
+    assert_eq!(activities.len(), expected);
+    for activity in &activities {
+        assert_ne!(
+            policy.check_str(&activity.url),
+            Verdict::Deny,
+            "{}",
+            activity.url
+        );
+        assert!(!activity.url.contains("google.com"));
+    }
+}
+
+#[test]
+fn test_subtopics_narrow_generated_content() {
+    let mut gen = ProfileGenerator::new(Some(42));
+    let profile = gen.generate();
+    let taxonomy = Taxonomy::builtin();
+    assert!(!profile.subtopics.is_empty());
+    for id in &profile.subtopics {
+        let subtopic = taxonomy.get(id).unwrap();
+        assert!(subtopic.is_leaf());
+        assert!(profile.has_interest(&subtopic.category));
+    }
+
+    let mut profile = profile;
+    profile.interests = InterestAffinity::uniform([InterestCategory::Programming]);
+    profile.subtopics = vec!["programming/rust".to_string()];
+    let rust = taxonomy.get("programming/rust").unwrap();
+    let mut simulator = ActivitySimulator::new(profile);
+    let activities = simulator.generate_activities_from(WEDNESDAY, 48);
+
+    assert!(activities.iter().any(|a| rust
+        .domains
+        .iter()
+        .any(|host| a.url.starts_with(&format!("https://{}/", host)))));
+    // Profiles saved before subtopics existed still load
+    let json = r#"{"id":"p","name":"A B","demographics":{"age":30,"gender":"Female","location_type":"Urban","occupation_category":"Technology","education_level":"Bachelor"},"interests":["Gaming"],"browsing_style":"Casual","activity_level":"Low","created_at":0}"#;
+    let old: Profile = serde_json::from_str(json).unwrap();
+    assert!(old.subtopics.is_empty());
+}
+
+#[test]
+fn test_interest_affinities_are_skewed_and_sampled() {
+    let mut gen = ProfileGenerator::new(Some(42));
+    let profile = gen.generate();
+    let total: f64 = profile.interests.iter().map(|i| i.weight).sum();
+    assert!((total - 1.0).abs() < 1e-9);
+    let top = profile
+        .interests
+        .iter()
+        .map(|i| i.weight)
+        .fold(0.0, f64::max);
+    assert!(top > 1.0 / profile.interests.len() as f64);
+
+    let mut profile = profile;
+    profile.interests = vec![
+        InterestAffinity {
+            category: InterestCategory::Cooking,
+            weight: 0.9,
+        },
+        InterestAffinity {
+            category: InterestCategory::Gaming,
+            weight: 0.1,
+        },
+    ];
+    let activities = ActivitySimulator::new(profile.clone())
+        .with_drift(DriftConfig::disabled())
+        .generate_activities_from(WEDNESDAY, 96);
+    let cooking = activities
+        .iter()
+        .filter(|a| a.interest_category == Some(InterestCategory::Cooking))
+        .count();
+    assert!(cooking as f64 > activities.len() as f64 * 0.75);
+
+    // Neighbours in the interest graph are sampled by affinity too, not by
+    // drifting from one to the other
+    profile.interests = vec![
+        InterestAffinity {
+            category: InterestCategory::Technology,
+            weight: 0.9,
+        },
+        InterestAffinity {
+            category: InterestCategory::Gaming,
+            weight: 0.1,
+        },
+    ];
+    let activities = ActivitySimulator::new(profile.clone())
+        .with_seed(8)
+        .with_drift(DriftConfig::disabled())
+        .generate_activities_from(WEDNESDAY, 96);
+    let technology = activities
+        .iter()
+        .filter(|a| a.interest_category == Some(InterestCategory::Technology))
+        .count();
+    assert!(technology as f64 > activities.len() as f64 * 0.8);
+
+    // Leaving an interest can also start over from the affinities, which
+    // following the graph alone never does
+    let restarting = ActivitySimulator::new(profile)
+        .with_seed(8)
+        .with_drift(DriftConfig {
+            stay_probability: 0.0,
+            restart_probability: 0.5,
+            ..DriftConfig::default()
+        })
+        .generate_activities_from(WEDNESDAY, 96);
+    assert!(restarting.windows(2).any(|pair| {
+        pair[1].timestamp - pair[0].timestamp <= 30 * 60
+            && pair[0].interest_category == pair[1].interest_category
+    }));
+
+    // The older shape, a list of bare categories, loads with equal weights
+    let old: Vec<InterestAffinity> = serde_json::from_str::<Profile>(
+        r#"{"id":"p","name":"A B","demographics":{"age":30,"gender":"Female","location_type":"Urban","occupation_category":"Technology","education_level":"Bachelor"},"interests":["Gaming","Music"],"browsing_style":"Casual","activity_level":"Low","created_at":0}"#,
+    )
+    .unwrap()
+    .interests;
+    assert_eq!(
+        old,
+        InterestAffinity::uniform([InterestCategory::Gaming, InterestCategory::Music])
+    );
+}
+
+#[test]
+fn test_drift_stays_within_configured_bounds() {
+    let mut gen = ProfileGenerator::new(Some(7));
+    let profile = gen.generate();
+
+    let contained = ActivitySimulator::new(profile.clone())
+        .with_drift(DriftConfig {
+            stay_probability: 0.2,
+            restart_probability: 0.3,
+            outside_weight: 0.0,
+            max_hops_outside: 5,
+        })
+        .generate_activities_from(WEDNESDAY, 72);
+    for activity in &contained {
+        assert!(profile.has_interest(activity.interest_category.as_ref().unwrap()));
+    }
+
+    let sticky = ActivitySimulator::new(profile)
+        .with_drift(DriftConfig {
+            stay_probability: 1.0,
+            ..DriftConfig::default()
+        })
+        .generate_activities_from(WEDNESDAY, 72);
+    for pair in sticky.windows(2) {
+        if pair[1].timestamp - pair[0].timestamp <= 30 * 60 {
+            assert_eq!(pair[0].interest_category, pair[1].interest_category);
+        }
+    }
+}
+
+#[test]
+fn test_profile_spec_constrains_generation() {
+    let spec = ProfileSpec::new()
+        .age_range(30, 40)
+        .location_type(LocationType::Rural)
+        .occupation(OccupationCategory::Healthcare)
+        .require_interest(InterestCategory::Gardening)
+        .forbid_interest(InterestCategory::Gaming)
+        .forbid_interest(InterestCategory::Politics)
+        .activity_level(ActivityLevel::Low);
+
+    for seed in 0..50 {
+        let profile = ProfileGenerator::new(Some(seed))
+            .generate_with(&spec)
+            .unwrap();
+        assert!(profile.is_valid());
+        assert!((30..=40).contains(&profile.demographics.age));
+        assert_eq!(profile.demographics.location_type, LocationType::Rural);
+        assert_eq!(
+            profile.demographics.occupation_category,
+            OccupationCategory::Healthcare
+        );
+        assert_eq!(profile.activity_level, ActivityLevel::Low);
+        assert!(profile.has_interest(&InterestCategory::Gardening));
+        assert!(!profile.has_interest(&InterestCategory::Gaming));
+        assert!(!profile.has_interest(&InterestCategory::Politics));
+    }
+
+    let impossible = ProfileSpec::new()
+        .age(70)
+        .occupation(OccupationCategory::Student);
+    assert!(matches!(
+        ProfileGenerator::new(Some(1)).generate_with(&impossible),
+        Err(SpecError::OccupationAge { .. })
+    ));
+}
+
+#[test]
+fn test_contrasting_profiles_avoid_the_real_user() {
+    let real = ProfileSummary {
+        age_band: Some(AgeBand::Adult),
+        occupation: Some(OccupationCategory::Technology),
+        top_interests: vec![
+            InterestCategory::Programming,
+            InterestCategory::Gaming,
+            InterestCategory::Technology,
+        ],
+    };
+
+    for seed in 0..30 {
+        let persona = ProfileGenerator::new(Some(seed)).generate_contrasting(&real);
+        assert!(persona.is_valid());
+        assert_eq!(AgeBand::of(persona.demographics.age), AgeBand::Senior);
+        assert_ne!(
+            persona.demographics.occupation_category,
+            OccupationCategory::Technology
+        );
+        for interest in &real.top_interests {
+            assert!(!persona.has_interest(interest));
+        }
+        assert!(real.distance(&persona) > 0.8, "{}", real.distance(&persona));
+
+        // At least as far as an unconstrained profile from the same seed
+        let random = ProfileGenerator::new(Some(seed)).generate();
+        assert!(real.distance(&persona) >= real.distance(&random));
+    }
+
+    // Knowing nothing still yields an ordinary profile
+    let persona = ProfileGenerator::new(Some(3)).generate_contrasting(&ProfileSummary::default());
+    assert!(persona.is_valid());
+}
+
+#[test]
+fn test_population_covers_and_spreads() {
+    let population = ProfileGenerator::new(Some(11)).generate_population(12, 1.0);
+    assert_eq!(population.len(), 12);
+
+    for occupation in &OccupationCategory::ALL {
+        assert!(population
+            .iter()
+            .any(|p| &p.demographics.occupation_category == occupation));
+    }
+    for band in &AgeBand::ALL {
+        assert!(population
+            .iter()
+            .any(|p| &AgeBand::of(p.demographics.age) == band));
+    }
+    for style in &BrowsingStyle::ALL {
+        assert!(population.iter().any(|p| &p.browsing_style == style));
+    }
+
+    // Spread out further than the same number of independent draws
+    let mean_nearest = |profiles: &[Profile]| {
+        profiles
+            .iter()
+            .enumerate()
+            .map(|(i, a)| {
+                profiles
+                    .iter()
+                    .enumerate()
+                    .filter(|(j, _)| *j != i)
+                    .map(|(_, b)| a.distance(b))
+                    .fold(f64::INFINITY, f64::min)
+            })
+            .sum::<f64>()
+            / profiles.len() as f64
+    };
+    let independent = ProfileGenerator::new(Some(11)).generate_population(12, 0.0);
+    assert!(mean_nearest(&population) > mean_nearest(&independent));
+    assert_eq!(population[0].distance(&population[0]), 0.0);
+}
+
+#[test]
+fn test_demographics_follow_priors() {
+    let share = |profiles: &[Profile], location: LocationType| {
+        profiles
+            .iter()
+            .filter(|p| p.demographics.location_type == location)
+            .count() as f64
+            / profiles.len() as f64
+    };
+
+    let mut gen = ProfileGenerator::new(Some(5));
+    let profiles: Vec<Profile> = (0..1000).map(|_| gen.generate()).collect();
+    let expected = DemographicPriors::builtin().marginals().location.unwrap();
+    assert!((share(&profiles, LocationType::Urban) - expected[&LocationType::Urban]).abs() < 0.04);
+    // Ages stay feasible for the education and occupation drawn with them
+    for profile in &profiles {
+        if profile.demographics.education_level == EducationLevel::Doctorate {
+            assert!(profile.demographics.age >= 25);
+        }
+        if profile.demographics.occupation_category == OccupationCategory::Retired {
+            assert!(profile.demographics.age >= 50);
+        }
+    }
+
+    let mut rural = DemographicPriors::for_region("GB").unwrap().clone();
+    rural
+        .calibrate(&Marginals {
+            location: Some(
+                [(LocationType::Rural, 0.8), (LocationType::Urban, 0.2)]
+                    .into_iter()
+                    .collect(),
+            ),
+            ..Marginals::default()
+        })
+        .unwrap();
+    let mut gen = ProfileGenerator::new(Some(5)).with_priors(rural);
+    let profiles: Vec<Profile> = (0..500).map(|_| gen.generate()).collect();
+    assert!(share(&profiles, LocationType::Rural) > 0.7);
+    assert_eq!(share(&profiles, LocationType::Suburban), 0.0);
+}
+
+#[test]
+fn test_interest_model_conditions_on_demographics() {
+    let share = |spec: &ProfileSpec, interest: InterestCategory| {
+        let mut gen = ProfileGenerator::new(Some(8));
+        (0..300)
+            .filter(|_| gen.generate_with(spec).unwrap().has_interest(&interest))
+            .count() as f64
+            / 300.0
+    };
+    let young = ProfileSpec::new()
+        .age_range(18, 24)
+        .occupation(OccupationCategory::Service);
+    let old = ProfileSpec::new()
+        .age_range(60, 70)
+        .occupation(OccupationCategory::Service);
+    assert!(share(&young, InterestCategory::Gaming) > share(&old, InterestCategory::Gaming) + 0.15);
+    assert!(share(&old, InterestCategory::News) > share(&young, InterestCategory::News) + 0.15);
+
+    let mut gen = ProfileGenerator::new(Some(8));
+    let mut profile = gen.generate();
+    let log_p = gen.log_probability(&profile);
+    assert!(log_p.is_finite() && log_p < 0.0);
+    assert_eq!(gen.log_probability(&profile), log_p);
+
+    profile.interests.push(InterestAffinity {
+        category: InterestCategory::Immigration,
+        weight: 0.1,
+    });
+    assert_eq!(gen.log_probability(&profile), f64::NEG_INFINITY);
+}
+
+#[test]
+fn test_validate_explains_problems() {
+    let mut gen = ProfileGenerator::new(Some(21));
+    for profile in gen.generate_population(40, 0.5) {
+        assert_eq!(profile.validate(), Ok(()), "{:?}", profile.demographics);
+    }
+
+    let mut profile = gen.generate_with(&ProfileSpec::new().age(20)).unwrap();
+    profile.demographics.occupation_category = OccupationCategory::Retired;
+    let first = profile.interests[0].category.clone();
+    profile.interests.push(InterestAffinity {
+        category: first,
+        weight: 0.2,
+    });
+    let duplicate = profile.interests.len() - 1;
+
+    let issues = profile.validate().unwrap_err();
+    let retired = issues
+        .iter()
+        .find(|i| i.field == "demographics.occupation_category")
+        .unwrap();
+    assert_eq!(retired.severity, Severity::Implausible);
+    let repeated = issues
+        .iter()
+        .find(|i| i.field == format!("interests[{}].category", duplicate))
+        .unwrap();
+    assert_eq!(repeated.severity, Severity::Error);
+    assert!(!profile.is_valid());
+
+    // Implausible alone still leaves a usable profile
+    profile.interests.pop();
+    assert!(profile.validate().is_err());
+    assert!(profile.is_valid());
+}
+
+#[test]
+fn test_min_plausibility_filters_outliers() {
+    let mut gen = ProfileGenerator::new(Some(42)).with_min_plausibility(50.0);
+    for _ in 0..30 {
+        let profile = gen.generate();
+        assert!(profile.plausibility().percentile >= 50.0);
+        // The built-in generator scores the same way
+        assert_eq!(gen.plausibility(&profile), profile.plausibility());
+    }
+
+    // Other priors are ranked against profiles drawn from them
+    let gb = DemographicPriors::for_region("GB").unwrap().clone();
+    let gen = ProfileGenerator::new(Some(7)).with_priors(gb);
+    let profile = ProfileGenerator::new(Some(7)).generate();
+    let plausibility = gen.plausibility(&profile);
+    assert_eq!(plausibility.log_likelihood, gen.log_probability(&profile));
+    assert!((0.0..=100.0).contains(&plausibility.percentile));
+}
+
+#[test]
+fn test_v0_profiles_migrate_to_current_schema() {
+    let fixtures = [
+        include_str!("fixtures/profile_v0_bare_interests.json"),
+        include_str!("fixtures/profile_v0_weighted_interests.json"),
+    ];
+    for fixture in fixtures {
+        let json: serde_json::Value = serde_json::from_str(fixture).unwrap();
+        let profile = Profile::migrate(json.clone()).unwrap();
+
+        assert_eq!(profile.schema_version, SCHEMA_VERSION);
+        assert_eq!(profile.id, json["id"]);
+        assert_eq!(profile.validate(), Ok(()));
+        let total: f64 = profile.interests.iter().map(|i| i.weight).sum();
+        assert!((total - 1.0).abs() < 1e-9);
+
+        // Migrating is idempotent once current
+        let again = Profile::migrate(serde_json::to_value(&profile).unwrap()).unwrap();
+        assert_eq!(
+            serde_json::to_value(&again).unwrap(),
+            serde_json::to_value(&profile).unwrap()
+        );
+    }
+
+    let json: serde_json::Value = serde_json::from_str(fixtures[0]).unwrap();
+    let profile = Profile::migrate(json).unwrap();
+    assert!(profile.has_interest(&InterestCategory::Gardening));
+    assert!(profile.subtopics.is_empty());
+    assert_eq!(profile.interests[0].weight, 0.25);
+}
+
+/// Every field of `expected` is in `actual` with the same value; fields
+/// added since the golden file was written are ignored
+fn assert_matches_golden(expected: &serde_json::Value, actual: &serde_json::Value, path: &str) {
+    use serde_json::Value;
+    match (expected, actual) {
+        (Value::Object(expected), Value::Object(actual)) => {
+            for (key, value) in expected {
+                let path = format!("{}.{}", path, key);
+                let actual = actual
+                    .get(key)
+                    .unwrap_or_else(|| panic!("{} is missing", path));
+                assert_matches_golden(value, actual, &path);
+            }
+        }
+        (Value::Array(expected), Value::Array(actual)) => {
+            assert_eq!(expected.len(), actual.len(), "{} length", path);
+            for (i, (e, a)) in expected.iter().zip(actual).enumerate() {
+                assert_matches_golden(e, a, &format!("{}[{}]", path, i));
+            }
+        }
+        (Value::Number(e), Value::Number(a)) if e.is_f64() || a.is_f64() => {
+            let (e, a) = (e.as_f64().unwrap(), a.as_f64().unwrap());
+            assert!((e - a).abs() < 1e-9, "{}: expected {}, got {}", path, e, a);
+        }
+        _ => assert_eq!(expected, actual, "{}", path),
+    }
+}
+
+#[test]
+fn test_generator_v1_is_frozen() {
+    let golden: Vec<serde_json::Value> =
+        serde_json::from_str(include_str!("fixtures/generator_v1.json")).unwrap();
+    for case in &golden {
+        let seed = case["seed"].as_u64().unwrap();
+        let mut gen = ProfileGenerator::new_v(Some(seed), GeneratorVersion::V1);
+        let profile = match case.get("spec") {
+            Some(spec) => gen
+                .generate_with(&serde_json::from_value(spec.clone()).unwrap())
+                .unwrap(),
+            None => gen.generate(),
+        };
+        let actual = serde_json::to_value(&profile).unwrap();
+        assert_matches_golden(&case["profile"], &actual, &format!("seed {}", seed));
+    }
+
+    assert_eq!(GeneratorVersion::from_number(1), Some(GeneratorVersion::V1));
+    assert_eq!(GeneratorVersion::from_number(0), None);
+    assert_eq!(
+        GeneratorVersion::LATEST.number() as usize,
+        GeneratorVersion::ALL.len()
+    );
+}
+
+#[test]
+fn test_passphrase_reproduces_persona_across_installs() {
+    let install = || {
+        let seed =
+            PersonaSeed::derive_with_iterations("purple otter lighthouse", 2, MIN_KDF_ITERATIONS)
+                .unwrap();
+        let mut profile = ProfileGenerator::new(Some(seed.profile)).generate();
+        profile.created_at = 0;
+        let schedule = Schedule::from_profile(&profile);
+        let activities = ActivitySimulator::new(profile.clone())
+            .with_seed(seed.activity)
+            .generate_activities_from(WEDNESDAY, 6);
+        (profile, schedule, activities)
+    };
+
+    let (profile, schedule, activities) = install();
+    let (other_profile, other_schedule, other_activities) = install();
+    assert_eq!(
+        serde_json::to_value(&profile).unwrap(),
+        serde_json::to_value(&other_profile).unwrap()
+    );
+    assert_eq!(
+        serde_json::to_value(&schedule).unwrap(),
+        serde_json::to_value(&other_schedule).unwrap()
+    );
+    let urls = |activities: &[BrowsingActivity]| -> Vec<String> {
+        activities.iter().map(|a| a.url.clone()).collect()
+    };
+    assert!(!activities.is_empty());
+    assert_eq!(urls(&activities), urls(&other_activities));
+}
+
+#[test]
+fn test_generator_accepts_any_rng() {
+    use rand::SeedableRng;
+
+    let fast = |seed| ProfileGenerator::from_rng(FastRng::seed_from_u64(seed)).generate();
+    assert_eq!(fast(3).id, fast(3).id);
+    assert!(fast(3).is_valid());
+
+    let mut live = ProfileGenerator::live();
+    let (a, b) = (live.generate(), live.generate());
+    assert!(a.is_valid() && b.is_valid());
+    assert_ne!(a.id, b.id);
+}
+
+#[test]
+fn test_persona_tells_a_story_consistent_with_profile() {
+    let profile = ProfileGenerator::new(Some(7)).generate();
+    let persona = Persona::from_profile(profile.clone());
+
+    assert_eq!(persona.profile.id, profile.id);
+    assert!(persona.bio.starts_with(persona.first_name()));
+    assert!(persona.bio.contains(&persona.occupation_title));
+    assert!(persona.bio.contains(&persona.hometown));
+    assert!(!persona.employer.is_empty());
+
+    let surname = profile.name.split_whitespace().last().unwrap();
+    for member in &persona.household.members {
+        if member.relation != Relation::Roommate {
+            assert!(member.name.ends_with(surname));
+        }
+    }
+
+    let json = serde_json::to_string(&persona).unwrap();
+    let restored: Persona = serde_json::from_str(&json).unwrap();
+    assert_eq!(restored.bio, persona.bio);
+}
+
+#[test]
+fn test_german_persona_browses_like_a_german() {
+    let german = Locale::parse("de-DE").unwrap();
+    let profile = ProfileGenerator::new(Some(11))
+        .with_locale(german)
+        .generate();
+    assert_eq!(profile.demographics.locale, german);
+    assert!(profile.is_valid());
+
+    let schedule = Schedule::from_profile(&profile);
+    assert_eq!(schedule.timezone, "Europe/Berlin");
+    assert_eq!(schedule.timezone_offset, 60);
+
+    let activities = ActivitySimulator::new(profile)
+        .with_seed(3)
+        .generate_activities_from(WEDNESDAY, 48);
+    let searches: Vec<&BrowsingActivity> = activities
+        .iter()
+        .filter(|a| matches!(a.activity_type, ActivityType::Search))
+        .collect();
+    assert!(!searches.is_empty());
+    for search in &searches {
+        assert!(
+            search.url.contains("google.de")
+                || search.url.contains("ecosia.org")
+                || search.url.contains("bing.com")
+                || search.url.contains("duckduckgo.com"),
+            "{}",
+            search.url
+        );
+    }
+    assert!(activities.iter().any(|a| a.url.contains(".de/")));
+    assert!(!activities.iter().any(|a| a.url.contains("www.google.com")));
+}
+
+#[test]
+fn test_activities_come_from_the_personas_devices() {
+    let profile = ProfileGenerator::new(Some(21)).generate();
+    let devices = DeviceProfile::from_profile(&profile);
+    assert!(devices.validate().is_ok());
+    assert!(devices.primary(FormFactor::Desktop).is_some());
+    assert!(devices.primary(FormFactor::Mobile).is_some());
+
+    let activities = ActivitySimulator::new(profile)
+        .with_seed(5)
+        .generate_activities_from(WEDNESDAY, 72);
+    assert!(!activities.is_empty());
+    for activity in &activities {
+        let id = activity
+            .device_id
+            .as_deref()
+            .expect("every activity has a device");
+        let device = devices.device(id).expect("device belongs to the profile");
+        assert!(device.browser.runs_on(&device.os));
+        assert!(!device.user_agent().is_empty());
+    }
+}
+
+#[test]
+fn test_heavy_browsers_switch_between_devices() {
+    let spec = ProfileSpec::new().activity_level(ActivityLevel::VeryHigh);
+    let profile = ProfileGenerator::new(Some(6)).generate_with(&spec).unwrap();
+    let mut simulator = ActivitySimulator::new(profile).with_seed(2);
+    let activities = simulator.generate_activities_from(WEDNESDAY, 72);
+    let devices = simulator.devices();
+
+    for form_factor in [FormFactor::Desktop, FormFactor::Mobile] {
+        let id = &devices.primary(form_factor).unwrap().id;
+        let on_device: Vec<&BrowsingActivity> = activities
+            .iter()
+            .filter(|a| a.device_id.as_ref() == Some(id))
+            .collect();
+        assert!(on_device.len() > activities.len() / 10, "{:?}", form_factor);
+    }
+    assert!(activities.iter().all(|a| a.device_id.is_some()));
+}
+
+#[test]
+fn test_shared_household_interleaves_members() {
+    let mut generator = ProfileGenerator::new(Some(30));
+    let members: Vec<Profile> = (0..2).map(|_| generator.generate()).collect();
+    let mut household = SharedHousehold::new(members.clone()).with_seed(7);
+    let activities = household.generate_activities_from(WEDNESDAY, 24 * 5);
+
+    let device = household.device().id.clone();
+    assert!(device.starts_with(&members[0].id));
+    assert!(activities
+        .iter()
+        .all(|a| a.device_id.as_ref() == Some(&device)));
+    for member in &members {
+        let theirs = activities
+            .iter()
+            .filter(|a| a.profile_id.as_ref() == Some(&member.id))
+            .count();
+        assert!(theirs > 0, "{} never got the device", member.id);
+    }
+}
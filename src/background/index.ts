@@ -10,8 +10,10 @@ import type {
   MessageType,
   ExtensionConfig,
   BrowsingActivity,
+  ProfileSpec,
   Profile,
 } from "../types";
+import { SCHEMA_VERSION, Severity } from "../types";
 
 class BackgroundService {
   private wasmCore = getWasmCore();
@@ -29,6 +31,8 @@ class BackgroundService {
       console.error("Failed to initialize WASM core:", error);
     }
 
+    await this.migrateStoredProfile();
+
     // Set up message listeners
     this.setupMessageListeners();
 
@@ -44,6 +48,22 @@ class BackgroundService {
     console.log("DoubleTrack Browser: Background service initialized");
   }
 
+  /**
+   * Upgrade a profile stored by an earlier version to the current schema
+   */
+  private async migrateStoredProfile(): Promise<void> {
+    const stored = await StorageManager.getProfile();
+    if (!stored || stored.schema_version === SCHEMA_VERSION) {
+      return;
+    }
+
+    try {
+      await StorageManager.setProfile(await this.wasmCore.migrateProfile(stored));
+    } catch (error) {
+      console.error("Failed to migrate stored profile:", error);
+    }
+  }
+
   /**
    * Set up message listeners for communication with popup/options
    */
@@ -84,8 +104,23 @@ class BackgroundService {
         return { success: true };
 
       case "GENERATE_PROFILE":
-        const seed = message.payload as number | undefined;
-        const profile = await this.wasmCore.generateProfile(seed);
+        const request = message.payload as
+          | number
+          | { seed?: number; spec?: ProfileSpec; passphrase?: string; slot?: number }
+          | undefined;
+        if (typeof request === "object" && request.passphrase) {
+          const derived = await this.wasmCore.derivePersonaSeed(
+            request.passphrase,
+            request.slot ?? 0
+          );
+          request.seed = derived.profile;
+        }
+        const profile =
+          typeof request === "object" && request.spec
+            ? await this.wasmCore.generateProfileWithSpec(request.spec, request.seed)
+            : await this.wasmCore.generateProfile(
+                typeof request === "object" ? request.seed : request
+              );
         await StorageManager.setProfile(profile);
 
         // Reset statistics for new profile
@@ -93,6 +128,25 @@ class BackgroundService {
 
         return profile;
 
+      case "IMPORT_PROFILE": {
+        let imported: Profile;
+        try {
+          imported = await this.wasmCore.migrateProfile(message.payload);
+        } catch (error) {
+          const issue = { severity: Severity.Error, field: "", message: String(error) };
+          return { success: false, issues: [issue] };
+        }
+        const issues = await this.wasmCore.validateProfile(imported);
+        if (issues.some((issue) => issue.severity === Severity.Error)) {
+          return { success: false, issues };
+        }
+
+        const plausibility = await this.wasmCore.profilePlausibility(imported);
+        await StorageManager.setProfile(imported);
+        await StorageManager.clearActivityHistory();
+        return { success: true, issues, plausibility };
+      }
+
       case "GET_STATISTICS":
         return await StorageManager.getStatistics();
 
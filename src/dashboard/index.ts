@@ -477,10 +477,14 @@ class DashboardController {
     container.innerHTML = recent
       .map(
         (activity) => `
-      <div class="activity-item">
+      <div class="activity-item${activity.sensitive_cover ? " sensitive-cover" : ""}">
         <div class="activity-icon">${icons[activity.activity_type] || "📄"}</div>
         <div class="activity-content">
-          <div class="activity-title">${activity.title}</div>
+          <div class="activity-title">${activity.title}${
+            activity.sensitive_cover
+              ? ' <span class="activity-badge">Sensitive cover</span>'
+              : ""
+          }</div>
           <div class="activity-url">${activity.url}</div>
         </div>
         <div class="activity-meta">
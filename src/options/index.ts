@@ -8,7 +8,13 @@ import type {
   Statistics,
   Message,
   BrowsingActivity,
+  ValidationIssue,
+  Plausibility,
 } from "../types";
+import { Severity } from "../types";
+
+/** Imported personas less typical than this are flagged as outlandish */
+const OUTLANDISH_PERCENTILE = 5;
 
 class OptionsController {
   private config: ExtensionConfig | null = null;
@@ -58,10 +64,27 @@ class OptionsController {
       .getElementById("generate-profile-btn")
       ?.addEventListener("click", () => this.handleGenerateProfile());
 
+    document
+      .getElementById("passphrase-profile-btn")
+      ?.addEventListener("click", () => this.handleGenerateFromPassphrase());
+
     document
       .getElementById("export-profile-btn")
       ?.addEventListener("click", () => this.handleExportProfile());
 
+    document.getElementById("import-profile-btn")?.addEventListener("click", () => {
+      document.getElementById("import-profile-file")?.click();
+    });
+
+    document.getElementById("import-profile-file")?.addEventListener("change", (e) => {
+      const input = e.target as HTMLInputElement;
+      const file = input.files?.[0];
+      input.value = "";
+      if (file) {
+        this.handleImportProfile(file);
+      }
+    });
+
     // Activity actions
     document
       .getElementById("view-dashboard-btn")
@@ -137,8 +160,12 @@ class OptionsController {
       this.profile.demographics.occupation_category;
     document.getElementById("profile-education")!.textContent =
       this.profile.demographics.education_level;
-    document.getElementById("profile-interests")!.textContent =
-      this.profile.interests.join(", ");
+    document.getElementById("profile-interests")!.textContent = [
+      ...this.profile.interests,
+    ]
+      .sort((a, b) => b.weight - a.weight)
+      .map((i) => `${i.category} (${Math.round(i.weight * 100)}%)`)
+      .join(", ");
     document.getElementById("profile-style")!.textContent =
       this.profile.browsing_style;
     document.getElementById("profile-activity")!.textContent =
@@ -219,6 +246,40 @@ class OptionsController {
     }
   }
 
+  private async handleGenerateFromPassphrase(): Promise<void> {
+    const passphraseInput = document.getElementById("persona-passphrase") as HTMLInputElement;
+    const slotInput = document.getElementById("persona-slot") as HTMLInputElement;
+    const passphrase = passphraseInput.value;
+    if (!passphrase) {
+      alert("Enter a passphrase first.");
+      return;
+    }
+    if (!confirm("This will replace the current profile and clear history. Continue?")) {
+      return;
+    }
+
+    const btn = document.getElementById("passphrase-profile-btn") as HTMLButtonElement;
+    btn.textContent = "Deriving...";
+    btn.disabled = true;
+
+    try {
+      this.profile = await this.sendMessage({
+        type: "GENERATE_PROFILE",
+        payload: { passphrase, slot: Math.max(0, Math.floor(Number(slotInput.value) || 0)) },
+      });
+      passphraseInput.value = "";
+      await this.loadState();
+      this.updateUI();
+      alert("Profile generated from passphrase.");
+    } catch (error) {
+      console.error("Failed to generate profile from passphrase:", error);
+      alert("Failed to generate profile. Check console for details.");
+    } finally {
+      btn.textContent = "Generate from Passphrase";
+      btn.disabled = false;
+    }
+  }
+
   private handleExportProfile(): void {
     if (!this.profile) return;
 
@@ -234,6 +295,61 @@ class OptionsController {
     URL.revokeObjectURL(url);
   }
 
+  private async handleImportProfile(file: File): Promise<void> {
+    let imported: Profile;
+    try {
+      imported = JSON.parse(await file.text());
+    } catch (error) {
+      this.showImportIssues([
+        { severity: Severity.Error, field: "", message: `not valid JSON: ${error}` },
+      ]);
+      return;
+    }
+
+    const result: {
+      success: boolean;
+      issues: ValidationIssue[];
+      plausibility?: Plausibility;
+    } = await this.sendMessage({ type: "IMPORT_PROFILE", payload: imported });
+    this.showImportIssues(result.issues);
+
+    if (result.success) {
+      await this.loadState();
+      this.updateUI();
+      const percentile = result.plausibility?.percentile ?? 100;
+      if (result.issues.length > 0) {
+        alert("Profile imported, but some details look implausible.");
+      } else if (percentile < OUTLANDISH_PERCENTILE) {
+        alert(
+          `Profile imported, but it is less typical than ${Math.round(100 - percentile)}% ` +
+            "of generated personas, which may make its traffic easier to dismiss as noise."
+        );
+      } else {
+        alert("Profile imported successfully!");
+      }
+    }
+  }
+
+  private showImportIssues(issues: ValidationIssue[]): void {
+    const list = document.getElementById("import-issues");
+    if (!list) return;
+
+    list.innerHTML = "";
+    for (const issue of issues) {
+      const item = document.createElement("li");
+      item.className = issue.severity === Severity.Error ? "error" : "implausible";
+      if (issue.field) {
+        const field = document.createElement("span");
+        field.className = "issue-field";
+        field.textContent = `${issue.field} `;
+        item.appendChild(field);
+      }
+      item.appendChild(document.createTextNode(issue.message));
+      list.appendChild(item);
+    }
+    list.style.display = issues.length > 0 ? "block" : "none";
+  }
+
   private handleViewDashboard(): void {
     // Open dashboard in a new tab
     chrome.tabs.create({
@@ -136,8 +136,12 @@ class PopupController {
       this.profile.demographics.age.toString();
     document.getElementById("profile-occupation")!.textContent =
       this.profile.demographics.occupation_category;
-    document.getElementById("profile-interests")!.textContent =
-      this.profile.interests.join(", ");
+    document.getElementById("profile-interests")!.textContent = [
+      ...this.profile.interests,
+    ]
+      .sort((a, b) => b.weight - a.weight)
+      .map((i) => `${i.category} (${Math.round(i.weight * 100)}%)`)
+      .join(", ");
     document.getElementById("profile-activity-level")!.textContent =
       this.profile.activity_level;
   }
@@ -2,22 +2,57 @@
  * Type definitions for DoubleTrack Browser
  */
 
+/** Version of the profile JSON the WASM core writes */
+export const SCHEMA_VERSION = 2;
+
 export interface Profile {
+  /** Absent on profiles stored before versioning; see WasmCore.migrateProfile */
+  schema_version?: number;
   id: string;
   name: string;
   demographics: Demographics;
-  interests: InterestCategory[];
+  interests: InterestAffinity[];
+  /** Specific subtopic ids within `interests`, e.g. "programming/rust" */
+  subtopics?: string[];
   browsing_style: BrowsingStyle;
   activity_level: ActivityLevel;
   created_at: number;
 }
 
+/**
+ * An interest and its share of the profile's time; weights sum to 1
+ */
+export interface InterestAffinity {
+  category: InterestCategory;
+  weight: number;
+}
+
 export interface Demographics {
   age: number;
   gender: Gender;
   location_type: LocationType;
   occupation_category: OccupationCategory;
   education_level: EducationLevel;
+  /** Where the persona lives and the language it browses in */
+  locale: Locale;
+}
+
+export interface Locale {
+  country: Country;
+  language: Language;
+}
+
+export enum Country {
+  US = "US",
+  GB = "GB",
+  DE = "DE",
+  FR = "FR",
+}
+
+export enum Language {
+  En = "en",
+  De = "de",
+  Fr = "fr",
 }
 
 export enum Gender {
@@ -74,6 +109,11 @@ export enum InterestCategory {
   Photography = "Photography",
   Programming = "Programming",
   DataScience = "DataScience",
+  // Only used as opt-in cover traffic, never as profile interests
+  HealthConditions = "HealthConditions",
+  MentalHealth = "MentalHealth",
+  LegalAdvice = "LegalAdvice",
+  Immigration = "Immigration",
 }
 
 export enum BrowsingStyle {
@@ -90,6 +130,158 @@ export enum ActivityLevel {
   VeryHigh = "VeryHigh",
 }
 
+/**
+ * Constraints for generating a profile; anything left out is drawn as usual
+ */
+export interface ProfileSpec {
+  /** Inclusive [min, max] age */
+  age_range?: [number, number];
+  gender?: Gender;
+  location_type?: LocationType;
+  occupation?: OccupationCategory;
+  education_level?: EducationLevel;
+  required_interests?: InterestCategory[];
+  forbidden_interests?: InterestCategory[];
+  browsing_style?: BrowsingStyle;
+  activity_level?: ActivityLevel;
+}
+
+export enum AgeBand {
+  Young = "Young",
+  Adult = "Adult",
+  MiddleAged = "MiddleAged",
+  Senior = "Senior",
+}
+
+/**
+ * What is known about the real user, for generating a contrasting persona
+ */
+export interface ProfileSummary {
+  age_band?: AgeBand;
+  occupation?: OccupationCategory;
+  /** Most prominent first */
+  top_interests?: InterestCategory[];
+}
+
+export enum Severity {
+  /** Usable, but not a person anyone would believe in */
+  Implausible = "Implausible",
+  /** The profile can't be used as it is */
+  Error = "Error",
+}
+
+/**
+ * One problem found when validating a profile
+ */
+export interface ValidationIssue {
+  severity: Severity;
+  /** Path into the profile, e.g. "interests[2].category" */
+  field: string;
+  message: string;
+}
+
+/**
+ * Seeds for one persona, derived from a passphrase and slot
+ */
+export interface PersonaSeed {
+  profile: number;
+  activity: number;
+}
+
+/**
+ * A profile with the life story that explains it
+ */
+export interface Persona {
+  profile: Profile;
+  occupation_title: string;
+  employer: string;
+  household: Household;
+  hometown: string;
+  /** One per interest, most favoured first */
+  hobbies: Hobby[];
+  bio: string;
+}
+
+export type HouseholdKind =
+  | "Alone"
+  | "Partner"
+  | "PartnerAndChildren"
+  | "SingleParent"
+  | "WithParents"
+  | "Roommates";
+
+export interface Household {
+  kind: HouseholdKind;
+  members: HouseholdMember[];
+}
+
+export interface HouseholdMember {
+  relation: "Partner" | "Child" | "Parent" | "Roommate";
+  name: string;
+  age: number;
+}
+
+export interface Hobby {
+  interest: InterestCategory;
+  subtopic: string | null;
+  description: string;
+}
+
+/**
+ * The devices a persona browses on, for setting headers consistently
+ */
+export interface DeviceProfile {
+  /** A desktop or laptop, then a phone, then any tablet or second laptop */
+  devices: Device[];
+  /** Preferred languages, most preferred first, e.g. "de-DE" */
+  languages: string[];
+  /** IANA name, e.g. "Europe/Berlin" */
+  timezone: string;
+  /** Minutes east of UTC, in standard time; summer time follows `timezone` */
+  utc_offset_minutes: number;
+  /** Share of browsing done on the phone, 0-1, as on the phone itself */
+  mobile_share: number;
+}
+
+export interface Device {
+  id: string;
+  form_factor: "Desktop" | "Mobile" | "Tablet";
+  os: OperatingSystem;
+  browser: Browser;
+  /** Major versions the browser reports; headers should use one of these */
+  browser_versions: { min: number; max: number };
+  /** Viewport in CSS pixels */
+  screen: { width: number; height: number; pixel_ratio: number };
+  /** Share of the persona's browsing done on this device, 0-1 */
+  share: number;
+}
+
+export type OperatingSystem =
+  | "Windows"
+  | "MacOS"
+  | "Linux"
+  | "ChromeOS"
+  | "IOS"
+  | "Android";
+
+export type Browser =
+  | "Chrome"
+  | "Safari"
+  | "Firefox"
+  | "Edge"
+  | "Opera"
+  | "SamsungInternet";
+
+/**
+ * How typical a profile is of generated ones
+ */
+export interface Plausibility {
+  /** Negative infinity for a profile the generator could never draw */
+  log_likelihood: number;
+  /** Share of generated profiles no more likely than this one, 0-100 */
+  percentile: number;
+}
+
 export interface BrowsingActivity {
   activity_type: ActivityType;
   url: string;
@@ -97,6 +289,14 @@ export interface BrowsingActivity {
   duration_seconds: number;
   timestamp: number;
   interest_category: InterestCategory | null;
+  /** Whether the safety policy allows actually loading `url` */
+  safe_to_load?: boolean;
+  /** Injected by the sensitive-topic cover mode */
+  sensitive_cover?: boolean;
+  /** Id of the device in the profile's `DeviceProfile` it came from */
+  device_id?: string | null;
+  /** Id of the profile browsing; on a shared device, any household member */
+  profile_id?: string | null;
 }
 
 export enum ActivityType {
@@ -109,9 +309,52 @@ export enum ActivityType {
   Research = "Research",
 }
 
+/**
+ * Opt-in sensitive-topic cover traffic; omitted fields take the defaults
+ */
+export interface SensitiveCover {
+  /** Fraction of activities replaced by a sensitive decoy (default 0.1) */
+  rate?: number;
+  topics?: InterestCategory[];
+}
+
+export interface CoverQuery {
+  category: InterestCategory;
+  query: string;
+}
+
+/**
+ * Local sitemap, RSS or Atom document used to seed decoy content offline
+ */
+export interface FeedSource {
+  category: InterestCategory;
+  xml: string;
+}
+
+export type RiskCategory = "Adult" | "Gambling" | "Extremist" | "Malware";
+
+export type UrlVerdict = "Allow" | "SimulateOnly" | "Deny";
+
+/**
+ * Rules applied to every generated URL; omitted fields take the defaults
+ */
+export interface SafetyPolicy {
+  /** Never emitted, whatever `allow_domains` says; all by default */
+  blocked_categories?: RiskCategory[];
+  deny_domains?: string[];
+  /** Safe to load; never overrides `blocked_categories` */
+  allow_domains?: string[];
+  path_rules?: { pattern: string; verdict: UrlVerdict }[];
+  load_only_allowlisted?: boolean;
+}
+
 export interface Schedule {
+  /** Hours are local to `timezone` */
   time_patterns: TimePattern[];
+  /** Minutes east of UTC, in standard time */
   timezone_offset: number;
+  /** IANA name, e.g. "Europe/Berlin" */
+  timezone: string;
 }
 
 export interface TimePattern {
@@ -174,6 +417,7 @@ export enum MessageType {
   GetCurrentProfile = "GET_CURRENT_PROFILE",
   SimulateActivity = "SIMULATE_ACTIVITY",
   ClearHistory = "CLEAR_HISTORY",
+  ImportProfile = "IMPORT_PROFILE",
 }
 
 /**
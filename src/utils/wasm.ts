@@ -1,4 +1,22 @@
-import type { Profile, BrowsingActivity, Schedule } from "../types";
+import type {
+  Profile,
+  ProfileSpec,
+  ProfileSummary,
+  BrowsingActivity,
+  Schedule,
+  ValidationIssue,
+  Plausibility,
+  PersonaSeed,
+  Persona,
+  DeviceProfile,
+} from "../types";
+import { Country, Language, SCHEMA_VERSION } from "../types";
+
+/** Domain-separation string of the passphrase seed derivation */
+const SEED_DOMAIN = "doubletrack.persona-seed.v1";
+
+/** PBKDF2 rounds unless configured otherwise */
+const DEFAULT_KDF_ITERATIONS = 600_000;
 
 /**
  * Wrapper for the WASM module
@@ -56,37 +74,336 @@ export class WasmCore {
     return this.mockGenerateProfile(seed);
   }
 
+  /**
+   * Generate a profile the way a given generator version did, so a shared
+   * seed keeps reproducing the same persona across releases
+   */
+  async generateProfileVersioned(seed: number, version: number): Promise<Profile> {
+    await this.ensureInitialized();
+
+    // Placeholder implementation
+    // In production: return this.wasm.generate_profile_versioned(seed, version);
+
+    if (version !== 1) {
+      throw new Error(`unknown generator version ${version}`);
+    }
+    return this.mockGenerateProfile(seed);
+  }
+
+  /**
+   * Generate a profile living in a locale such as "de-DE", or a bare
+   * country code such as "FR"
+   */
+  async generateProfileForLocale(locale: string, seed?: number): Promise<Profile> {
+    await this.ensureInitialized();
+
+    // Placeholder implementation
+    // In production: return this.wasm.generate_profile_for_locale(locale, seed);
+
+    const [language, country] = locale.includes("-")
+      ? locale.split("-")
+      : [undefined, locale];
+    const profile = this.mockGenerateProfile(seed);
+    const countries: Record<string, Language> = {
+      US: Language.En,
+      GB: Language.En,
+      DE: Language.De,
+      FR: Language.Fr,
+    };
+    const code = country.toUpperCase();
+    if (!(code in countries)) {
+      throw new Error(`unsupported locale ${locale}`);
+    }
+    profile.demographics.locale = {
+      country: code as Country,
+      language: (language?.toLowerCase() as Language) ?? countries[code],
+    };
+    return profile;
+  }
+
+  /**
+   * Derive the seeds for a persona slot from a passphrase
+   *
+   * Deliberately slow. Every install given the same passphrase, slot and
+   * iteration count gets the same seeds.
+   */
+  async derivePersonaSeed(
+    passphrase: string,
+    slot: number,
+    iterations = DEFAULT_KDF_ITERATIONS
+  ): Promise<PersonaSeed> {
+    await this.ensureInitialized();
+
+    // Placeholder implementation, following the same derivation format
+    // In production: return this.wasm.derive_persona_seed(passphrase, slot, iterations);
+
+    if (!passphrase) {
+      throw new Error("passphrase is empty");
+    }
+    const encoder = new TextEncoder();
+    const domain = encoder.encode(SEED_DOMAIN);
+    const salt = new Uint8Array(domain.length + 5);
+    salt.set(domain);
+    new DataView(salt.buffer).setUint32(domain.length + 1, slot);
+
+    const key = await crypto.subtle.importKey(
+      "raw",
+      encoder.encode(passphrase),
+      "PBKDF2",
+      false,
+      ["deriveBits"]
+    );
+    const bits = await crypto.subtle.deriveBits(
+      { name: "PBKDF2", hash: "SHA-256", salt, iterations },
+      key,
+      256
+    );
+    const view = new DataView(bits);
+    const seed = (offset: number) =>
+      Number(view.getBigUint64(offset, true) & ((1n << 53n) - 1n));
+    return { profile: seed(0), activity: seed(8) };
+  }
+
+  /**
+   * Generate a profile that satisfies a spec
+   *
+   * Rejects with a message when the constraints contradict each other.
+   */
+  async generateProfileWithSpec(spec: ProfileSpec, seed?: number): Promise<Profile> {
+    await this.ensureInitialized();
+
+    // Placeholder implementation
+    // In production: return this.wasm.generate_profile_with_spec(spec, seed);
+
+    const profile = this.mockGenerateProfile(seed);
+    if (spec.age_range) {
+      profile.demographics.age = spec.age_range[0];
+    }
+    if (spec.occupation) {
+      profile.demographics.occupation_category = spec.occupation;
+    }
+    if (spec.activity_level) {
+      profile.activity_level = spec.activity_level;
+    }
+    return profile;
+  }
+
+  /**
+   * Generate a persona as unlike the real user as plausibly possible
+   */
+  async generateContrastingProfile(
+    summary: ProfileSummary,
+    seed?: number
+  ): Promise<Profile> {
+    await this.ensureInitialized();
+
+    // Placeholder implementation
+    // In production: return this.wasm.generate_contrasting_profile(summary, seed);
+
+    return this.mockGenerateProfile(seed);
+  }
+
+  /**
+   * Tell the life story behind a profile; the same profile always gets the
+   * same story
+   */
+  async personaForProfile(profile: Profile): Promise<Persona> {
+    await this.ensureInitialized();
+
+    // Placeholder implementation
+    // In production: return this.wasm.persona_for_profile(profile);
+
+    const { age, occupation_category } = profile.demographics;
+    const occupation_title = occupation_category.toLowerCase();
+    const employer = "a local company";
+    return {
+      profile,
+      occupation_title,
+      employer,
+      household: { kind: "Alone", members: [] },
+      hometown: "a small town",
+      hobbies: [],
+      bio: `${profile.name.split(" ")[0]} is a ${age}-year-old ${occupation_title} at ${employer}.`,
+    };
+  }
+
+  /**
+   * The devices a profile browses on; the same profile always gets the
+   * same devices
+   */
+  async deviceProfileForProfile(profile: Profile): Promise<DeviceProfile> {
+    await this.ensureInitialized();
+
+    // Placeholder implementation
+    // In production: return this.wasm.device_profile_for_profile(profile);
+
+    const { timezone, timezone_offset } = this.mockGetSchedule(profile);
+    const { country, language } = profile.demographics.locale ?? {
+      country: Country.US,
+      language: Language.En,
+    };
+    return {
+      devices: [
+        {
+          id: `${profile.id}-1`,
+          form_factor: "Desktop",
+          os: "Windows",
+          browser: "Chrome",
+          browser_versions: { min: 152, max: 153 },
+          screen: { width: 1920, height: 1080, pixel_ratio: 1 },
+          share: 0.5,
+        },
+        {
+          id: `${profile.id}-2`,
+          form_factor: "Mobile",
+          os: "Android",
+          browser: "Chrome",
+          browser_versions: { min: 152, max: 153 },
+          screen: { width: 412, height: 915, pixel_ratio: 2.625 },
+          share: 0.5,
+        },
+      ],
+      languages: [`${language}-${country}`],
+      timezone,
+      utc_offset_minutes: timezone_offset,
+      mobile_share: 0.5,
+    };
+  }
+
+  /**
+   * Generate `n` personas spread across demographics and interests
+   *
+   * `diversity` runs from 0 (independent draws) to 1.
+   */
+  async generatePopulation(
+    n: number,
+    diversity: number,
+    seed?: number
+  ): Promise<Profile[]> {
+    await this.ensureInitialized();
+
+    // Placeholder implementation
+    // In production: return this.wasm.generate_population(n, diversity, seed);
+
+    return Array.from({ length: n }, (_, i) =>
+      this.mockGenerateProfile(seed === undefined ? undefined : seed + i)
+    );
+  }
+
   /**
    * Generate browsing activities for a profile
    */
   async generateActivities(
     profile: Profile,
-    durationHours: number
+    durationHours: number,
+    seed?: number
   ): Promise<BrowsingActivity[]> {
     await this.ensureInitialized();
 
     // Placeholder implementation
-    // In production: return this.wasm.generate_activities(profile, durationHours);
+    // In production: return this.wasm.generate_activities(profile, durationHours, seed);
 
     return this.mockGenerateActivities(profile, durationHours);
   }
 
   /**
-   * Validate a profile
+   * Generate the browsing on one device shared by several profiles, which
+   * take turns at it
    */
-  async validateProfile(profile: Profile): Promise<boolean> {
+  async generateHouseholdActivities(
+    profiles: Profile[],
+    durationHours: number,
+    seed?: number
+  ): Promise<BrowsingActivity[]> {
     await this.ensureInitialized();
 
     // Placeholder implementation
-    // In production: return this.wasm.validate_profile(profile);
+    // In production: return this.wasm.generate_household_activities(profiles, durationHours, seed);
 
-    return (
-      profile.name !== "" &&
-      profile.interests.length > 0 &&
-      profile.demographics.age >= 18
+    if (profiles.length === 0) {
+      throw new Error("a household needs at least one member");
+    }
+    const device_id = `${profiles[0].id}-1`;
+    return this.mockGenerateActivities(profiles[0], durationHours).map(
+      (activity, i) => {
+        // Members take turns of an hour each
+        const profile = profiles[Math.floor(i / 4) % profiles.length];
+        return { ...activity, device_id, profile_id: profile.id };
+      }
     );
   }
 
+  /**
+   * Upgrade a stored profile of any earlier schema version to the current one
+   *
+   * Rejects with a message when the JSON isn't a profile or comes from a
+   * newer version.
+   */
+  async migrateProfile(json: any): Promise<Profile> {
+    await this.ensureInitialized();
+
+    // Placeholder implementation
+    // In production: return this.wasm.migrate_profile(json);
+
+    const version = json?.schema_version ?? 0;
+    if (version > SCHEMA_VERSION) {
+      throw new Error(
+        `schema version ${version} is newer than the supported ${SCHEMA_VERSION}`
+      );
+    }
+    const interests = Array.isArray(json?.interests) ? json.interests : [];
+    return {
+      ...json,
+      schema_version: SCHEMA_VERSION,
+      demographics: {
+        locale: { country: Country.US, language: Language.En },
+        ...json?.demographics,
+      },
+      interests: interests.map((interest: any) =>
+        typeof interest === "string"
+          ? { category: interest, weight: 1 / interests.length }
+          : interest
+      ),
+      subtopics: json?.subtopics ?? [],
+    };
+  }
+
+  /**
+   * Validate a profile, returning every issue found; empty means valid
+   */
+  async validateProfile(profile: Profile): Promise<ValidationIssue[]> {
+    await this.ensureInitialized();
+
+    // Placeholder implementation
+    // In production: return this.wasm.validate_profile(profile);
+
+    const issues: ValidationIssue[] = [];
+    const error = (field: string, message: string) =>
+      issues.push({ severity: "Error" as any, field, message });
+    if (!profile?.name) {
+      error("name", "is empty");
+    }
+    if (!(profile?.demographics?.age >= 18 && profile.demographics.age <= 100)) {
+      error("demographics.age", "is outside 18-100");
+    }
+    if (!Array.isArray(profile?.interests) || profile.interests.length === 0) {
+      error("interests", "has no entries");
+    }
+    return issues;
+  }
+
+  /**
+   * Score how typical a profile is of generated ones
+   */
+  async profilePlausibility(profile: Profile): Promise<Plausibility> {
+    await this.ensureInitialized();
+
+    // Placeholder implementation
+    // In production: return this.wasm.profile_plausibility(profile);
+
+    return { log_likelihood: -20, percentile: 50 };
+  }
+
   /**
    * Get activity schedule for a profile
    */
@@ -117,6 +434,7 @@ export class WasmCore {
     const name = names[seed ? seed % names.length : Math.floor(Math.random() * names.length)];
 
     return {
+      schema_version: SCHEMA_VERSION,
       id,
       name,
       demographics: {
@@ -125,8 +443,13 @@ export class WasmCore {
         location_type: "Urban" as any,
         occupation_category: "Technology" as any,
         education_level: "Bachelor" as any,
+        locale: { country: Country.US, language: Language.En },
       },
-      interests: ["Technology", "Gaming", "Programming"] as any[],
+      interests: [
+        { category: "Technology" as any, weight: 0.6 },
+        { category: "Gaming" as any, weight: 0.25 },
+        { category: "Programming" as any, weight: 0.15 },
+      ],
       browsing_style: "Explorer" as any,
       activity_level: "Medium" as any,
       created_at: Math.floor(Date.now() / 1000),
@@ -153,7 +476,10 @@ export class WasmCore {
         title: `Example Page ${i}`,
         duration_seconds: 60 + Math.floor(Math.random() * 300),
         timestamp,
-        interest_category: profile.interests[0] || null,
+        interest_category: profile.interests[0]?.category || null,
+        // Phone in the evening, desktop otherwise
+        device_id: `${profile.id}-${new Date(timestamp * 1000).getHours() >= 18 ? 2 : 1}`,
+        profile_id: profile.id,
       });
     }
 
@@ -164,9 +490,18 @@ export class WasmCore {
    * Mock schedule generation (to be replaced with actual WASM call)
    */
   private mockGetSchedule(profile: Profile): Schedule {
+    const timezones: Record<Country, [string, number]> = {
+      [Country.US]: ["America/New_York", -300],
+      [Country.GB]: ["Europe/London", 0],
+      [Country.DE]: ["Europe/Berlin", 60],
+      [Country.FR]: ["Europe/Paris", 60],
+    };
+    const [timezone, offset] =
+      timezones[profile.demographics.locale?.country ?? Country.US];
     return {
       time_patterns: [],
-      timezone_offset: 0,
+      timezone_offset: offset,
+      timezone,
     };
   }
 }